@@ -0,0 +1,413 @@
+use crate::analysis::{partition, type_check, CycleDetector, FsmValidator};
+use crate::analysis::cycles::DEFAULT_MAX_CYCLES;
+use crate::fsm::FunctionBlock;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Check of the `analyze` subcommand, as named on the command line and in
+/// the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Cycles,
+    Unreachable,
+    DeadStates,
+    Signatures,
+    Safety,
+    Types,
+    SingleEntry,
+    SelfCompare,
+    Resets,
+    Partition,
+    ComputedTargets,
+    Interface,
+    Nondeterminism,
+    Masked,
+    Duplicates,
+}
+
+impl Check {
+    /// Every check, in the order `analyze` reports them
+    pub const ALL: [Check; 15] = [
+        Check::Unreachable,
+        Check::DeadStates,
+        Check::Cycles,
+        Check::Safety,
+        Check::Types,
+        Check::SingleEntry,
+        Check::SelfCompare,
+        Check::Partition,
+        Check::ComputedTargets,
+        Check::Nondeterminism,
+        Check::Masked,
+        Check::Duplicates,
+        Check::Interface,
+        Check::Resets,
+        Check::Signatures,
+    ];
+}
+
+/// One issue a check reports about a function block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+/// A structural check run over each function block by `FsmAnalyzer`; implement
+/// it to add project-specific rules alongside the built-in ones
+pub trait FsmCheck {
+    /// Heading the findings are reported under
+    fn name(&self) -> &str;
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding>;
+}
+
+/// States no initial state leads to
+pub struct UnreachableStates;
+
+impl FsmCheck for UnreachableStates {
+    fn name(&self) -> &str {
+        "Unreachable states"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        FsmValidator::find_unreachable_states(fb)
+            .into_iter()
+            .map(|state| Finding::new(format!("State {}", state)))
+            .collect()
+    }
+}
+
+/// States without outgoing transitions
+pub struct DeadStates;
+
+impl FsmCheck for DeadStates {
+    fn name(&self) -> &str {
+        "Dead-end states"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        FsmValidator::find_dead_states(fb)
+            .into_iter()
+            .map(|state| Finding::new(format!("State {}", state)))
+            .collect()
+    }
+}
+
+/// Elementary cycles, summarized once more than `max_cycles`
+/// (`DEFAULT_MAX_CYCLES` when `None`) exist
+pub struct Cycles {
+    pub max_cycles: Option<usize>,
+}
+
+impl FsmCheck for Cycles {
+    fn name(&self) -> &str {
+        "Cycles"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        let report = CycleDetector::cycle_report(fb, Some(self.max_cycles.unwrap_or(DEFAULT_MAX_CYCLES)));
+        if let Some(summary) = report.summary() {
+            return vec![Finding::new(summary)];
+        }
+
+        report.cycles
+            .iter()
+            .map(|cycle| Finding::new(format!("{} → {}", cycle.join(" → "), cycle[0])))
+            .collect()
+    }
+}
+
+/// States from which none of `safe_states` can be reached
+pub struct Safety {
+    pub safe_states: HashSet<String>,
+}
+
+impl FsmCheck for Safety {
+    fn name(&self) -> &str {
+        "States unable to reach a safe state"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        if self.safe_states.is_empty() {
+            return vec![Finding::new("No safe states given (use --safe-states)")];
+        }
+
+        FsmValidator::find_safety_violations(fb, &self.safe_states)
+            .into_iter()
+            .map(|state| Finding::new(format!("State {}", state)))
+            .collect()
+    }
+}
+
+/// Variables compared against values of incompatible types
+pub struct TypeConsistency;
+
+impl FsmCheck for TypeConsistency {
+    fn name(&self) -> &str {
+        "Inconsistently typed variables"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        type_check::find_type_inconsistent_variables(fb)
+            .into_iter()
+            .map(|(variable, conditions)| Finding::new(format!("{}: {}", variable, conditions.join(" vs "))))
+            .collect()
+    }
+}
+
+/// Blocks without exactly one initial state
+pub struct SingleEntry;
+
+impl FsmCheck for SingleEntry {
+    fn name(&self) -> &str {
+        "Entry problems"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        let report = FsmValidator::check_single_entry(fb);
+        if report.has_no_entry() {
+            vec![Finding::new("No initial state: every state has an incoming transition")]
+        } else if report.has_multiple_entries() {
+            vec![Finding::new(format!("Multiple initial states: {}", report.initial_states.join(", ")))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Guard conditions comparing a variable with itself
+pub struct SelfComparisons;
+
+impl FsmCheck for SelfComparisons {
+    fn name(&self) -> &str {
+        "Self-comparisons"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        FsmValidator::find_self_comparisons(fb)
+            .into_iter()
+            .map(|found| {
+                let outcome = if found.tautology { "always true" } else { "always false" };
+                Finding::new(format!("{}: {} ({})", found.transition_id, found.condition, outcome))
+            })
+            .collect()
+    }
+}
+
+/// States whose outgoing guards leave input combinations unhandled, with
+/// examples of such inputs
+pub struct GuardPartition;
+
+impl FsmCheck for GuardPartition {
+    fn name(&self) -> &str {
+        "Unhandled inputs"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        partition::check_guard_partition(fb)
+            .into_iter()
+            .map(|report| {
+                let examples: Vec<String> = report.uncovered_examples
+                    .iter()
+                    .map(partition::PartitionReport::format_example)
+                    .collect();
+                let truncated = if report.enumeration_truncated { " (not all combinations tried)" } else { "" };
+                Finding::new(format!("State {}{}: e.g. {}", report.state_id, truncated, examples.join("; ")))
+            })
+            .collect()
+    }
+}
+
+/// Transitions whose target state is computed at runtime
+pub struct ComputedTargets;
+
+impl FsmCheck for ComputedTargets {
+    fn name(&self) -> &str {
+        "Computed targets"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        fb.transitions
+            .iter()
+            .filter(|t| t.computed_target)
+            .map(|t| Finding::new(format!("{} → {}", t.from_state, t.target_expression.as_deref().unwrap_or_default())))
+            .collect()
+    }
+}
+
+/// Declared inputs no guard reads and guard variables the block doesn't declare
+pub struct Interface;
+
+impl FsmCheck for Interface {
+    fn name(&self) -> &str {
+        "Interface mismatches"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        let report = FsmValidator::check_interface_usage(fb);
+        let unused = report.unused_inputs
+            .iter()
+            .map(|input| Finding::new(format!("Unused input {}", input)));
+        let undeclared = report.undeclared_variables
+            .iter()
+            .map(|variable| Finding::new(format!("Undeclared variable {}", variable)));
+        unused.chain(undeclared).collect()
+    }
+}
+
+/// States with transitions whose guards can hold at the same time
+pub struct Nondeterminism;
+
+impl FsmCheck for Nondeterminism {
+    fn name(&self) -> &str {
+        "Nondeterministic states"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        FsmValidator::find_nondeterministic_transitions(fb)
+            .into_iter()
+            .map(|(state_id, indices)| {
+                let enabled: Vec<String> = indices
+                    .iter()
+                    .map(|&idx| &fb.transitions[idx])
+                    .map(|t| format!("→ {} when {}", t.to_state, t.condition_label("TRUE")))
+                    .collect();
+                Finding::new(format!("State {}: {}", state_id, enabled.join(", ")))
+            })
+            .collect()
+    }
+}
+
+/// Branches an earlier branch of the same IF/ELSIF chain always pre-empts
+pub struct MaskedTransitions;
+
+impl FsmCheck for MaskedTransitions {
+    fn name(&self) -> &str {
+        "Masked transitions"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        FsmValidator::find_masked_transitions(fb)
+            .into_iter()
+            .map(|idx| &fb.transitions[idx])
+            .map(|t| Finding::new(format!("State {}: → {} when {}", t.from_state, t.to_state, t.condition_label("TRUE"))))
+            .collect()
+    }
+}
+
+/// Transitions repeating an earlier one, and transitions sharing an earlier
+/// one's id despite a different guard
+pub struct DuplicateTransitions;
+
+impl FsmCheck for DuplicateTransitions {
+    fn name(&self) -> &str {
+        "Duplicate transitions"
+    }
+
+    fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+        let describe = |idx: usize| {
+            let t = &fb.transitions[idx];
+            format!("{} → {} when {}", t.from_state, t.to_state, t.condition_label("TRUE"))
+        };
+        let duplicates = FsmValidator::find_duplicate_transitions(fb)
+            .into_iter()
+            .map(|idx| Finding::new(format!("{} repeats an earlier transition", describe(idx))));
+        let collisions = FsmValidator::find_transition_id_collisions(fb)
+            .into_iter()
+            .map(|idx| Finding::new(format!("{} reuses id {}", describe(idx), fb.transitions[idx].id)));
+        duplicates.chain(collisions).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{AnalysisOptions, FsmAnalyzer};
+    use crate::fsm::{State, Transition};
+
+    /// Flags states whose id isn't a multiple of ten
+    struct OffGridStates;
+
+    impl FsmCheck for OffGridStates {
+        fn name(&self) -> &str {
+            "Off-grid states"
+        }
+
+        fn run(&self, fb: &FunctionBlock) -> Vec<Finding> {
+            fb.states
+                .keys()
+                .filter(|id| id.parse::<u32>().is_ok_and(|n| n % 10 != 0))
+                .map(|id| Finding::new(format!("State {}", id)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_custom_check_reported() {
+        let mut fb = FunctionBlock::new("GridFB".to_string(), "state".to_string());
+        for id in ["10", "15", "20"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "15".to_string(), "a = TRUE".to_string()));
+        fb.add_transition(Transition::new("15".to_string(), "20".to_string(), "b = TRUE".to_string()));
+
+        let analyzer = FsmAnalyzer::new().with_checks(vec![Box::new(OffGridStates)]);
+        let options = AnalysisOptions { checks: vec![Check::DeadStates], ..AnalysisOptions::default() };
+        let report = analyzer.run_checks(&fb, &options);
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0], ("Dead-end states".to_string(), vec![Finding::new("State 20")]));
+        assert_eq!(report[1], ("Off-grid states".to_string(), vec![Finding::new("State 15")]));
+    }
+
+    #[test]
+    fn test_duplicate_and_colliding_transitions_reported() {
+        let mut fb = FunctionBlock::new("ValveFB".to_string(), "state".to_string());
+        for id in ["10", "20"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "open = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "open = TRUE".to_string()));
+        // As in imported JSON, where ids are not made unique
+        fb.transitions.push(Transition::new("10".to_string(), "20".to_string(), "fault = TRUE".to_string()));
+
+        assert_eq!(
+            DuplicateTransitions.run(&fb),
+            [
+                Finding::new("10 → 20 when open = TRUE repeats an earlier transition"),
+                Finding::new("10 → 20 when fault = TRUE reuses id 10_to_20"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checks_reported_in_fixed_order() {
+        let mut fb = FunctionBlock::new("ValveFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "open = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "open = TRUE AND fault = TRUE".to_string()));
+
+        let options = AnalysisOptions {
+            checks: vec![Check::Nondeterminism, Check::SelfCompare, Check::Signatures],
+            ..AnalysisOptions::default()
+        };
+        let report = FsmAnalyzer::new().run_checks(&fb, &options);
+
+        let names: Vec<&str> = report.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Self-comparisons", "Nondeterministic states"]);
+        assert!(report[0].1.is_empty());
+        assert_eq!(
+            report[1].1,
+            [Finding::new("State 10: → 20 when open = TRUE, → 30 when open = TRUE AND fault = TRUE")]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::analysis::FsmValidator;
+use crate::fsm::FunctionBlock;
+use std::collections::{HashMap, VecDeque};
+
+/// State-id walks that together take every transition of `fb` at least once.
+/// Walks start from the initial state and greedily take an untaken outgoing
+/// transition, otherwise the shortest route to the nearest state that has
+/// one. When nothing untaken can be reached, a new walk starts from the
+/// initial state, or from the source of a transition it can't reach.
+pub fn coverage_walks(fb: &FunctionBlock) -> Vec<Vec<String>> {
+    let outgoing = fb.outgoing_transitions();
+    let mut untaken: Vec<bool> = vec![true; fb.transitions.len()];
+    let initial = FsmValidator::initial_state(fb);
+    let mut walks = Vec::new();
+
+    while let Some(first_untaken) = untaken.iter().position(|&u| u) {
+        let start = initial
+            .as_deref()
+            .filter(|initial| route_to_untaken(fb, &outgoing, &untaken, initial).is_some())
+            .unwrap_or(&fb.transitions[first_untaken].from_state);
+
+        let mut walk = vec![start.to_string()];
+        while let Some(route) = route_to_untaken(fb, &outgoing, &untaken, walk.last().unwrap()) {
+            for idx in route {
+                untaken[idx] = false;
+                walk.push(fb.transitions[idx].to_state.clone());
+            }
+        }
+        walks.push(walk);
+    }
+
+    walks
+}
+
+/// Transitions from `from` ending with an untaken one: that transition alone
+/// when `from` has one, else a shortest route to a state that does
+fn route_to_untaken(
+    fb: &FunctionBlock,
+    outgoing: &HashMap<String, Vec<usize>>,
+    untaken: &[bool],
+    from: &str,
+) -> Option<Vec<usize>> {
+    let mut reached_by: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        let transitions = outgoing.get(current).map_or(&[][..], Vec::as_slice);
+        if let Some(&next) = transitions.iter().find(|&&idx| untaken[idx]) {
+            let mut route = vec![next];
+            let mut state = current;
+            while let Some(Some(idx)) = reached_by.get(state) {
+                route.push(*idx);
+                state = &fb.transitions[*idx].from_state;
+            }
+            route.reverse();
+            return Some(route);
+        }
+
+        for &idx in transitions {
+            let next = fb.transitions[idx].to_state.as_str();
+            if !reached_by.contains_key(next) {
+                reached_by.insert(next, Some(idx));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::cyclic_block;
+    use crate::fsm::Transition;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_walks_cover_every_transition() {
+        let fb = cyclic_block();
+        let walks = coverage_walks(&fb);
+
+        let taken: HashSet<(&str, &str)> = walks
+            .iter()
+            .flat_map(|walk| walk.windows(2).map(|pair| (pair[0].as_str(), pair[1].as_str())))
+            .collect();
+        for transition in &fb.transitions {
+            assert!(taken.contains(&(transition.from_state.as_str(), transition.to_state.as_str())));
+        }
+        assert!(walks.iter().all(|walk| walk.len() > 1));
+
+        // Coming back round the cycle picks up the remaining branch in the same walk
+        let mut branched = cyclic_block();
+        branched.add_transition(Transition::new("10".to_string(), "30".to_string(), "skip = TRUE".to_string()));
+        let walks = coverage_walks(&branched);
+        assert_eq!(walks.len(), 1);
+        assert_eq!(walks[0], ["10", "20", "30", "10", "30"]);
+    }
+}
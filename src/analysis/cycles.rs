@@ -1,12 +1,53 @@
 use crate::fsm::FunctionBlock;
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::algo::kosaraju_scc;
+use petgraph::algo::{condensation, is_cyclic_directed, kosaraju_scc};
 use std::collections::HashMap;
 
+/// Elementary cycles of a block, possibly cut short by a cap
+#[derive(Debug, Clone)]
+pub struct CycleReport {
+    pub cycles: Vec<Vec<String>>,
+    /// Enumeration stopped at the cap; more cycles exist
+    pub truncated: bool,
+    /// Number of states in the largest strongly connected component
+    pub largest_scc: usize,
+}
+
+impl CycleReport {
+    /// Replacement for the full listing when the cap was hit
+    pub fn summary(&self) -> Option<String> {
+        self.truncated.then(|| format!(
+            "showing {} of many; machine is highly cyclic (largest strongly connected component: {} states)",
+            self.cycles.len(),
+            self.largest_scc
+        ))
+    }
+}
+
+/// Cycles enumerated when the caller sets no cap of its own; a highly
+/// cyclic block has exponentially many
+pub const DEFAULT_MAX_CYCLES: usize = 1000;
+
 pub struct CycleDetector;
 
 impl CycleDetector {
-    pub fn find_cycles(fsm: &FunctionBlock) -> Vec<Vec<String>> {
+    /// Strongly connected components of the transition graph, as state ids
+    pub fn strongly_connected_components(fsm: &FunctionBlock) -> Vec<Vec<String>> {
+        let (graph, index_map) = Self::build_graph(fsm);
+
+        kosaraju_scc(&graph)
+            .iter()
+            .map(|scc| scc.iter().filter_map(|idx| index_map.get(idx).cloned()).collect())
+            .collect()
+    }
+
+    /// DAG with one node per strongly connected component, holding its state
+    /// ids, and an edge wherever a transition leads from one component to another
+    pub fn condensation(fsm: &FunctionBlock) -> DiGraph<Vec<String>, ()> {
+        condensation(Self::build_graph(fsm).0, true)
+    }
+
+    fn build_graph(fsm: &FunctionBlock) -> (DiGraph<String, ()>, HashMap<NodeIndex, String>) {
         let mut graph = DiGraph::new();
         let mut node_map: HashMap<String, NodeIndex> = HashMap::new();
         let mut index_map: HashMap<NodeIndex, String> = HashMap::new();
@@ -26,32 +67,225 @@ impl CycleDetector {
             }
         }
 
-        // Find strongly connected components
-        let sccs = kosaraju_scc(&graph);
+        (graph, index_map)
+    }
+
+    /// Cycles of the transition graph in traversal order: each state steps to
+    /// the next by a transition and the last one back to the first. Self-loops
+    /// are single-state cycles. Stops after `DEFAULT_MAX_CYCLES`.
+    pub fn find_cycles(fsm: &FunctionBlock) -> Vec<Vec<String>> {
+        Self::find_elementary_cycles_limited(fsm, Some(DEFAULT_MAX_CYCLES)).0
+    }
+
+    /// Enumerate every elementary (simple) cycle, each rooted at its earliest state.
+    /// Exponential on densely connected blocks; prefer `find_cycles`.
+    pub fn find_elementary_cycles(fsm: &FunctionBlock) -> Vec<Vec<String>> {
+        Self::find_elementary_cycles_limited(fsm, None).0
+    }
+
+    /// Elementary cycles, stopping once `limit` have been found.
+    /// The flag is set when enumeration was cut short.
+    pub fn find_elementary_cycles_limited(fsm: &FunctionBlock, limit: Option<usize>) -> (Vec<Vec<String>>, bool) {
+        let limit = limit.unwrap_or(usize::MAX);
+        let states: Vec<&String> = fsm.states.keys().collect();
+        let index_of: HashMap<&str, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); states.len()];
+        for transition in &fsm.transitions {
+            if let (Some(&from), Some(&to)) =
+                (index_of.get(transition.from_state.as_str()), index_of.get(transition.to_state.as_str()))
+                && !successors[from].contains(&to) {
+                successors[from].push(to);
+            }
+        }
 
-        // Filter out single-node SCCs without self-loops
         let mut cycles = Vec::new();
-        for scc in sccs {
-            if scc.len() > 1 {
-                let cycle: Vec<String> = scc.iter()
-                    .filter_map(|idx| index_map.get(idx).cloned())
-                    .collect();
-                cycles.push(cycle);
-            } else if scc.len() == 1 {
-                // Check for self-loop
-                let node = scc[0];
-                if graph.find_edge(node, node).is_some() {
-                    if let Some(state_id) = index_map.get(&node) {
-                        cycles.push(vec![state_id.clone()]);
-                    }
+        let mut truncated = false;
+        for start in 0..states.len() {
+            let mut path = vec![start];
+            let mut on_path = vec![false; states.len()];
+            on_path[start] = true;
+            truncated = Self::walk_cycles(start, start, &successors, &mut path, &mut on_path, &mut cycles, limit);
+            if truncated {
+                break;
+            }
+        }
+
+        let cycles = cycles
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|idx| states[idx].clone()).collect())
+            .collect();
+
+        (cycles, truncated)
+    }
+
+    pub fn cycle_report(fsm: &FunctionBlock, limit: Option<usize>) -> CycleReport {
+        let (cycles, truncated) = Self::find_elementary_cycles_limited(fsm, limit);
+        let largest_scc = Self::strongly_connected_components(fsm)
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+
+        CycleReport {
+            cycles,
+            truncated,
+            largest_scc,
+        }
+    }
+
+    // Only visits states after `start` so each cycle is reported exactly once.
+    // Returns true if another cycle turned up after `limit` was reached.
+    fn walk_cycles(
+        start: usize,
+        current: usize,
+        successors: &[Vec<usize>],
+        path: &mut Vec<usize>,
+        on_path: &mut [bool],
+        cycles: &mut Vec<Vec<usize>>,
+        limit: usize,
+    ) -> bool {
+        for &next in &successors[current] {
+            if next == start {
+                if cycles.len() >= limit {
+                    return true;
+                }
+                cycles.push(path.clone());
+            } else if next > start && !on_path[next] {
+                path.push(next);
+                on_path[next] = true;
+                let truncated = Self::walk_cycles(start, next, successors, path, on_path, cycles, limit);
+                on_path[next] = false;
+                path.pop();
+                if truncated {
+                    return true;
                 }
             }
         }
 
-        cycles
+        false
+    }
+
+    /// Number of elementary cycles each cycle-involved state takes part in,
+    /// counted over the cycles `find_cycles` returns
+    pub fn cycle_membership(fsm: &FunctionBlock) -> HashMap<String, usize> {
+        Self::membership_of(&Self::find_cycles(fsm))
+    }
+
+    pub fn membership_of(cycles: &[Vec<String>]) -> HashMap<String, usize> {
+        let mut membership = HashMap::new();
+
+        for cycle in cycles {
+            for state_id in cycle {
+                *membership.entry(state_id.clone()).or_insert(0) += 1;
+            }
+        }
+
+        membership
     }
 
     pub fn is_acyclic(fsm: &FunctionBlock) -> bool {
-        Self::find_cycles(fsm).is_empty()
+        !is_cyclic_directed(&Self::build_graph(fsm).0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{cyclic_block, linear_block};
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_cycles_are_ordered_edge_sequences() {
+        let mut fb = FunctionBlock::new("CyclicFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "reset = true".to_string()));
+        fb.add_transition(Transition::new("40".to_string(), "40".to_string(), "hold = TRUE".to_string()));
+
+        let cycles = CycleDetector::find_cycles(&fb);
+        assert_eq!(cycles, [vec!["10", "20", "30"], vec!["40"]]);
+        for cycle in &cycles {
+            for (i, from) in cycle.iter().enumerate() {
+                let to = &cycle[(i + 1) % cycle.len()];
+                assert!(fb.transitions.iter().any(|t| &t.from_state == from && &t.to_state == to));
+            }
+        }
+        assert!(!CycleDetector::is_acyclic(&fb));
+    }
+
+    #[test]
+    fn test_sccs_and_condensation() {
+        let cyclic = cyclic_block();
+        let sccs = CycleDetector::strongly_connected_components(&cyclic);
+        assert_eq!(sccs.len(), 1);
+        let mut members = sccs[0].clone();
+        members.sort();
+        assert_eq!(members, ["10", "20", "30"]);
+        let dag = CycleDetector::condensation(&cyclic);
+        assert_eq!((dag.node_count(), dag.edge_count()), (1, 0));
+
+        let linear = linear_block();
+        let sccs = CycleDetector::strongly_connected_components(&linear);
+        assert_eq!(sccs.len(), 3);
+        assert!(sccs.iter().all(|scc| scc.len() == 1));
+        let dag = CycleDetector::condensation(&linear);
+        assert_eq!((dag.node_count(), dag.edge_count()), (3, 2));
+        assert!(!is_cyclic_directed(&dag));
+    }
+
+    #[test]
+    fn test_max_cycles_on_complete_digraph() {
+        let mut fb = FunctionBlock::new("MeshFB".to_string(), "state".to_string());
+        let ids = ["10", "20", "30", "40"];
+        for id in ids {
+            fb.add_state(State::new(id.to_string()));
+        }
+        for from in ids {
+            for to in ids.iter().filter(|to| **to != from) {
+                fb.add_transition(Transition::new(from.to_string(), to.to_string(), format!("go{} = TRUE", to)));
+            }
+        }
+
+        assert_eq!(CycleDetector::find_elementary_cycles(&fb).len(), 20);
+
+        let report = CycleDetector::cycle_report(&fb, Some(5));
+        assert_eq!(report.cycles.len(), 5);
+        assert!(report.truncated);
+        assert_eq!(report.largest_scc, 4);
+
+        let summary = report.summary().unwrap();
+        assert!(summary.contains("showing 5 of many; machine is highly cyclic"));
+        assert!(summary.contains("4 states"));
+
+        assert!(CycleDetector::cycle_report(&fb, Some(20)).summary().is_none());
+    }
+
+    #[test]
+    fn test_find_cycles_is_capped() {
+        let mut fb = FunctionBlock::new("MeshFB".to_string(), "state".to_string());
+        let ids: Vec<String> = (1..=8).map(|i| (i * 10).to_string()).collect();
+        for id in &ids {
+            fb.add_state(State::new(id.clone()));
+        }
+        for from in &ids {
+            for to in ids.iter().filter(|to| *to != from) {
+                fb.add_transition(Transition::new(from.clone(), to.clone(), format!("go{} = TRUE", to)));
+            }
+        }
+
+        // A complete digraph on 8 states has 16064 elementary cycles
+        assert_eq!(CycleDetector::find_cycles(&fb).len(), DEFAULT_MAX_CYCLES);
+        let stats = crate::analysis::FsmStatistics::analyze(&fb);
+        assert_eq!(stats.cycles.len(), DEFAULT_MAX_CYCLES);
+        assert!(stats.cycles_truncated);
+        assert!(stats.summary_line("MeshFB").contains("1000+ cycles"));
     }
-}
\ No newline at end of file
+}
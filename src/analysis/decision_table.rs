@@ -0,0 +1,108 @@
+use crate::analysis::signatures::{Condition, SignatureGenerator};
+use crate::fsm::FunctionBlock;
+use serde::{Deserialize, Serialize};
+
+/// Placeholder for a variable that a row does not constrain
+pub const DONT_CARE: &str = "-";
+
+/// One DNF conjunct of an outgoing guard and the state it leads to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionRow {
+    /// Required value per column, in the same order as `DecisionTable::variables`
+    pub cells: Vec<String>,
+    pub target_state: String,
+}
+
+/// Outgoing guards of a single state laid out as a decision table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionTable {
+    pub state_id: String,
+    pub variables: Vec<String>,
+    pub rows: Vec<DecisionRow>,
+}
+
+impl DecisionTable {
+    /// Build the decision table for the outgoing transitions of `state_id`
+    pub fn build(fsm: &FunctionBlock, state_id: &str) -> Self {
+        let mut variables: Vec<String> = Vec::new();
+        let mut conjuncts: Vec<(Vec<Condition>, String)> = Vec::new();
+
+        for transition in fsm.transitions.iter().filter(|t| t.from_state == state_id) {
+            for conjunct in SignatureGenerator::parse_transition_condition(transition.condition.as_deref().unwrap_or_default()) {
+                for cond in &conjunct {
+                    if !variables.contains(&cond.variable) {
+                        variables.push(cond.variable.clone());
+                    }
+                }
+                conjuncts.push((conjunct, transition.to_state.clone()));
+            }
+        }
+
+        variables.sort();
+
+        let rows = conjuncts
+            .into_iter()
+            .map(|(conjunct, target_state)| DecisionRow {
+                cells: variables
+                    .iter()
+                    .map(|var| Self::format_cell(&conjunct, var))
+                    .collect(),
+                target_state,
+            })
+            .collect();
+
+        Self {
+            state_id: state_id.to_string(),
+            variables,
+            rows,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// `=` constraints show the bare value, other operators keep their symbol
+    fn format_cell(conjunct: &[Condition], variable: &str) -> String {
+        let parts: Vec<String> = conjunct
+            .iter()
+            .filter(|c| c.variable == variable)
+            .map(|c| if c.operator == "=" {
+                c.shown_value().to_string()
+            } else {
+                format!("{} {}", c.operator, c.shown_value())
+            })
+            .collect();
+
+        if parts.is_empty() {
+            DONT_CARE.to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_decision_table_columns_and_dont_cares() {
+        let mut fb = FunctionBlock::new("DecisionFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("30".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "a = 1".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "a = 0 AND b > 5".to_string()));
+
+        let table = DecisionTable::build(&fb, "10");
+
+        assert_eq!(table.variables, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0].cells, vec!["1".to_string(), DONT_CARE.to_string()]);
+        assert_eq!(table.rows[0].target_state, "20");
+        assert_eq!(table.rows[1].cells, vec!["0".to_string(), "> 5".to_string()]);
+        assert_eq!(table.rows[1].target_state, "30");
+    }
+}
@@ -0,0 +1,168 @@
+use crate::fsm::{FunctionBlock, Transition};
+use std::collections::HashMap;
+
+/// Outgoing transitions of a state as (label, block of the target), sorted
+type Behavior = Vec<(String, Option<usize>)>;
+
+/// Copy of `fb` with behaviorally equivalent states merged, found by partition
+/// refinement. States start out grouped by their actions and terminal flag
+/// (parents of nested CASEs stay on their own), and groups are split until
+/// every member takes the same transitions, labelled by guard and actions, into
+/// the same groups. A merged state's id joins its members' ids with `|`.
+pub fn minimize(fb: &FunctionBlock) -> FunctionBlock {
+    let states: Vec<&String> = fb.states.keys().collect();
+    let outgoing = fb.outgoing_transitions();
+
+    // Block of each state, by position in `states`
+    let mut block: Vec<usize> = {
+        let mut keys: HashMap<String, usize> = HashMap::new();
+        states
+            .iter()
+            .map(|id| {
+                let state = &fb.states[*id];
+                let is_parent = fb.sub_machines.iter().any(|sub| sub.parent_state.as_ref() == Some(*id));
+                let key = format!(
+                    "{}|{}|{}",
+                    state.terminal,
+                    state.actions.join(";"),
+                    if is_parent { id.as_str() } else { "" }
+                );
+                let next = keys.len();
+                *keys.entry(key).or_insert(next)
+            })
+            .collect()
+    };
+    let index_of: HashMap<&str, usize> = states.iter().enumerate().map(|(idx, id)| (id.as_str(), idx)).collect();
+
+    loop {
+        let mut keys: HashMap<(usize, Behavior), usize> = HashMap::new();
+        let refined: Vec<usize> = states
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| {
+                let mut behavior: Behavior = outgoing
+                    .get(id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .map(|&t| {
+                        let transition = &fb.transitions[t];
+                        let target = index_of.get(transition.to_state.as_str()).map(|&to| block[to]);
+                        (transition_label(transition), target)
+                    })
+                    .collect();
+                behavior.sort();
+                behavior.dedup();
+                let next = keys.len();
+                *keys.entry((block[idx], behavior)).or_insert(next)
+            })
+            .collect();
+
+        let stable = keys.len() == block.iter().max().map_or(0, |max| max + 1);
+        block = refined;
+        if stable {
+            break;
+        }
+    }
+
+    // Merged ids, members in block order
+    let mut members: HashMap<usize, Vec<&str>> = HashMap::new();
+    for (idx, id) in states.iter().enumerate() {
+        members.entry(block[idx]).or_default().push(id);
+    }
+    let merged_id = |state: &str| -> String {
+        match index_of.get(state) {
+            Some(&idx) => members[&block[idx]].join("|"),
+            None => state.to_string(),
+        }
+    };
+
+    let mut minimized = FunctionBlock::new(fb.name.clone(), fb.case_variable.clone());
+    minimized.parent_state = fb.parent_state.clone();
+    minimized.declared_inputs = fb.declared_inputs.clone();
+    minimized.declared_variables = fb.declared_variables.clone();
+    minimized.init_transition = fb.init_transition.clone().map(|init| {
+        let to = merged_id(&init.to_state);
+        retarget(init, None, to)
+    });
+    minimized.sub_machines = fb.sub_machines
+        .iter()
+        .map(|sub| {
+            let mut sub = sub.clone();
+            sub.parent_state = sub.parent_state.map(|parent| merged_id(&parent));
+            sub
+        })
+        .collect();
+
+    // The first member stands in for its block: the others behave the same
+    let representatives: Vec<usize> = (0..states.len())
+        .filter(|&idx| members[&block[idx]][0] == states[idx].as_str())
+        .collect();
+    for &idx in &representatives {
+        let mut state = fb.states[states[idx]].clone();
+        state.id = merged_id(states[idx]);
+        state.transitions_in.clear();
+        state.transitions_out.clear();
+        minimized.add_state(state);
+    }
+    for &idx in &representatives {
+        for &t in outgoing.get(states[idx].as_str()).into_iter().flatten() {
+            let transition = fb.transitions[t].clone();
+            let from = merged_id(&transition.from_state);
+            let to = merged_id(&transition.to_state);
+            let duplicate = minimized.transitions.iter().any(|existing| {
+                existing.from_state == from
+                    && existing.to_state == to
+                    && transition_label(existing) == transition_label(&transition)
+            });
+            if !duplicate {
+                minimized.add_transition(retarget(transition, Some(from), to));
+            }
+        }
+    }
+
+    minimized
+}
+
+/// What distinguishes two transitions between the same blocks
+fn transition_label(transition: &Transition) -> String {
+    format!(
+        "{} / {}",
+        transition.condition.as_deref().unwrap_or_default().trim(),
+        transition.actions.join("; ")
+    )
+}
+
+fn retarget(mut transition: Transition, from: Option<String>, to: String) -> Transition {
+    if let Some(from) = from {
+        transition.from_state = from;
+    }
+    transition.to_state = to;
+    transition.id = format!("{}_to_{}", transition.from_state, transition.to_state);
+    transition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::State;
+
+    #[test]
+    fn test_equivalent_sinks_merged() {
+        let mut fb = FunctionBlock::new("SortFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.states.get_mut("40").unwrap().actions.push("alarm := 1".to_string());
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "part = small".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "part = large".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "40".to_string(), "part = unknown".to_string()));
+
+        let minimized = minimize(&fb);
+        let ids: Vec<&str> = minimized.states.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["10", "20|30", "40"]);
+        assert_eq!(minimized.case_variable, "state");
+        assert_eq!(minimized.transition_count(), 3);
+        assert_eq!(minimized.states["20|30"].transitions_in.len(), 2);
+        assert!(minimized.transitions.iter().all(|t| minimized.states.contains_key(&t.to_state)));
+    }
+}
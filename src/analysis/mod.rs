@@ -1,26 +1,96 @@
 pub mod validator;
+pub mod checks;
 pub mod cycles;
 pub mod stats;
 pub mod signatures;
+pub mod decision_table;
+pub mod signature_diff;
+pub mod type_check;
+pub mod partition;
+pub mod coverage;
+pub mod minimize;
 
-use crate::fsm::FiniteStateMachine;
+use crate::error::FsmError;
+use crate::fsm::{FiniteStateMachine, FunctionBlock, Transition};
 use colored::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // Re-export all public types
-pub use cycles::CycleDetector;
+pub use checks::{Check, Finding, FsmCheck};
+pub use cycles::{CycleDetector, CycleReport};
+pub use decision_table::{DecisionRow, DecisionTable};
 pub use signatures::{
+    evaluate_guard,
+    ConditionExpr,
+    Inconsistency,
+    MatchMode,
+    PathFinder,
+    PathFinderOptions,
+    PathSignature,
+    ProcessingLimits,
     SignatureGenerator,
+    SignatureOptions,
     StateSignatureTable,
 };
-pub use stats::FsmStatistics;
-pub use validator::FsmValidator;
+pub use signature_diff::{SignatureChange, SignatureDiff};
+pub use stats::{AggregateStatistics, BlockStatus, FsmStatistics};
+pub use type_check::ValueKind;
+pub use validator::{EntryReport, FsmValidator, InterfaceReport, SelfComparison};
 
-pub struct FsmAnalyzer;
+#[derive(Default)]
+pub struct FsmAnalyzer {
+    signature_options: SignatureOptions,
+    /// Run on every block in addition to the built-in checks
+    checks: Vec<Box<dyn FsmCheck>>,
+}
 
 impl FsmAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_signature_options(mut self, options: SignatureOptions) -> Self {
+        self.signature_options = options;
+        self
+    }
+
+    /// Register custom checks, reported after the built-in ones
+    pub fn with_checks(mut self, checks: Vec<Box<dyn FsmCheck>>) -> Self {
+        self.checks.extend(checks);
+        self
+    }
+
+    /// Findings of each enabled built-in check and every registered check, by name
+    pub fn run_checks(&self, fb: &FunctionBlock, options: &AnalysisOptions) -> Vec<(String, Vec<Finding>)> {
+        let built_in: Vec<Box<dyn FsmCheck>> = Check::ALL
+            .into_iter()
+            .filter(|check| options.has_check(*check))
+            .filter_map(|check| -> Option<Box<dyn FsmCheck>> {
+                Some(match check {
+                    Check::Unreachable => Box::new(checks::UnreachableStates),
+                    Check::DeadStates => Box::new(checks::DeadStates),
+                    Check::Cycles => Box::new(checks::Cycles { max_cycles: options.max_cycles }),
+                    Check::Safety => Box::new(checks::Safety { safe_states: options.safe_states.clone() }),
+                    Check::Types => Box::new(checks::TypeConsistency),
+                    Check::SingleEntry => Box::new(checks::SingleEntry),
+                    Check::SelfCompare => Box::new(checks::SelfComparisons),
+                    Check::Partition => Box::new(checks::GuardPartition),
+                    Check::ComputedTargets => Box::new(checks::ComputedTargets),
+                    Check::Nondeterminism => Box::new(checks::Nondeterminism),
+                    Check::Masked => Box::new(checks::MaskedTransitions),
+                    Check::Duplicates => Box::new(checks::DuplicateTransitions),
+                    Check::Interface => Box::new(checks::Interface),
+                    // Reports rather than pass/fail checks, printed by `analyze_and_report`
+                    Check::Resets | Check::Signatures => return None,
+                })
+            })
+            .collect();
+
+        built_in
+            .iter()
+            .chain(&self.checks)
+            .map(|check| (check.name().to_string(), check.run(fb)))
+            .collect()
     }
 
     pub fn analyze_all(&self, fsm: &FiniteStateMachine) -> HashMap<String, FsmStatistics> {
@@ -36,16 +106,121 @@ impl FsmAnalyzer {
 
     /// Generate state signatures for all function blocks
     pub fn generate_signatures(&self, fsm: &FiniteStateMachine)
-                               -> HashMap<String, StateSignatureTable>
+                               -> Result<HashMap<String, StateSignatureTable>, FsmError>
     {
         let mut results = HashMap::new();
 
         for fb in &fsm.function_blocks {
-            let signature_table = SignatureGenerator::generate(fb);
+            let signature_table = SignatureGenerator::generate_with_options(fb, &self.signature_options)?;
             results.insert(fb.name.clone(), signature_table);
         }
 
-        results
+        Ok(results)
+    }
+
+    /// Lay out the outgoing guards of `state_id` as a decision table
+    pub fn decision_table(&self, fb: &FunctionBlock, state_id: &str) -> DecisionTable {
+        DecisionTable::build(fb, state_id)
+    }
+
+    /// States from which none of the `safe` states can be reached
+    pub fn can_always_reach(&self, fb: &FunctionBlock, safe: &HashSet<String>) -> Vec<String> {
+        FsmValidator::find_safety_violations(fb, safe)
+    }
+
+    /// Transitions back into the block's initial state (resets/restarts)
+    pub fn reset_transitions<'a>(&self, fb: &'a FunctionBlock) -> Vec<&'a Transition> {
+        let Some(initial) = FsmValidator::initial_state(fb) else {
+            return Vec::new();
+        };
+        fb.transitions
+            .iter()
+            .filter(|t| t.to_state == initial && t.from_state != initial)
+            .collect()
+    }
+
+    /// States from which the initial state can't be reached, so the machine
+    /// never recovers once there
+    pub fn states_without_reset_path(&self, fb: &FunctionBlock) -> Vec<String> {
+        let Some(initial) = FsmValidator::initial_state(fb) else {
+            return Vec::new();
+        };
+        FsmValidator::find_safety_violations(fb, &HashSet::from([initial]))
+    }
+
+    /// State-id walks from the initial state that together take every
+    /// transition at least once, as a basis for transition-coverage tests
+    pub fn generate_coverage_sequences(&self, fb: &FunctionBlock) -> Vec<Vec<String>> {
+        coverage::coverage_walks(fb)
+    }
+
+    /// Reduced copy of `fb` with behaviorally equivalent states merged into
+    /// one whose id joins theirs with `|`
+    pub fn minimize(&self, fb: &FunctionBlock) -> FunctionBlock {
+        minimize::minimize(fb)
+    }
+
+    /// Transitions whose next state is only known at runtime
+    pub fn computed_target_transitions<'a>(&self, fb: &'a FunctionBlock) -> Vec<&'a Transition> {
+        fb.transitions.iter().filter(|t| t.computed_target).collect()
+    }
+
+    /// Variables compared against values of incompatible types (e.g. `flag = TRUE`
+    /// and `flag > 5`), with the conflicting conditions, across all blocks
+    pub fn find_type_inconsistent_variables(&self, fsm: &FiniteStateMachine) -> Vec<(String, Vec<String>)> {
+        fsm.function_blocks
+            .iter()
+            .flat_map(type_check::find_type_inconsistent_variables)
+            .collect()
+    }
+
+    fn report_path(&self, fb: &FunctionBlock, from: &str, to: &str) {
+        let missing: Vec<&str> = [from, to].into_iter().filter(|s| !fb.states.contains_key(*s)).collect();
+        if !missing.is_empty() {
+            println!("{} No state {} in this block", "⚠".yellow(), missing.join(" or "));
+            return;
+        }
+
+        let Some(route) = FsmValidator::shortest_path_transitions(fb, from, to) else {
+            println!("{} State {} can't be reached from {}", "⚠".yellow(), to.red(), from);
+            return;
+        };
+
+        let mut states = vec![from];
+        states.extend(route.iter().map(|&idx| fb.transitions[idx].to_state.as_str()));
+        println!("{} Route {}", "✓".green(), states.join(" → "));
+        for transition in route.iter().map(|&idx| &fb.transitions[idx]) {
+            println!("  - {} → {} when {}", transition.from_state, transition.to_state, transition.condition_label("TRUE"));
+        }
+
+        let guards: Vec<&str> = route.iter().filter_map(|&idx| fb.transitions[idx].condition.as_deref()).collect();
+        if !guards.is_empty() {
+            let conjoined: Vec<String> = guards.iter().map(|g| format!("({})", g)).collect();
+            println!("  Conditions: {}", conjoined.join(" AND "));
+        }
+    }
+
+    fn report_reach(&self, fb: &FunctionBlock, target: &str) {
+        if !fb.states.contains_key(target) {
+            println!("{} No state {} in this block", "⚠".yellow(), target);
+            return;
+        }
+
+        let Some((states, conditions)) = PathFinder::reach_sequence(fb, target) else {
+            println!("{} State {} can't be reached from an initial state", "⚠".yellow(), target.red());
+            return;
+        };
+
+        println!("{} Reach {} via {}", "✓".green(), target, states.join(" → "));
+        let signature = PathSignature::new(conditions, 0);
+        if signature.conditions.is_empty() {
+            println!("  Conditions: none");
+        } else {
+            println!("  Conditions: {}", signature.format_conditions());
+        }
+        if !signature.is_satisfiable() {
+            println!("{} These conditions can't all hold at once", "⚠".yellow());
+        }
     }
 
     pub fn analyze_and_report(&self, fsm: &FiniteStateMachine, options: &AnalysisOptions) -> anyhow::Result<()> {
@@ -53,70 +228,128 @@ impl FsmAnalyzer {
             println!("\n{}", format!("Analyzing Function Block: {}", fb.name).bold().blue());
             println!("{}", "=".repeat(50));
 
-            if options.check_unreachable {
-                let unreachable = FsmValidator::find_unreachable_states(fb);
-                if !unreachable.is_empty() {
-                    println!("{} Unreachable states found:", "⚠".yellow());
-                    for state in &unreachable {
-                        println!("  - State {}", state.red());
+            for (name, findings) in self.run_checks(fb, options) {
+                if !findings.is_empty() {
+                    println!("{} {} found:", "⚠".yellow(), name);
+                    for finding in &findings {
+                        println!("  - {}", finding.message.red());
                     }
                 } else {
-                    println!("{} No unreachable states", "✓".green());
+                    println!("{} No {}", "✓".green(), name.to_lowercase());
                 }
             }
 
-            if options.check_dead_states {
-                let dead = FsmValidator::find_dead_states(fb);
-                if !dead.is_empty() {
-                    println!("{} Dead-end states found:", "⚠".yellow());
-                    for state in &dead {
-                        println!("  - State {}", state.red());
+            if let Some((from, to)) = &options.path {
+                self.report_path(fb, from, to);
+            }
+
+            if let Some(target) = &options.reach {
+                self.report_reach(fb, target);
+            }
+
+            if options.coverage {
+                let walks = self.generate_coverage_sequences(fb);
+                if !walks.is_empty() {
+                    println!("{} Walks covering all {} transition(s):", "✓".green(), fb.transition_count());
+                    for walk in &walks {
+                        println!("  - {}", walk.join(" → "));
                     }
                 } else {
-                    println!("{} No dead-end states", "✓".green());
+                    println!("{} No transitions to cover", "⚠".yellow());
                 }
             }
 
-            if options.check_cycles {
-                let cycles = CycleDetector::find_cycles(fb);
-                if !cycles.is_empty() {
-                    println!("{} Cycles detected:", "ℹ".blue());
-                    for cycle in &cycles {
-                        println!("  - {}", cycle.join(" → "));
+            if options.has_check(Check::Resets)
+                && let Some(initial) = FsmValidator::initial_state(fb) {
+                let resets = self.reset_transitions(fb);
+                if !resets.is_empty() {
+                    println!("{} Reset transitions to initial state {}:", "✓".green(), initial);
+                    for transition in &resets {
+                        match &transition.condition {
+                            Some(guard) => println!("  - {} → {} when {}", transition.from_state, transition.to_state, guard),
+                            None => println!("  - {} → {}", transition.from_state, transition.to_state),
+                        }
                     }
                 } else {
-                    println!("{} No cycles detected", "✓".green());
+                    println!("{} No transitions back to initial state {}", "⚠".yellow(), initial);
+                }
+
+                let stuck = self.states_without_reset_path(fb);
+                if !stuck.is_empty() {
+                    println!("{} States that can never return to initial state {}:", "⚠".yellow(), initial);
+                    for state in &stuck {
+                        println!("  - State {}", state.red());
+                    }
+                } else {
+                    println!("{} Every state can return to initial state {}", "✓".green(), initial);
                 }
             }
 
             // Show signatures if enabled
-            if options.show_signatures {
-                let signature_table = SignatureGenerator::generate(fb);
+            if options.has_check(Check::Signatures) {
+                let signature_table = SignatureGenerator::generate_with_options(fb, &self.signature_options)?;
                 println!("\n{}", "State Signatures:".bold());
-                for (state_id, sig) in &signature_table.signatures {
+                // Block order, so repeated runs print the same listing
+                for (state_id, sig) in fb.states.keys().filter_map(|id| Some((id, signature_table.get_signature(id)?))) {
                     println!("  State {}: {}",
                              state_id.cyan(),
                              sig.format_conditions()
                     );
                 }
+
+                let partial = signature_table.partial_states();
+                if !partial.is_empty() {
+                    println!("{} Signatures for cyclic states are partial: {}", "⚠".yellow(), partial.join(", "));
+                }
+
+                let contradictory: usize = signature_table.signatures.values().map(|s| s.contradictory_paths).sum();
+                if contradictory > 0 {
+                    let verb = if self.signature_options.drop_contradictory { "Dropped" } else { "Found" };
+                    println!("{} {} {} contradictory path signature(s)", "⚠".yellow(), verb, contradictory);
+                }
+
+                let limited = signature_table.limited_states();
+                if !limited.is_empty() {
+                    println!("{} Path limits truncated signatures of: {}", "⚠".yellow(), limited.join(", "));
+                }
             }
 
             // Always show statistics
-            let stats = FsmStatistics::analyze(fb);
+            let stats = FsmStatistics::analyze_with_cycle_limit(fb, options.max_cycles);
             println!("\n{}", "Statistics:".bold());
             println!("  Total states: {}", stats.total_states);
             println!("  Total transitions: {}", stats.total_transitions);
             println!("  Avg transitions per state: {:.2}", stats.avg_transitions_per_state);
             println!("  Max transitions from state: {}", stats.max_transitions_from_state);
+            println!("  Max transitions into state: {}", stats.max_transitions_into_state);
+            println!("  Strongly connected components: {}", stats.scc_count);
+            println!("  Cyclomatic complexity: {}", stats.cyclomatic_complexity);
+            println!("  Max depth from start: {}", stats.max_depth);
+            println!("  Deterministic: {}", if stats.is_deterministic { "yes" } else { "no" });
         }
 
         Ok(())
     }
 }
 
+#[derive(Default)]
 pub struct AnalysisOptions {
-    pub check_cycles: bool,
-    pub check_unreachable: bool,
-    pub check_dead_states: bool,
-    pub show_signatures: bool,
-}
\ No newline at end of file
+    /// Checks and reports to run on each block
+    pub checks: Vec<Check>,
+    /// Stop elementary-cycle enumeration after this many cycles
+    pub max_cycles: Option<usize>,
+    /// States `Check::Safety` requires to stay reachable
+    pub safe_states: HashSet<String>,
+    /// Print a shortest route between these two states and the guards along it
+    pub path: Option<(String, String)>,
+    /// Print state walks that together take every transition
+    pub coverage: bool,
+    /// Print a shortest route from the initial state to this state and the conditions it needs
+    pub reach: Option<String>,
+}
+
+impl AnalysisOptions {
+    pub fn has_check(&self, check: Check) -> bool {
+        self.checks.contains(&check)
+    }
+}
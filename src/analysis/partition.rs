@@ -0,0 +1,163 @@
+use crate::analysis::signatures::{Condition, PathSignature, SignatureGenerator};
+use crate::fsm::FunctionBlock;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// Input combinations tried per state before giving up
+const MAX_COMBINATIONS: usize = 4096;
+
+/// Uncovered combinations kept per state
+const MAX_EXAMPLES: usize = 5;
+
+/// A state whose outgoing guards leave some inputs unhandled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionReport {
+    pub state_id: String,
+    /// Assignments to the guard variables under which no outgoing transition fires
+    pub uncovered_examples: Vec<HashMap<String, String>>,
+    /// The domains were too large to try every combination
+    pub enumeration_truncated: bool,
+}
+
+impl PartitionReport {
+    /// `sensor=mid, mode=AUTO`, variables sorted by name
+    pub fn format_example(example: &HashMap<String, String>) -> String {
+        let mut pairs: Vec<_> = example.iter().collect();
+        pairs.sort();
+        pairs.iter().map(|(var, value)| format!("{}={}", var, value)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// States of `fb` where some combination of the values its guards mention
+/// satisfies none of the outgoing guards. A variable's domain is every value
+/// it's compared against anywhere in the block, both booleans for a boolean,
+/// and the neighbours of and midpoints between its numeric thresholds.
+pub fn check_guard_partition(fb: &FunctionBlock) -> Vec<PartitionReport> {
+    let domains = variable_domains(fb);
+    let mut reports = Vec::new();
+
+    for state_id in fb.states.keys() {
+        let mut guards: Vec<Vec<PathSignature>> = Vec::new();
+        let mut unconditional = false;
+        for transition in fb.transitions.iter().filter(|t| &t.from_state == state_id) {
+            let guard = transition.condition.as_deref().unwrap_or_default();
+            let dnf = SignatureGenerator::parse_transition_condition(guard);
+            unconditional |= dnf.iter().any(Vec::is_empty);
+            guards.push(dnf.into_iter().map(|conjunction| PathSignature::new(conjunction, 0)).collect());
+        }
+        if guards.is_empty() || unconditional {
+            continue;
+        }
+
+        let mut variables: Vec<&String> = guards
+            .iter()
+            .flatten()
+            .flat_map(|sig| sig.conditions.iter().map(|c| &c.variable))
+            .collect();
+        variables.sort();
+        variables.dedup();
+
+        let report = uncovered_combinations(state_id, &variables, &domains, &guards);
+        if !report.uncovered_examples.is_empty() {
+            reports.push(report);
+        }
+    }
+
+    reports
+}
+
+fn uncovered_combinations(
+    state_id: &str,
+    variables: &[&String],
+    domains: &IndexMap<String, Vec<String>>,
+    guards: &[Vec<PathSignature>],
+) -> PartitionReport {
+    let domains: Vec<&[String]> = variables
+        .iter()
+        .map(|var| domains.get(*var).map_or(&[][..], Vec::as_slice))
+        .collect();
+    let mut report = PartitionReport {
+        state_id: state_id.to_string(),
+        uncovered_examples: Vec::new(),
+        enumeration_truncated: false,
+    };
+    if domains.iter().any(|d| d.is_empty()) {
+        return report;
+    }
+
+    // Mixed-radix counter over the domains
+    let mut digits = vec![0; variables.len()];
+    for tried in 0.. {
+        if tried == MAX_COMBINATIONS {
+            report.enumeration_truncated = true;
+            break;
+        }
+
+        let assignment: HashMap<String, String> = variables
+            .iter()
+            .zip(&digits)
+            .zip(&domains)
+            .map(|((var, &digit), domain)| (var.to_string(), domain[digit].clone()))
+            .collect();
+        if !guards.iter().flatten().any(|sig| sig.matches(&assignment)) {
+            report.uncovered_examples.push(assignment);
+            if report.uncovered_examples.len() == MAX_EXAMPLES {
+                break;
+            }
+        }
+
+        let Some(pos) = (0..digits.len()).rev().find(|&i| digits[i] + 1 < domains[i].len()) else {
+            break;
+        };
+        digits[pos] += 1;
+        digits[pos + 1..].fill(0);
+    }
+
+    report
+}
+
+fn variable_domains(fb: &FunctionBlock) -> IndexMap<String, Vec<String>> {
+    let conditions: Vec<Condition> = fb.transitions
+        .iter()
+        .filter_map(|t| t.condition.as_deref())
+        .flat_map(|guard| SignatureGenerator::parse_transition_condition(guard).into_iter().flatten())
+        .collect();
+
+    let mut by_variable: IndexMap<&str, Vec<&Condition>> = IndexMap::new();
+    for cond in &conditions {
+        by_variable.entry(cond.variable.as_str()).or_default().push(cond);
+    }
+
+    // The values `PathSignature::is_satisfiable` probes with separate every
+    // outcome of the comparisons
+    by_variable
+        .into_iter()
+        .map(|(variable, conditions)| (variable.to_string(), PathSignature::probe_values(&conditions)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_uncovered_domain_value_reported() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "sensor = high".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "40".to_string(), "sensor = mid".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "".to_string()));
+
+        let reports = check_guard_partition(&fb);
+        let report = reports.iter().find(|r| r.state_id == "10").unwrap();
+
+        assert_eq!(report.uncovered_examples.len(), 1);
+        assert_eq!(report.uncovered_examples[0].get("sensor").map(String::as_str), Some("mid"));
+        assert_eq!(PartitionReport::format_example(&report.uncovered_examples[0]), "sensor=mid");
+        assert!(!reports.iter().any(|r| r.state_id == "30"));
+    }
+}
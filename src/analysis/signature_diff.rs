@@ -0,0 +1,97 @@
+use crate::analysis::StateSignatureTable;
+use serde::{Deserialize, Serialize};
+
+/// A state whose reaching conditions differ between two signature tables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureChange {
+    pub state_id: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Difference between the signature tables of one function block in two
+/// versions of a program
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignatureDiff {
+    pub function_block: String,
+    /// States only present in the new table
+    pub added: Vec<String>,
+    /// States only present in the old table
+    pub removed: Vec<String>,
+    pub changed: Vec<SignatureChange>,
+}
+
+impl SignatureDiff {
+    /// Compare tables state by state using `StateSignature::canonical_string`
+    pub fn compare(old: &StateSignatureTable, new: &StateSignatureTable) -> Self {
+        let mut diff = SignatureDiff {
+            function_block: new.function_block_name.clone(),
+            ..Default::default()
+        };
+
+        for (state_id, old_sig) in &old.signatures {
+            match new.get_signature(state_id) {
+                None => diff.removed.push(state_id.clone()),
+                Some(new_sig) => {
+                    let (old_text, new_text) = (old_sig.canonical_string(), new_sig.canonical_string());
+                    if old_text != new_text {
+                        diff.changed.push(SignatureChange {
+                            state_id: state_id.clone(),
+                            old: old_text,
+                            new: new_text,
+                        });
+                    }
+                }
+            }
+        }
+
+        diff.added = new.signatures
+            .keys()
+            .filter(|state_id| old.get_signature(state_id).is_none())
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort_by(|a, b| a.state_id.cmp(&b.state_id));
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::SignatureGenerator;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    fn pump_fb(guard: &str) -> FunctionBlock {
+        let mut fb = FunctionBlock::new("Pump".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("30".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), guard.to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "stop = TRUE".to_string()));
+        fb
+    }
+
+    #[test]
+    fn test_changed_guard_reported() {
+        let old = SignatureGenerator::generate(&pump_fb("sensor = low")).unwrap();
+        let new = SignatureGenerator::generate(&pump_fb("sensor = high")).unwrap();
+
+        let diff = SignatureDiff::compare(&old, &new);
+
+        assert_eq!(diff.changed, vec![SignatureChange {
+            state_id: "20".to_string(),
+            old: "sensor = low".to_string(),
+            new: "sensor = high".to_string(),
+        }]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+
+        assert!(SignatureDiff::compare(&old, &old).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::fsm::{FunctionBlock};
+use crate::analysis::FsmValidator;
+use crate::error::FsmError;
+use crate::fsm::{FunctionBlock, Transition};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -12,56 +14,241 @@ use std::collections::{HashMap, HashSet};
 /// None for initial states (no incoming transition)
 type TransitionPath = Vec<(String, Option<usize>)>;
 
+/// Every path found to each state, keyed by state id
+type PathsByState = HashMap<String, Vec<TransitionPath>>;
+
 // ============================================================================
 // DATA STRUCTURES
 // ============================================================================
 
+/// Bounds on the recursive guard parser, DNF conversion and path search, so
+/// adversarial input fails with an error instead of overflowing the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessingLimits {
+    /// Deepest parenthesis nesting, expression tree or transition path accepted
+    pub max_recursion_depth: usize,
+}
+
+impl Default for ProcessingLimits {
+    fn default() -> Self {
+        Self { max_recursion_depth: 256 }
+    }
+}
+
 /// Represents a single atomic condition in a signature (e.g., "H = Input")
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Condition {
     pub variable: String,
     pub operator: String,
+    /// Compared value; numbers are canonical so `100`, `100.0` and `1e2` agree
     pub value: String,
+    /// Value as written in the guard, when canonicalization changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_value: Option<String>,
+    /// Id of the transition whose guard contributed the condition to a signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_transition_id: Option<String>,
+}
+
+/// Equality and hashing ignore `raw_value` and `origin_transition_id`, so
+/// spellings of one number match and provenance doesn't split signatures
+impl PartialEq for Condition {
+    fn eq(&self, other: &Self) -> bool {
+        self.variable == other.variable && self.operator == other.operator && self.value == other.value
+    }
+}
+
+impl Eq for Condition {}
+
+impl std::hash::Hash for Condition {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.variable.hash(state);
+        self.operator.hash(state);
+        self.value.hash(state);
+    }
 }
 
 impl Condition {
     pub fn new(variable: String, operator: String, value: String) -> Self {
+        let canonical = Self::canonical_number(&value);
+        let raw_value = canonical.as_ref().filter(|c| **c != value).map(|_| value.clone());
+
         Self {
             variable,
             operator,
-            value,
+            value: canonical.unwrap_or(value),
+            raw_value,
+            origin_transition_id: None,
+        }
+    }
+
+    /// Integral numbers print without a fraction, others in shortest float form;
+    /// `None` for anything that isn't a plain decimal number
+    fn canonical_number(value: &str) -> Option<String> {
+        if let Ok(int) = value.parse::<i64>() {
+            return Some(int.to_string());
+        }
+
+        let float = value.parse::<f64>().ok().filter(|f| f.is_finite())?;
+        if float.fract() == 0.0 && float.abs() < 1e15 {
+            Some((float as i64).to_string())
+        } else {
+            Some(float.to_string())
+        }
+    }
+
+    /// Outcome of a variable compared with itself: `x = x` always holds and
+    /// `x <> x` never does; `None` unless both sides are the same identifier
+    pub fn self_comparison(&self) -> Option<bool> {
+        let is_identifier = self.variable.starts_with(|c: char| c.is_alphabetic() || c == '_');
+        if !is_identifier || self.variable != self.value {
+            return None;
+        }
+
+        match self.operator.as_str() {
+            "=" | "<=" | ">=" => Some(true),
+            "<>" | "<" | ">" => Some(false),
+            _ => None,
         }
     }
 
-    /// Format condition as a string (e.g., "H = Input")
-    pub fn to_string(&self) -> String {
-        format!("{} {} {}", self.variable, self.operator, self.value)
+    /// Value as the guard spelled it (`100.0` rather than the canonical `100`)
+    pub fn shown_value(&self) -> &str {
+        self.raw_value.as_deref().unwrap_or(&self.value)
+    }
+
+    /// Variable the condition compares against, when the value is an
+    /// identifier rather than a literal (`setpoint` in `level > setpoint`)
+    pub fn value_variable(&self) -> Option<&str> {
+        let value = self.value.as_str();
+        let is_identifier = value.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && value.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '[' | ']'));
+        let is_boolean = value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE");
+        (is_identifier && !is_boolean).then_some(value)
+    }
+
+    /// `Some(true)` for a test that a boolean is TRUE (`var = TRUE`,
+    /// `var <> FALSE`), `Some(false)` for one that it is FALSE, `None` otherwise
+    pub fn boolean_test(&self) -> Option<bool> {
+        let value = match self.value.to_ascii_uppercase().as_str() {
+            "TRUE" => true,
+            "FALSE" => false,
+            _ => return None,
+        };
+        match self.operator.as_str() {
+            "=" => Some(value),
+            "<>" => Some(!value),
+            _ => None,
+        }
+    }
+
+    /// English phrasing for operator-facing docs, e.g. `temp > 50` reads
+    /// "temp is greater than 50"
+    pub fn to_natural_language(&self) -> String {
+        let phrase = match (self.operator.as_str(), self.value.to_ascii_uppercase().as_str()) {
+            ("=" | "<>", "TRUE" | "FALSE") => {
+                let negated = (self.operator == "<>") == self.value.eq_ignore_ascii_case("TRUE");
+                return format!("{} is {}", self.variable, if negated { "FALSE" } else { "TRUE" });
+            }
+            ("=", _) => "equals",
+            ("<>", _) => "does not equal",
+            ("<", _) => "is less than",
+            ("<=", _) => "is less than or equal to",
+            (">", _) => "is greater than",
+            (">=", _) => "is greater than or equal to",
+            (op, _) => op,
+        };
+
+        format!("{} {} {}", self.variable, phrase, self.shown_value())
     }
 }
 
-/// Boolean expression tree for parsing complex conditions
+/// Format condition as a string (e.g., "H = Input")
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.variable, self.operator, self.shown_value())
+    }
+}
+
+/// Boolean expression tree of a parsed transition guard
 #[derive(Debug, Clone, PartialEq)]
-enum BooleanExpr {
+pub enum ConditionExpr {
     /// Atomic condition (e.g., "A = 1")
     Atomic(Condition),
     /// Logical AND
-    And(Box<BooleanExpr>, Box<BooleanExpr>),
+    And(Box<ConditionExpr>, Box<ConditionExpr>),
     /// Logical OR
-    Or(Box<BooleanExpr>, Box<BooleanExpr>),
+    Or(Box<ConditionExpr>, Box<ConditionExpr>),
+    /// Logical XOR
+    Xor(Box<ConditionExpr>, Box<ConditionExpr>),
     /// Logical NOT
-    Not(Box<BooleanExpr>),
+    Not(Box<ConditionExpr>),
 }
 
-impl BooleanExpr {
+impl ConditionExpr {
+    /// Parse a guard string such as `A = 1 AND (B = 2 OR NOT C = 3)`.
+    /// Returns `None` for empty guards or text the expression parser can't handle.
+    pub fn parse(condition_str: &str) -> Option<Self> {
+        Self::parse_with_limits(condition_str, &ProcessingLimits::default())
+            .ok()
+            .flatten()
+    }
+
+    /// Like `parse`, failing with `RecursionLimitExceeded` when parentheses
+    /// nest deeper than `limits` allow
+    pub fn parse_with_limits(condition_str: &str, limits: &ProcessingLimits) -> Result<Option<Self>, FsmError> {
+        Self::parse_tokens(condition_str, limits, false)
+    }
+
+    /// Like `parse_with_limits`, failing with `InvalidGuard` when tokens are
+    /// left over after the expression instead of ignoring them
+    fn parse_whole_with_limits(condition_str: &str, limits: &ProcessingLimits) -> Result<Option<Self>, FsmError> {
+        Self::parse_tokens(condition_str, limits, true)
+    }
+
+    fn parse_tokens(condition_str: &str, limits: &ProcessingLimits, whole: bool) -> Result<Option<Self>, FsmError> {
+        let tokens = Tokenizer::new(condition_str).tokenize();
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+
+        let mut parser = ExpressionParser::new(tokens, limits.max_recursion_depth);
+        let expr = parser.parse();
+        if parser.limit_exceeded {
+            return Err(FsmError::RecursionLimitExceeded(limits.max_recursion_depth));
+        }
+        if whole && parser.position < parser.tokens.len() {
+            return Err(FsmError::InvalidGuard(condition_str.to_string()));
+        }
+
+        Ok(expr)
+    }
+
     /// Convert to Disjunctive Normal Form (DNF): (A AND B) OR (C AND D) OR ...
     /// Each inner Vec<Condition> is a conjunction (AND), outer Vec is disjunction (OR)
-    fn to_dnf(&self) -> Vec<Vec<Condition>> {
-        match self {
-            BooleanExpr::Atomic(cond) => vec![vec![cond.clone()]],
+    pub fn to_dnf(&self) -> Vec<Vec<Condition>> {
+        self.dnf_at(0, usize::MAX)
+            .expect("unbounded DNF conversion cannot hit a limit")
+    }
+
+    /// Like `to_dnf`, failing with `RecursionLimitExceeded` on trees deeper
+    /// than `limits` allow
+    pub fn to_dnf_with_limits(&self, limits: &ProcessingLimits) -> Result<Vec<Vec<Condition>>, FsmError> {
+        self.dnf_at(0, limits.max_recursion_depth)
+    }
+
+    fn dnf_at(&self, depth: usize, max_depth: usize) -> Result<Vec<Vec<Condition>>, FsmError> {
+        if depth > max_depth {
+            return Err(FsmError::RecursionLimitExceeded(max_depth));
+        }
+        let depth = depth + 1;
 
-            BooleanExpr::And(left, right) => {
-                let left_dnf = left.to_dnf();
-                let right_dnf = right.to_dnf();
+        let dnf = match self {
+            ConditionExpr::Atomic(cond) => vec![vec![cond.clone()]],
+
+            ConditionExpr::And(left, right) => {
+                let left_dnf = left.dnf_at(depth, max_depth)?;
+                let right_dnf = right.dnf_at(depth, max_depth)?;
 
                 // Distribute AND over OR: (A OR B) AND (C OR D) = (A AND C) OR (A AND D) OR (B AND C) OR (B AND D)
                 let mut result = Vec::new();
@@ -75,43 +262,61 @@ impl BooleanExpr {
                 result
             }
 
-            BooleanExpr::Or(left, right) => {
-                let mut left_dnf = left.to_dnf();
-                let mut right_dnf = right.to_dnf();
+            ConditionExpr::Or(left, right) => {
+                let mut left_dnf = left.dnf_at(depth, max_depth)?;
+                let mut right_dnf = right.dnf_at(depth, max_depth)?;
                 left_dnf.append(&mut right_dnf);
                 left_dnf
             }
 
-            BooleanExpr::Not(inner) => {
+            ConditionExpr::Xor(left, right) => {
+                // A XOR B = (A AND NOT B) OR (NOT A AND B)
+                let only_left = ConditionExpr::And(left.clone(), Box::new(ConditionExpr::Not(right.clone())));
+                let only_right = ConditionExpr::And(Box::new(ConditionExpr::Not(left.clone())), right.clone());
+                ConditionExpr::Or(Box::new(only_left), Box::new(only_right)).dnf_at(depth, max_depth)?
+            }
+
+            ConditionExpr::Not(inner) => {
                 // For NOT, we need to apply De Morgan's laws
                 // NOT(A AND B) = NOT(A) OR NOT(B)
                 // NOT(A OR B) = NOT(A) AND NOT(B)
                 // NOT(NOT(A)) = A
                 match inner.as_ref() {
-                    BooleanExpr::Atomic(cond) => {
+                    ConditionExpr::Atomic(cond) => {
                         // Negate the operator
                         let negated = Self::negate_condition(cond);
                         vec![vec![negated]]
                     }
-                    BooleanExpr::And(left, right) => {
+                    ConditionExpr::And(left, right) => {
                         // NOT(A AND B) = NOT(A) OR NOT(B)
-                        let not_left = BooleanExpr::Not(left.clone());
-                        let not_right = BooleanExpr::Not(right.clone());
-                        BooleanExpr::Or(Box::new(not_left), Box::new(not_right)).to_dnf()
+                        let not_left = ConditionExpr::Not(left.clone());
+                        let not_right = ConditionExpr::Not(right.clone());
+                        ConditionExpr::Or(Box::new(not_left), Box::new(not_right)).dnf_at(depth, max_depth)?
                     }
-                    BooleanExpr::Or(left, right) => {
+                    ConditionExpr::Or(left, right) => {
                         // NOT(A OR B) = NOT(A) AND NOT(B)
-                        let not_left = BooleanExpr::Not(left.clone());
-                        let not_right = BooleanExpr::Not(right.clone());
-                        BooleanExpr::And(Box::new(not_left), Box::new(not_right)).to_dnf()
+                        let not_left = ConditionExpr::Not(left.clone());
+                        let not_right = ConditionExpr::Not(right.clone());
+                        ConditionExpr::And(Box::new(not_left), Box::new(not_right)).dnf_at(depth, max_depth)?
+                    }
+                    ConditionExpr::Xor(left, right) => {
+                        // NOT(A XOR B) = (A AND B) OR (NOT A AND NOT B)
+                        let both = ConditionExpr::And(left.clone(), right.clone());
+                        let neither = ConditionExpr::And(
+                            Box::new(ConditionExpr::Not(left.clone())),
+                            Box::new(ConditionExpr::Not(right.clone())),
+                        );
+                        ConditionExpr::Or(Box::new(both), Box::new(neither)).dnf_at(depth, max_depth)?
                     }
-                    BooleanExpr::Not(inner) => {
+                    ConditionExpr::Not(inner) => {
                         // NOT(NOT(A)) = A
-                        inner.to_dnf()
+                        inner.dnf_at(depth, max_depth)?
                     }
                 }
             }
-        }
+        };
+
+        Ok(dnf)
     }
 
     /// Negate a condition operator
@@ -126,14 +331,24 @@ impl BooleanExpr {
             _ => "=", // fallback
         };
 
-        Condition::new(
-            cond.variable.clone(),
-            negated_op.to_string(),
-            cond.value.clone(),
-        )
+        Condition {
+            operator: negated_op.to_string(),
+            ..cond.clone()
+        }
     }
 }
 
+/// How runtime matching treats conditions on variables absent from the input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// A missing variable fails its condition
+    #[default]
+    Strict,
+    /// Conditions on missing variables are skipped (assumed satisfiable), for
+    /// partial-observation monitoring
+    LenientMissing,
+}
+
 /// A single path signature (one way to reach a state)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PathSignature {
@@ -162,52 +377,138 @@ impl PathSignature {
         }
     }
 
+    /// "when A and B", or "initially" for the empty signature
+    pub fn to_natural_language(&self) -> String {
+        if self.conditions.is_empty() {
+            return "initially".to_string();
+        }
+
+        let phrases: Vec<String> = self.conditions.iter().map(Condition::to_natural_language).collect();
+        format!("when {}", phrases.join(" and "))
+    }
+
+    /// Whether some assignment meets every condition at once; false for
+    /// conflicts like `A = 1 AND A = 2` or `x > 5 AND x < 3`. Each variable is
+    /// tried with the values it's compared against, their neighbours and
+    /// midpoints, and a value unrelated to any of them.
+    pub fn is_satisfiable(&self) -> bool {
+        let mut by_variable: IndexMap<&str, Vec<&Condition>> = IndexMap::new();
+        for cond in &self.conditions {
+            by_variable.entry(cond.variable.as_str()).or_default().push(cond);
+        }
+
+        by_variable.values().all(|conditions| {
+            let mut candidates = vec!["\u{0}unrelated".to_string()];
+            candidates.extend(Self::probe_values(conditions));
+
+            candidates.iter().any(|value| conditions.iter().all(|cond| Self::evaluate_condition(cond, value)))
+        })
+    }
+
+    /// Values of one variable that tell apart every outcome of `conditions` on
+    /// it: the compared values, their neighbours and midpoints, and both
+    /// booleans when it's tested as one
+    pub(crate) fn probe_values(conditions: &[&Condition]) -> Vec<String> {
+        let mut numbers: Vec<f64> = conditions.iter().filter_map(|c| Self::numeric_value(&c.value)).collect();
+        numbers.sort_by(f64::total_cmp);
+        numbers.dedup();
+
+        let mut values: Vec<String> = Vec::new();
+        if conditions.iter().any(|c| c.boolean_test().is_some()) {
+            values.extend(["TRUE".to_string(), "FALSE".to_string()]);
+        }
+        values.extend(conditions.iter().filter(|c| c.boolean_test().is_none()).map(|c| c.value.clone()));
+        values.extend(numbers.iter().flat_map(|n| [n - 1.0, *n, n + 1.0]).map(|v| v.to_string()));
+        values.extend(numbers.windows(2).map(|pair| ((pair[0] + pair[1]) / 2.0).to_string()));
+
+        let mut unique = Vec::new();
+        for value in values {
+            if !unique.contains(&value) {
+                unique.push(value);
+            }
+        }
+        unique
+    }
+
     /// Check if runtime conditions match this signature
     pub fn matches(&self, runtime_vars: &HashMap<String, String>) -> bool {
+        self.matches_with_mode(runtime_vars, MatchMode::Strict)
+    }
+
+    pub fn matches_with_mode(&self, runtime_vars: &HashMap<String, String>, mode: MatchMode) -> bool {
         self.conditions.iter().all(|cond| {
             if let Some(runtime_value) = runtime_vars.get(&cond.variable) {
                 Self::evaluate_condition(cond, runtime_value)
             } else {
-                false // Variable not present in runtime state
+                mode == MatchMode::LenientMissing // Variable not present in runtime state
             }
         })
     }
 
     fn evaluate_condition(cond: &Condition, runtime_value: &str) -> bool {
+        // Numbers compare by value, so a runtime `37.50` equals a REAL literal `37.5`
+        // and `T#5000ms` equals `T#5s`
+        let numeric = (Self::numeric_value(runtime_value), Self::numeric_value(&cond.value));
+
         match cond.operator.as_str() {
-            "=" => runtime_value == cond.value,
-            "<>" => runtime_value != cond.value,
-            "<" => {
-                if let (Ok(rv), Ok(cv)) = (runtime_value.parse::<f64>(), cond.value.parse::<f64>()) {
-                    rv < cv
-                } else {
-                    false
-                }
-            }
-            "<=" => {
-                if let (Ok(rv), Ok(cv)) = (runtime_value.parse::<f64>(), cond.value.parse::<f64>()) {
-                    rv <= cv
-                } else {
-                    false
-                }
-            }
-            ">" => {
-                if let (Ok(rv), Ok(cv)) = (runtime_value.parse::<f64>(), cond.value.parse::<f64>()) {
-                    rv > cv
-                } else {
-                    false
-                }
-            }
-            ">=" => {
-                if let (Ok(rv), Ok(cv)) = (runtime_value.parse::<f64>(), cond.value.parse::<f64>()) {
-                    rv >= cv
-                } else {
-                    false
-                }
-            }
+            "=" => match numeric {
+                (Some(rv), Some(cv)) => rv == cv,
+                _ => runtime_value == cond.value,
+            },
+            "<>" => match numeric {
+                (Some(rv), Some(cv)) => rv != cv,
+                _ => runtime_value != cond.value,
+            },
+            "<" => matches!(numeric, (Some(rv), Some(cv)) if rv < cv),
+            "<=" => matches!(numeric, (Some(rv), Some(cv)) if rv <= cv),
+            ">" => matches!(numeric, (Some(rv), Some(cv)) if rv > cv),
+            ">=" => matches!(numeric, (Some(rv), Some(cv)) if rv >= cv),
             _ => false,
         }
     }
+
+    /// A plain number, or a TIME literal in milliseconds (the unit PLCs count
+    /// TIME in, so a runtime `5000` matches `T#5s`)
+    fn numeric_value(value: &str) -> Option<f64> {
+        value.parse::<f64>().ok().or_else(|| parse_duration_ms(value))
+    }
+}
+
+/// Milliseconds of an IEC TIME literal such as `T#5s`, `TIME#1h30m` or
+/// `t#1.5s`; `None` for anything else
+pub fn parse_duration_ms(literal: &str) -> Option<f64> {
+    let (prefix, body) = literal.split_once('#')?;
+    if !matches!(prefix.to_ascii_uppercase().as_str(), "T" | "TIME" | "LT" | "LTIME") {
+        return None;
+    }
+
+    let (sign, body) = match body.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, body),
+    };
+    let body = body.replace('_', "").to_ascii_lowercase();
+
+    let mut total = 0.0;
+    let mut rest = body.as_str();
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let unit_len = rest[number_len..].find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len() - number_len);
+        let amount: f64 = rest[..number_len].parse().ok()?;
+        let scale = match &rest[number_len..number_len + unit_len] {
+            "d" => 86_400_000.0,
+            "h" => 3_600_000.0,
+            "m" => 60_000.0,
+            "s" => 1_000.0,
+            "ms" => 1.0,
+            "us" => 0.001,
+            "ns" => 0.000_001,
+            _ => return None,
+        };
+        total += amount * scale;
+        rest = &rest[number_len + unit_len..];
+    }
+
+    (!body.is_empty()).then_some(sign * total)
 }
 
 /// Signature for a single state (MULTIPLE path signatures - disjunctive OR)
@@ -216,6 +517,19 @@ pub struct StateSignature {
     pub state_id: String,
     pub path_signatures: Vec<PathSignature>,
     pub paths_count: usize,
+    /// Path search skipped a way around a cycle through this state, so the
+    /// signature misses the conditions of those paths
+    #[serde(default)]
+    pub reached_via_cycle_truncation: bool,
+    /// Path search stopped at a `PathFinderOptions` limit, so the signature
+    /// may miss some ways of reaching the state
+    #[serde(default)]
+    pub truncated_by_path_limits: bool,
+    /// Path signatures whose conditions can't all hold at once (`A = 1 AND
+    /// A = 2`). Under `SignatureOptions::drop_contradictory` they're left out,
+    /// and a state that keeps none is unsatisfiable rather than initial.
+    #[serde(default)]
+    pub contradictory_paths: usize,
 }
 
 impl StateSignature {
@@ -224,12 +538,22 @@ impl StateSignature {
             state_id,
             path_signatures: Vec::new(),
             paths_count: 0,
+            reached_via_cycle_truncation: false,
+            truncated_by_path_limits: false,
+            contradictory_paths: 0,
         }
     }
 
+    /// Every way of reaching the state was contradictory and dropped
+    pub fn is_unsatisfiable(&self) -> bool {
+        self.path_signatures.is_empty() && self.contradictory_paths > 0
+    }
+
     /// Format all signatures (showing OR logic)
     pub fn format_conditions(&self) -> String {
-        if self.path_signatures.is_empty() {
+        if self.is_unsatisfiable() {
+            "[unsatisfiable]".to_string()
+        } else if self.path_signatures.is_empty() {
             "[initial]".to_string()
         } else if self.path_signatures.len() == 1 {
             self.path_signatures[0].format_conditions()
@@ -243,12 +567,58 @@ impl StateSignature {
         }
     }
 
+    /// Order-independent form of `format_conditions`, for comparing tables
+    pub fn canonical_string(&self) -> String {
+        // Canonical values, so respelling a number isn't a change
+        let mut terms: Vec<String> = self.path_signatures
+            .iter()
+            .map(|path| if path.conditions.is_empty() {
+                "[initial]".to_string()
+            } else {
+                path.conditions
+                    .iter()
+                    .map(|c| format!("{} {} {}", c.variable, c.operator, c.value))
+                    .collect::<Vec<_>>()
+                    .join(" AND ")
+            })
+            .collect();
+        terms.sort();
+        terms.dedup();
+
+        match terms.len() {
+            0 if self.is_unsatisfiable() => "[unsatisfiable]".to_string(),
+            0 => "[initial]".to_string(),
+            1 => terms.remove(0),
+            _ => terms.iter().map(|t| format!("({})", t)).collect::<Vec<_>>().join(" OR "),
+        }
+    }
+
+    /// Path signatures phrased in English and joined with "or"
+    pub fn to_natural_language(&self) -> String {
+        if self.is_unsatisfiable() {
+            return "never".to_string();
+        }
+        if self.path_signatures.is_empty() {
+            return "initially".to_string();
+        }
+
+        self.path_signatures
+            .iter()
+            .map(PathSignature::to_natural_language)
+            .collect::<Vec<_>>()
+            .join(", or ")
+    }
+
     /// Check if runtime state matches ANY of the path signatures
     pub fn matches_any(&self, runtime_vars: &HashMap<String, String>) -> bool {
+        self.matches_any_with_mode(runtime_vars, MatchMode::Strict)
+    }
+
+    pub fn matches_any_with_mode(&self, runtime_vars: &HashMap<String, String>, mode: MatchMode) -> bool {
         if self.path_signatures.is_empty() {
-            return true; // Initial state
+            return !self.is_unsatisfiable(); // Initial state
         }
-        self.path_signatures.iter().any(|ps| ps.matches(runtime_vars))
+        self.path_signatures.iter().any(|ps| ps.matches_with_mode(runtime_vars, mode))
     }
 }
 
@@ -274,6 +644,28 @@ impl StateSignatureTable {
         self.signatures.get(state_id)
     }
 
+    /// States whose signatures omit cycle-based reaching conditions
+    pub fn partial_states(&self) -> Vec<&str> {
+        let mut states: Vec<&str> = self.signatures
+            .values()
+            .filter(|sig| sig.reached_via_cycle_truncation)
+            .map(|sig| sig.state_id.as_str())
+            .collect();
+        states.sort();
+        states
+    }
+
+    /// States whose signatures may be incomplete because path search hit a limit
+    pub fn limited_states(&self) -> Vec<&str> {
+        let mut states: Vec<&str> = self.signatures
+            .values()
+            .filter(|sig| sig.truncated_by_path_limits)
+            .map(|sig| sig.state_id.as_str())
+            .collect();
+        states.sort();
+        states
+    }
+
     /// Total number of states with signatures
     pub fn state_count(&self) -> usize {
         self.signatures.len()
@@ -281,8 +673,17 @@ impl StateSignatureTable {
 
     /// Verify runtime state against signatures (for runtime monitoring)
     pub fn verify_state(&self, state_id: &str, runtime_vars: &HashMap<String, String>) -> bool {
+        self.verify_state_with_mode(state_id, runtime_vars, MatchMode::Strict)
+    }
+
+    pub fn verify_state_with_mode(
+        &self,
+        state_id: &str,
+        runtime_vars: &HashMap<String, String>,
+        mode: MatchMode,
+    ) -> bool {
         if let Some(sig) = self.signatures.get(state_id) {
-            sig.matches_any(runtime_vars)
+            sig.matches_any_with_mode(runtime_vars, mode)
         } else {
             false // Unknown state
         }
@@ -298,6 +699,7 @@ enum Token {
     Condition(String),
     And,
     Or,
+    Xor,
     Not,
     LParen,
     RParen,
@@ -333,6 +735,9 @@ impl Tokenizer {
             } else if self.check_keyword("OR") {
                 tokens.push(Token::Or);
                 self.position += 2;
+            } else if self.check_keyword("XOR") {
+                tokens.push(Token::Xor);
+                self.position += 3;
             } else if self.check_keyword("NOT") {
                 tokens.push(Token::Not);
                 self.position += 3;
@@ -356,8 +761,13 @@ impl Tokenizer {
         tokens
     }
 
+    // Byte lookup keeps long guards linear; positions are byte offsets
     fn current_char(&self) -> char {
-        self.input.chars().nth(self.position).unwrap_or('\0')
+        self.char_at(self.position)
+    }
+
+    fn char_at(&self, position: usize) -> char {
+        self.input.as_bytes().get(position).map_or('\0', |&b| b as char)
     }
 
     fn skip_whitespace(&mut self) {
@@ -366,26 +776,31 @@ impl Tokenizer {
         }
     }
 
+    // Compares bytes: the position may sit inside a multi-byte character
     fn check_keyword(&self, keyword: &str) -> bool {
-        if self.position + keyword.len() > self.input.len() {
-            return false;
-        }
-
-        let substr = &self.input[self.position..self.position + keyword.len()];
-        if substr != keyword {
+        let bytes = self.input.as_bytes();
+        let end = self.position + keyword.len();
+        if bytes.get(self.position..end) != Some(keyword.as_bytes()) {
             return false;
         }
 
         // Ensure it's a complete word (not part of a variable name)
-        let next_pos = self.position + keyword.len();
-        if next_pos < self.input.len() {
-            let next_char = self.input.chars().nth(next_pos).unwrap_or('\0');
-            if next_char.is_alphanumeric() || next_char == '_' {
-                return false;
-            }
+        let is_word_byte = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_' || !b.is_ascii();
+        let inside_word = self.position > 0 && bytes.get(self.position - 1).is_some_and(is_word_byte);
+        !inside_word && !bytes.get(end).is_some_and(is_word_byte)
+    }
+
+    /// `AND` at the current position masks bits (`flags AND 16#0F = 0`) rather
+    /// than joining conditions: nothing compared yet and a numeric operand follows
+    fn is_bitwise_and(&self, start: usize) -> bool {
+        let before = &self.input[start..self.position];
+        if ["=", "<", ">"].iter().any(|op| before.contains(op)) {
+            return false;
         }
 
-        true
+        self.input[self.position + 3..]
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_digit())
     }
 
     fn parse_atomic_condition(&mut self) -> Option<String> {
@@ -407,7 +822,10 @@ impl Tokenizer {
                     // Unmatched closing paren - end of condition
                     break;
                 }
-            } else if paren_depth == 0 && (self.check_keyword("AND") || self.check_keyword("OR")) {
+            } else if paren_depth == 0 && self.check_keyword("AND") && self.is_bitwise_and(start) {
+                self.position += 3;
+            } else if paren_depth == 0
+                && (self.check_keyword("AND") || self.check_keyword("OR") || self.check_keyword("XOR")) {
                 break;
             } else {
                 self.position += 1;
@@ -432,13 +850,7 @@ impl Tokenizer {
 /// Parse a single atomic condition expression
 /// Handles: =, <>, <=, >=, <, >
 fn parse_atomic_condition_str(expr: &str) -> Option<Condition> {
-    // Remove outer parentheses if present
-    let expr = expr.trim();
-    let expr = if expr.starts_with('(') && expr.ends_with(')') {
-        &expr[1..expr.len() - 1]
-    } else {
-        expr
-    };
+    let expr = strip_wrapping_parens(expr);
 
     // Try different operators in order of precedence (longest first)
     let operators = vec![
@@ -452,53 +864,153 @@ fn parse_atomic_condition_str(expr: &str) -> Option<Condition> {
 
     for (op_str, op_name) in operators {
         if let Some(pos) = expr.find(op_str) {
-            let variable = expr[..pos].trim();
-            let value = expr[pos + op_str.len()..].trim();
+            let variable = normalize_operand(strip_wrapping_parens(&expr[..pos]));
+            let value = normalize_operand(strip_wrapping_parens(&expr[pos + op_str.len()..]));
 
-            // Clean up value expressions (remove outer parentheses if they wrap entire value)
-            let value = if value.starts_with('(') && value.ends_with(')') {
-                value[1..value.len() - 1].trim().to_string()
-            } else {
-                value.to_string()
-            };
+            // A comparison missing an operand (`speed >`) is not a condition
+            if variable.is_empty() || value.is_empty() {
+                return None;
+            }
 
             return Some(Condition::new(
-                variable.to_string(),
+                variable,
                 op_name.to_string(),
                 value,
             ));
         }
     }
 
+    // A bare boolean variable tests for TRUE; array accesses count as one name
+    let is_identifier = !expr.is_empty()
+        && expr.chars().all(|c| c.is_alphanumeric() || "_.[]".contains(c));
+    if is_identifier {
+        return Some(Condition::new(expr.to_string(), "=".to_string(), "TRUE".to_string()));
+    }
+
     None
 }
 
+/// `expr` trimmed, without parentheses enclosing all of it; `(a + b) > (c)`
+/// keeps its parentheses since the first one closes before the end
+fn strip_wrapping_parens(expr: &str) -> &str {
+    let mut expr = expr.trim();
+    while expr.starts_with('(') && expr.ends_with(')') {
+        let mut depth = 0;
+        let closes_at_end = expr.char_indices().all(|(idx, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth > 0 || idx == expr.len() - 1
+        });
+        if !closes_at_end {
+            break;
+        }
+        expr = expr[1..expr.len() - 1].trim();
+    }
+    expr
+}
+
+/// Arithmetic operand in one spelling, so `level+offset` and `level + offset`
+/// compare equal: binary `+ - * /` get single spaces, signs and parentheses
+/// stay attached, operand order is kept and quoted strings are left alone
+fn normalize_operand(operand: &str) -> String {
+    let mut out = String::new();
+    let mut expect_operand = true;
+    let mut pending_space = false;
+    let mut in_quote = false;
+
+    for c in operand.chars() {
+        if in_quote {
+            out.push(c);
+            in_quote = c != '\'';
+            continue;
+        }
+
+        match c {
+            _ if c.is_whitespace() => pending_space = true,
+            '+' | '-' | '*' | '/' if !expect_operand && !is_exponent(&out) => {
+                out.push(' ');
+                out.push(c);
+                out.push(' ');
+                expect_operand = true;
+                pending_space = false;
+            }
+            _ => {
+                // Keep words apart (`x MOD 3`), but not a sign from its operand
+                if pending_space && !expect_operand {
+                    out.push(' ');
+                }
+                out.push(c);
+                in_quote = c == '\'';
+                expect_operand = c == '(';
+                pending_space = false;
+            }
+        }
+    }
+
+    out
+}
+
+/// `out` ends in the `e` of a number's exponent, as in `1e-3`
+fn is_exponent(out: &str) -> bool {
+    let token = out.rsplit([' ', '(']).next().unwrap_or_default();
+    token.len() > 1
+        && token.ends_with(['e', 'E'])
+        && token.starts_with(|c: char| c.is_ascii_digit())
+        && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
 struct ExpressionParser {
     tokens: Vec<Token>,
     position: usize,
+    depth: usize,
+    max_depth: usize,
+    limit_exceeded: bool,
 }
 
 impl ExpressionParser {
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(tokens: Vec<Token>, max_depth: usize) -> Self {
         Self {
             tokens,
             position: 0,
+            depth: 0,
+            max_depth,
+            limit_exceeded: false,
         }
     }
 
-    fn parse(&mut self) -> Option<BooleanExpr> {
+    fn parse(&mut self) -> Option<ConditionExpr> {
         self.parse_or()
     }
 
     // OR has the lowest precedence
-    fn parse_or(&mut self) -> Option<BooleanExpr> {
-        let mut left = self.parse_and()?;
+    fn parse_or(&mut self) -> Option<ConditionExpr> {
+        let mut left = self.parse_xor()?;
 
         while self.position < self.tokens.len() {
             if matches!(self.tokens[self.position], Token::Or) {
+                self.position += 1;
+                let right = self.parse_xor()?;
+                left = ConditionExpr::Or(Box::new(left), Box::new(right));
+            } else {
+                break;
+            }
+        }
+
+        Some(left)
+    }
+
+    // XOR binds tighter than OR but looser than AND
+    fn parse_xor(&mut self) -> Option<ConditionExpr> {
+        let mut left = self.parse_and()?;
+
+        while self.position < self.tokens.len() {
+            if matches!(self.tokens[self.position], Token::Xor) {
                 self.position += 1;
                 let right = self.parse_and()?;
-                left = BooleanExpr::Or(Box::new(left), Box::new(right));
+                left = ConditionExpr::Xor(Box::new(left), Box::new(right));
             } else {
                 break;
             }
@@ -508,14 +1020,14 @@ impl ExpressionParser {
     }
 
     // AND has the higher precedence than OR
-    fn parse_and(&mut self) -> Option<BooleanExpr> {
+    fn parse_and(&mut self) -> Option<ConditionExpr> {
         let mut left = self.parse_not()?;
 
         while self.position < self.tokens.len() {
             if matches!(self.tokens[self.position], Token::And) {
                 self.position += 1;
                 let right = self.parse_not()?;
-                left = BooleanExpr::And(Box::new(left), Box::new(right));
+                left = ConditionExpr::And(Box::new(left), Box::new(right));
             } else {
                 break;
             }
@@ -525,28 +1037,34 @@ impl ExpressionParser {
     }
 
     // NOT has the highest precedence
-    fn parse_not(&mut self) -> Option<BooleanExpr> {
-        if self.position < self.tokens.len() {
-            if matches!(self.tokens[self.position], Token::Not) {
-                self.position += 1;
-                let inner = self.parse_primary()?;
-                return Some(BooleanExpr::Not(Box::new(inner)));
-            }
+    fn parse_not(&mut self) -> Option<ConditionExpr> {
+        if self.position < self.tokens.len() && matches!(self.tokens[self.position], Token::Not) {
+            self.position += 1;
+            let inner = self.parse_primary()?;
+            return Some(ConditionExpr::Not(Box::new(inner)));
         }
 
         self.parse_primary()
     }
 
     // Primary expression: atomic condition or parenthesized expression
-    fn parse_primary(&mut self) -> Option<BooleanExpr> {
+    fn parse_primary(&mut self) -> Option<ConditionExpr> {
         if self.position >= self.tokens.len() {
             return None;
         }
 
         match &self.tokens[self.position] {
             Token::LParen => {
+                if self.depth >= self.max_depth {
+                    self.limit_exceeded = true;
+                    return None;
+                }
+
                 self.position += 1;
-                let expr = self.parse_or()?;
+                self.depth += 1;
+                let expr = self.parse_or();
+                self.depth -= 1;
+                let expr = expr?;
 
                 // Expect closing paren
                 if self.position < self.tokens.len() && matches!(self.tokens[self.position], Token::RParen) {
@@ -559,7 +1077,7 @@ impl ExpressionParser {
                 self.position += 1;
                 // Parse the atomic condition using standalone function
                 parse_atomic_condition_str(cond_str)
-                    .map(BooleanExpr::Atomic)
+                    .map(ConditionExpr::Atomic)
             }
             _ => None,
         }
@@ -572,80 +1090,188 @@ impl ExpressionParser {
 
 pub struct PathFinder;
 
+/// Caps on path enumeration for branchy machines, where the number of paths
+/// grows exponentially; `None` means unbounded
+#[derive(Debug, Clone)]
+pub struct PathFinderOptions {
+    /// Paths recorded per state before the search stops expanding it
+    pub max_paths_per_state: Option<usize>,
+    /// Transitions a path may take before the search stops extending it
+    pub max_depth: Option<usize>,
+}
+
+impl PathFinderOptions {
+    pub const DEFAULT_MAX_PATHS_PER_STATE: usize = 1000;
+    pub const DEFAULT_MAX_DEPTH: usize = 1000;
+}
+
+impl Default for PathFinderOptions {
+    fn default() -> Self {
+        Self {
+            max_paths_per_state: Some(Self::DEFAULT_MAX_PATHS_PER_STATE),
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+        }
+    }
+}
+
+/// Paths found by `PathFinder`, with the states whose path sets are incomplete
+#[derive(Debug, Default)]
+pub struct PathSearchResult {
+    pub paths: PathsByState,
+    /// States on a cycle the search declined to go around
+    pub cycle_truncated: HashSet<String>,
+    /// States that may miss paths because a `PathFinderOptions` limit was hit
+    pub limit_truncated: HashSet<String>,
+}
+
+/// State of one depth-first search
+struct PathSearch<'a> {
+    fsm: &'a FunctionBlock,
+    outgoing: HashMap<String, Vec<usize>>,
+    options: &'a PathFinderOptions,
+    visited: HashSet<String>,
+    current_path: TransitionPath,
+    result: PathSearchResult,
+}
+
 impl PathFinder {
     /// Find all paths from initial states to each state using DFS
-    pub fn find_all_paths(fsm: &FunctionBlock) -> HashMap<String, Vec<TransitionPath>> {
-        let mut paths_to_states: HashMap<String, Vec<TransitionPath>> = HashMap::new();
-        let initial_states = Self::find_initial_states(fsm);
+    pub fn find_all_paths(fsm: &FunctionBlock) -> Result<PathsByState, FsmError> {
+        Ok(Self::find_all_paths_with_options(fsm, &PathFinderOptions::default())?.paths)
+    }
 
-        let starting_states = if initial_states.is_empty() {
-            Self::find_fallback_initial_state(fsm)
-        } else {
-            initial_states
+    /// Like `find_all_paths`, also returning the states whose paths are
+    /// incomplete because of cycles or `options`
+    pub fn find_all_paths_with_options(
+        fsm: &FunctionBlock,
+        options: &PathFinderOptions,
+    ) -> Result<PathSearchResult, FsmError> {
+        let starting_states = FsmValidator::initial_states(fsm);
+
+        let mut search = PathSearch {
+            fsm,
+            outgoing: fsm.outgoing_transitions(),
+            options,
+            visited: HashSet::new(),
+            current_path: Vec::new(),
+            result: PathSearchResult::default(),
         };
-
         for initial in starting_states {
-            let mut visited = HashSet::new();
-            let mut current_path = vec![(initial.clone(), None)];
-            Self::dfs(
-                fsm,
-                &initial,
-                &mut visited,
-                &mut current_path,
-                &mut paths_to_states,
-            );
+            search.visited.clear();
+            search.current_path = vec![(initial.clone(), None)];
+            search.dfs(&initial);
         }
 
-        paths_to_states
+        Ok(search.result)
     }
 
-    fn find_initial_states(fsm: &FunctionBlock) -> Vec<String> {
-        fsm.states
-            .values()
-            .filter(|s| s.transitions_in.is_empty())
-            .map(|s| s.id.clone())
-            .collect()
+    /// Shortest state path from a starting state to `target`, with the guard
+    /// conditions that must hold along it. Of a disjunctive guard the first
+    /// term consistent with the conditions so far is taken.
+    pub fn reach_sequence(fsm: &FunctionBlock, target: &str) -> Option<(Vec<String>, Vec<Condition>)> {
+        let (start, route) = FsmValidator::initial_states(fsm)
+            .into_iter()
+            .filter_map(|start| {
+                let route = FsmValidator::shortest_path_transitions(fsm, &start, target)?;
+                Some((start, route))
+            })
+            .min_by_key(|(_, route)| route.len())?;
+
+        let mut states = vec![start];
+        let mut conditions: Vec<Condition> = Vec::new();
+        for transition in route.iter().map(|&idx| &fsm.transitions[idx]) {
+            states.push(transition.to_state.clone());
+
+            let guard = transition.condition.as_deref().unwrap_or_default();
+            let dnf = SignatureGenerator::parse_transition_condition(guard);
+            let consistent = |term: &Vec<Condition>| {
+                let joined = conditions.iter().chain(term).cloned().collect();
+                PathSignature::new(joined, 0).is_satisfiable()
+            };
+            if let Some(term) = dnf.iter().find(|term| consistent(term)).or(dnf.first()) {
+                for cond in term {
+                    if !conditions.contains(cond) {
+                        conditions.push(Condition { origin_transition_id: Some(transition.id.clone()), ..cond.clone() });
+                    }
+                }
+            }
+        }
+
+        Some((states, SignatureGenerator::tighten_ranges(conditions)))
     }
+}
 
-    fn find_fallback_initial_state(fsm: &FunctionBlock) -> Vec<String> {
-        if fsm.states.contains_key("100") {
-            vec!["100".to_string()]
-        } else if fsm.states.contains_key("10") {
-            vec!["10".to_string()]
-        } else if let Some(first_state) = fsm.states.keys().next() {
-            vec![first_state.clone()]
-        } else {
-            Vec::new()
+impl PathSearch<'_> {
+    fn dfs(&mut self, current_state: &str) {
+        if !self.record_path(current_state) {
+            // Whatever lies beyond may miss the paths through here
+            self.mark_reachable_from(current_state);
+            return;
         }
-    }
 
-    fn dfs(
-        fsm: &FunctionBlock,
-        current_state: &str,
-        visited: &mut HashSet<String>,
-        current_path: &mut TransitionPath,
-        paths_to_states: &mut HashMap<String, Vec<TransitionPath>>,
-    ) {
-        paths_to_states
-            .entry(current_state.to_string())
-            .or_insert_with(Vec::new)
-            .push(current_path.clone());
-
-        visited.insert(current_state.to_string());
-
-        for (trans_idx, transition) in fsm.transitions.iter().enumerate() {
-            if transition.from_state == current_state {
-                let next_state = &transition.to_state;
-
-                if !visited.contains(next_state) {
-                    current_path.push((next_state.clone(), Some(trans_idx)));
-                    Self::dfs(fsm, next_state, visited, current_path, paths_to_states);
-                    current_path.pop();
-                }
+        if self.options.max_depth.is_some_and(|max| self.current_path.len() > max) {
+            let successors: Vec<String> = self.successors(current_state).collect();
+            for next_state in successors {
+                self.mark_reachable_from(&next_state);
             }
+            return;
         }
 
-        visited.remove(current_state);
+        self.visited.insert(current_state.to_string());
+
+        let outgoing = self.outgoing.get(current_state).cloned().unwrap_or_default();
+        for trans_idx in outgoing {
+            let next_state = self.fsm.transitions[trans_idx].to_state.clone();
+
+            if !self.fsm.states.contains_key(&next_state) {
+                // A target computed at runtime is no state to find paths to
+                continue;
+            } else if next_state == current_state {
+                // Staying put under the loop guard is another way to be
+                // in this state; record it without descending again
+                self.current_path.push((next_state.clone(), Some(trans_idx)));
+                self.record_path(current_state);
+                self.current_path.pop();
+            } else if !self.visited.contains(&next_state) {
+                self.current_path.push((next_state.clone(), Some(trans_idx)));
+                self.dfs(&next_state);
+                self.current_path.pop();
+            } else if let Some(loop_start) = self.current_path.iter().position(|(s, _)| *s == next_state) {
+                // Every state on the closed loop misses the paths going around it
+                let on_loop = self.current_path[loop_start..].iter().map(|(s, _)| s.clone());
+                self.result.cycle_truncated.extend(on_loop);
+            }
+        }
+
+        self.visited.remove(current_state);
+    }
+
+    /// Store the current path to `state_id`, unless the state already has
+    /// as many paths as allowed
+    fn record_path(&mut self, state_id: &str) -> bool {
+        let paths = self.result.paths.entry(state_id.to_string()).or_default();
+        if self.options.max_paths_per_state.is_some_and(|max| paths.len() >= max) {
+            return false;
+        }
+        paths.push(self.current_path.clone());
+        true
+    }
+
+    fn successors(&self, state_id: &str) -> impl Iterator<Item = String> + '_ {
+        self.outgoing
+            .get(state_id)
+            .into_iter()
+            .flatten()
+            .map(|&idx| self.fsm.transitions[idx].to_state.clone())
+    }
+
+    fn mark_reachable_from(&mut self, state_id: &str) {
+        let mut stack = vec![state_id.to_string()];
+        while let Some(state) = stack.pop() {
+            if self.result.limit_truncated.insert(state.clone()) {
+                stack.extend(self.successors(&state));
+            }
+        }
     }
 }
 
@@ -653,61 +1279,240 @@ impl PathFinder {
 // SIGNATURE GENERATION
 // ============================================================================
 
+/// Tuning knobs for signature generation
+#[derive(Debug, Clone, Default)]
+pub struct SignatureOptions {
+    /// Evaluate literal-vs-literal comparisons (e.g. `1 = 1`) and drop them from guards
+    pub prune_guards: bool,
+    /// Reduce signatures over boolean variables to a minimal sum of products
+    pub minimize_boolean: bool,
+    /// Leave out path signatures whose conditions contradict each other
+    pub drop_contradictory: bool,
+    pub limits: ProcessingLimits,
+    pub path_limits: PathFinderOptions,
+}
+
+/// A way a signature table disagrees with the transition graph it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// The table has a signature for a state the block doesn't declare
+    UnknownState { state_id: String },
+    /// A condition claims to come from a transition the block doesn't have
+    UnknownTransition { state_id: String, transition_id: String },
+    /// No incoming transition's guard ends this way of reaching the state
+    NoMatchingIncoming { state_id: String, signature: String },
+}
+
+impl std::fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Inconsistency::UnknownState { state_id } => write!(f, "signature for unknown state {}", state_id),
+            Inconsistency::UnknownTransition { state_id, transition_id } => {
+                write!(f, "state {}: condition from unknown transition {}", state_id, transition_id)
+            }
+            Inconsistency::NoMatchingIncoming { state_id, signature } => {
+                write!(f, "state {}: no incoming transition explains [{}]", state_id, signature)
+            }
+        }
+    }
+}
+
+/// Above this many distinct variables, boolean minimization is skipped
+const MAX_MINIMIZED_VARIABLES: usize = 12;
+
 pub struct SignatureGenerator;
 
 impl SignatureGenerator {
-    pub fn generate(fsm: &FunctionBlock) -> StateSignatureTable {
+    pub fn generate(fsm: &FunctionBlock) -> Result<StateSignatureTable, FsmError> {
+        Self::generate_with_options(fsm, &SignatureOptions::default())
+    }
+
+    pub fn generate_with_options(
+        fsm: &FunctionBlock,
+        options: &SignatureOptions,
+    ) -> Result<StateSignatureTable, FsmError> {
         let mut table = StateSignatureTable::new(fsm.name.clone(), fsm.case_variable.clone());
-        let paths = PathFinder::find_all_paths(fsm);
+        let search = PathFinder::find_all_paths_with_options(fsm, &options.path_limits)?;
 
-        for (state_id, paths_to_state) in paths {
-            let signature = Self::build_signature_for_state(fsm, &state_id, &paths_to_state);
+        for (state_id, paths_to_state) in search.paths {
+            let mut signature = Self::build_signature_for_state(fsm, &state_id, &paths_to_state, options)?;
+            signature.reached_via_cycle_truncation = search.cycle_truncated.contains(&state_id);
+            signature.truncated_by_path_limits = search.limit_truncated.contains(&state_id);
             table.signatures.insert(state_id.clone(), signature);
         }
 
-        table
+        Ok(table)
+    }
+
+    /// Self-consistency check: every path signature of a state must end with
+    /// the guard of one of its incoming transitions, and every condition must
+    /// come from a transition of `fsm`. Start states may also be reached
+    /// without taking any transition.
+    pub fn verify_against_graph(fsm: &FunctionBlock, table: &StateSignatureTable) -> Vec<Inconsistency> {
+        let mut found = Vec::new();
+        let transition_ids: HashSet<&str> = fsm.transitions
+            .iter()
+            .chain(&fsm.init_transition)
+            .map(|t| t.id.as_str())
+            .collect();
+        let start_states = FsmValidator::initial_states(fsm);
+
+        let mut state_ids: Vec<&String> = table.signatures.keys().collect();
+        state_ids.sort();
+        for state_id in state_ids {
+            if !fsm.states.contains_key(state_id) {
+                found.push(Inconsistency::UnknownState { state_id: state_id.clone() });
+                continue;
+            }
+
+            // Each incoming guard as the conjunctions any path through it contains
+            let incoming: Vec<(&Transition, Vec<Vec<Condition>>)> = fsm.transitions
+                .iter()
+                .filter(|t| &t.to_state == state_id)
+                .map(|t| {
+                    let dnf = Self::parse_transition_condition(t.condition.as_deref().unwrap_or_default());
+                    (t, Self::drop_current_state_checks(dnf, &fsm.case_variable, &t.from_state))
+                })
+                .collect();
+
+            for path in &table.signatures[state_id].path_signatures {
+                for condition in &path.conditions {
+                    if let Some(origin) = &condition.origin_transition_id
+                        && !transition_ids.contains(origin.as_str()) {
+                        found.push(Inconsistency::UnknownTransition {
+                            state_id: state_id.clone(),
+                            transition_id: origin.clone(),
+                        });
+                    }
+                }
+
+                // An empty term (an unconditional transition) adds nothing, so
+                // the path must instead extend one into the transition's source
+                let contains_all = |conditions: &[Condition]| conditions.iter().all(|c| path.conditions.contains(c));
+                let explained = start_states.contains(state_id)
+                    || incoming.iter().any(|(transition, dnf)| {
+                        path.conditions.iter().any(|c| c.origin_transition_id.as_deref() == Some(transition.id.as_str()))
+                            || dnf.iter().any(|conjunction| {
+                                if conjunction.is_empty() {
+                                    table.signatures.get(&transition.from_state).is_some_and(|source| {
+                                        source.path_signatures.iter().any(|p| contains_all(&p.conditions))
+                                    })
+                                } else {
+                                    contains_all(conjunction)
+                                }
+                            })
+                    });
+                if !explained {
+                    found.push(Inconsistency::NoMatchingIncoming {
+                        state_id: state_id.clone(),
+                        signature: path.format_conditions(),
+                    });
+                }
+            }
+        }
+
+        found
     }
 
     fn build_signature_for_state(
         fsm: &FunctionBlock,
         state_id: &str,
         paths: &[TransitionPath],
-    ) -> StateSignature {
+        options: &SignatureOptions,
+    ) -> Result<StateSignature, FsmError> {
         let mut path_signatures = Vec::new();
         let mut signature_id = 0;
+        let mut contradictory_paths = 0;
 
         for path in paths.iter() {
-            let condition_sets = Self::extract_conditions_from_path(fsm, path);
+            let condition_sets = Self::extract_conditions_from_path(fsm, path, options)?;
 
             for conditions in condition_sets {
                 let unique_conditions = Self::remove_redundancy_in_path(conditions);
-                path_signatures.push(PathSignature::new(unique_conditions, signature_id));
+                let signature = PathSignature::new(unique_conditions, signature_id);
+                if !signature.is_satisfiable() {
+                    contradictory_paths += 1;
+                    if options.drop_contradictory {
+                        continue;
+                    }
+                }
+                path_signatures.push(signature);
                 signature_id += 1;
             }
         }
 
-        let optimized_signatures = Self::merge_equivalent_signatures(path_signatures);
+        let mut optimized_signatures = Self::merge_equivalent_signatures(path_signatures);
+        if options.minimize_boolean {
+            optimized_signatures = Self::minimize_boolean(&optimized_signatures);
+        }
 
-        StateSignature {
+        Ok(StateSignature {
             state_id: state_id.to_string(),
             path_signatures: optimized_signatures,
             paths_count: paths.len(),
-        }
+            reached_via_cycle_truncation: false,
+            truncated_by_path_limits: false,
+            contradictory_paths,
+        })
     }
 
-    fn extract_conditions_from_path(fsm: &FunctionBlock, path: &TransitionPath) -> Vec<Vec<Condition>> {
+    fn extract_conditions_from_path(
+        fsm: &FunctionBlock,
+        path: &TransitionPath,
+        options: &SignatureOptions,
+    ) -> Result<Vec<Vec<Condition>>, FsmError> {
         let mut transition_dnfs: Vec<Vec<Vec<Condition>>> = Vec::new();
 
+        // Paths rooted at the init target start under the init guard
+        if let Some(init) = &fsm.init_transition
+            && path.first().is_some_and(|(state_id, _)| *state_id == init.to_state) {
+            let mut dnf = Self::try_parse_transition_condition(init.condition.as_deref().unwrap_or_default(), &options.limits)?;
+            if options.prune_guards {
+                dnf = Self::prune_constant_guards(dnf);
+            }
+            transition_dnfs.push(Self::with_origin(dnf, &init.id));
+        }
+
         for (_state_id, transition_idx) in path {
-            if let Some(idx) = transition_idx {
-                if let Some(transition) = fsm.transitions.get(*idx) {
-                    let dnf = Self::parse_transition_condition(&transition.condition);
-                    transition_dnfs.push(dnf);
+            if let Some(idx) = transition_idx
+                && let Some(transition) = fsm.transitions.get(*idx) {
+                let mut dnf = Self::try_parse_transition_condition(
+                    transition.condition.as_deref().unwrap_or_default(),
+                    &options.limits,
+                )?;
+                if options.prune_guards {
+                    dnf = Self::prune_constant_guards(dnf);
                 }
+                let dnf = Self::drop_current_state_checks(dnf, &fsm.case_variable, &transition.from_state);
+                transition_dnfs.push(Self::with_origin(dnf, &transition.id));
             }
         }
 
-        Self::cross_product_dnf(transition_dnfs)
+        Ok(Self::cross_product_dnf(transition_dnfs))
+    }
+
+    fn with_origin(dnf: Vec<Vec<Condition>>, transition_id: &str) -> Vec<Vec<Condition>> {
+        dnf.into_iter()
+            .map(|conjunction| {
+                conjunction
+                    .into_iter()
+                    .map(|c| Condition { origin_transition_id: Some(transition_id.to_string()), ..c })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// A guard on a transition out of `from_state` that checks `case_variable = from_state`
+    /// holds on every path taking it, so it adds nothing to the signature
+    fn drop_current_state_checks(dnf: Vec<Vec<Condition>>, case_variable: &str, from_state: &str) -> Vec<Vec<Condition>> {
+        dnf.into_iter()
+            .map(|conjunction| {
+                conjunction
+                    .into_iter()
+                    .filter(|c| !(c.variable == case_variable && c.operator == "=" && c.value == from_state))
+                    .collect()
+            })
+            .collect()
     }
 
     fn cross_product_dnf(dnfs: Vec<Vec<Vec<Condition>>>) -> Vec<Vec<Condition>> {
@@ -734,29 +1539,31 @@ impl SignatureGenerator {
         result
     }
 
-    fn parse_transition_condition(condition_str: &str) -> Vec<Vec<Condition>> {
-        if condition_str.is_empty() || condition_str == "No Check" {
-            return vec![vec![]];
-        }
-
-        let mut tokenizer = Tokenizer::new(condition_str);
-        let tokens = tokenizer.tokenize();
+    /// Guard as DNF, falling back to a plain AND split when it can't be parsed
+    /// within the default limits
+    pub(crate) fn parse_transition_condition(condition_str: &str) -> Vec<Vec<Condition>> {
+        Self::try_parse_transition_condition(condition_str, &ProcessingLimits::default())
+            .unwrap_or_else(|_| Self::parse_simple_condition(condition_str))
+    }
 
-        if tokens.is_empty() {
-            return vec![vec![]];
+    pub(crate) fn try_parse_transition_condition(
+        condition_str: &str,
+        limits: &ProcessingLimits,
+    ) -> Result<Vec<Vec<Condition>>, FsmError> {
+        if condition_str.trim().is_empty() {
+            return Ok(vec![vec![]]);
         }
 
-        let mut parser = ExpressionParser::new(tokens);
-        let expr = match parser.parse() {
+        let expr = match ConditionExpr::parse_with_limits(condition_str, limits)? {
             Some(e) => e,
             None => {
-                return Self::parse_simple_condition(condition_str);
+                return Ok(Self::parse_simple_condition(condition_str));
             }
         };
 
-        let dnf = expr.to_dnf();
+        let dnf = expr.to_dnf_with_limits(limits)?;
 
-        dnf.into_iter()
+        Ok(dnf.into_iter()
             .map(|conjunction| {
                 let mut seen = HashSet::new();
                 let mut unique = Vec::new();
@@ -768,9 +1575,52 @@ impl SignatureGenerator {
                 }
                 unique
             })
+            .collect())
+    }
+
+    /// Drop always-true literal comparisons from each conjunction and remove
+    /// conjunctions containing an always-false one
+    pub(crate) fn prune_constant_guards(dnf: Vec<Vec<Condition>>) -> Vec<Vec<Condition>> {
+        dnf.into_iter()
+            .filter(|conjunction| {
+                !conjunction.iter().any(|c| Self::evaluate_constant(c) == Some(false))
+            })
+            .map(|conjunction| {
+                conjunction
+                    .into_iter()
+                    .filter(|c| Self::evaluate_constant(c).is_none())
+                    .collect()
+            })
             .collect()
     }
 
+    /// Truth value of a comparison whose both sides are literals, `None` otherwise
+    fn evaluate_constant(cond: &Condition) -> Option<bool> {
+        if let (Ok(lhs), Ok(rhs)) = (cond.variable.parse::<f64>(), cond.value.parse::<f64>()) {
+            return match cond.operator.as_str() {
+                "=" => Some(lhs == rhs),
+                "<>" => Some(lhs != rhs),
+                "<" => Some(lhs < rhs),
+                "<=" => Some(lhs <= rhs),
+                ">" => Some(lhs > rhs),
+                ">=" => Some(lhs >= rhs),
+                _ => None,
+            };
+        }
+
+        let parse_bool = |s: &str| match s.to_ascii_uppercase().as_str() {
+            "TRUE" => Some(true),
+            "FALSE" => Some(false),
+            _ => None,
+        };
+
+        match (parse_bool(&cond.variable), parse_bool(&cond.value), cond.operator.as_str()) {
+            (Some(lhs), Some(rhs), "=") => Some(lhs == rhs),
+            (Some(lhs), Some(rhs), "<>") => Some(lhs != rhs),
+            _ => None,
+        }
+    }
+
     fn parse_simple_condition(condition_str: &str) -> Vec<Vec<Condition>> {
         let mut conditions = Vec::new();
 
@@ -800,6 +1650,7 @@ impl SignatureGenerator {
             }
         }
 
+        let mut unique = Self::tighten_ranges(unique);
         unique.sort_by(|a, b| {
             a.variable
                 .cmp(&b.variable)
@@ -810,20 +1661,221 @@ impl SignatureGenerator {
         unique
     }
 
-    fn merge_equivalent_signatures(mut signatures: Vec<PathSignature>) -> Vec<PathSignature> {
+    /// Per variable, keep only the tightest numeric lower (`>`, `>=`) and upper
+    /// (`<`, `<=`) bound, and drop bounds an equality already implies.
+    /// Contradictory bounds are left for `PathSignature::is_satisfiable`.
+    fn tighten_ranges(conditions: Vec<Condition>) -> Vec<Condition> {
+        let bound = |c: &Condition| {
+            let side = match c.operator.as_str() {
+                ">" | ">=" => '>',
+                "<" | "<=" => '<',
+                _ => return None,
+            };
+            Some((side, PathSignature::numeric_value(&c.value)?))
+        };
+
+        let redundant: Vec<bool> = conditions
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let Some((side, va)) = bound(a) else {
+                    return false;
+                };
+                conditions.iter().enumerate().any(|(j, b)| {
+                    if i == j || b.variable != a.variable {
+                        return false;
+                    }
+                    if b.operator == "=" {
+                        return PathSignature::numeric_value(&b.value).is_some()
+                            && PathSignature::evaluate_condition(a, &b.value);
+                    }
+                    match bound(b) {
+                        Some((b_side, vb)) if b_side == side => {
+                            let tighter = if side == '>' { vb > va } else { vb < va };
+                            // On a tie the strict `>`/`<` beats `>=`/`<=`
+                            tighter || (vb == va && b.operator.len() < a.operator.len())
+                        }
+                        _ => false,
+                    }
+                })
+            })
+            .collect();
+
+        conditions
+            .into_iter()
+            .zip(redundant)
+            .filter(|(_, redundant)| !redundant)
+            .map(|(cond, _)| cond)
+            .collect()
+    }
+
+    fn merge_equivalent_signatures(signatures: Vec<PathSignature>) -> Vec<PathSignature> {
         if signatures.len() <= 1 {
             return signatures;
         }
 
-        let mut grouped: HashMap<String, PathSignature> = HashMap::new();
+        // First-seen order keeps output stable across runs
+        let mut grouped: IndexMap<Vec<Condition>, PathSignature> = IndexMap::new();
 
         for sig in signatures {
-            let key = sig.format_conditions();
-            grouped.entry(key).or_insert(sig);
+            grouped.entry(sig.conditions.clone()).or_insert(sig);
         }
 
         grouped.into_values().collect()
     }
+
+    /// Quine–McCluskey over signatures whose conditions are all `var = TRUE/FALSE`
+    /// (or `<>`), so `(a AND b) OR (a AND NOT b)` becomes `a`. Signatures with
+    /// other conditions, or too many variables, only get the usual merge.
+    pub fn minimize_boolean(sigs: &[PathSignature]) -> Vec<PathSignature> {
+        let merged = Self::merge_equivalent_signatures(sigs.to_vec());
+
+        let mut variables: Vec<&str> = Vec::new();
+        let mut terms: Vec<Vec<(usize, bool)>> = Vec::new();
+        for sig in sigs {
+            let mut literals = Vec::new();
+            for condition in &sig.conditions {
+                let Some(polarity) = Self::boolean_polarity(condition) else {
+                    return merged;
+                };
+                let index = variables.iter().position(|v| *v == condition.variable).unwrap_or_else(|| {
+                    variables.push(&condition.variable);
+                    variables.len() - 1
+                });
+                literals.push((index, polarity));
+            }
+            terms.push(literals);
+        }
+        if variables.len() > MAX_MINIMIZED_VARIABLES {
+            return merged;
+        }
+
+        // Bit i of a minterm is variable i
+        let width = variables.len();
+        let minterms: Vec<u32> = (0..1u32 << width)
+            .filter(|m| terms.iter().any(|t| t.iter().all(|&(i, pos)| (m >> i & 1 == 1) == pos)))
+            .collect();
+        if minterms.is_empty() {
+            return merged;
+        }
+
+        let cover = Self::minimal_cover(&Self::prime_implicants(&minterms), &minterms);
+        cover
+            .into_iter()
+            .enumerate()
+            .map(|(path_id, (value, dont_care))| {
+                let conditions = (0..width)
+                    .filter(|i| dont_care >> i & 1 == 0)
+                    .map(|i| {
+                        let literal = if value >> i & 1 == 1 { "TRUE" } else { "FALSE" };
+                        Condition::new(variables[i].to_string(), "=".to_string(), literal.to_string())
+                    })
+                    .collect();
+                PathSignature::new(conditions, path_id)
+            })
+            .collect()
+    }
+
+    /// Whether a condition requires its variable TRUE, `None` if it isn't boolean
+    fn boolean_polarity(condition: &Condition) -> Option<bool> {
+        let value = match condition.value.to_ascii_uppercase().as_str() {
+            "TRUE" => true,
+            "FALSE" => false,
+            _ => return None,
+        };
+        match condition.operator.as_str() {
+            "=" => Some(value),
+            "<>" => Some(!value),
+            _ => None,
+        }
+    }
+
+    /// Prime implicants as `(value, don't-care mask)` pairs
+    fn prime_implicants(minterms: &[u32]) -> Vec<(u32, u32)> {
+        let mut current: Vec<(u32, u32)> = minterms.iter().map(|&m| (m, 0)).collect();
+        let mut primes = Vec::new();
+
+        while !current.is_empty() {
+            let mut combined = vec![false; current.len()];
+            let mut next = Vec::new();
+
+            for i in 0..current.len() {
+                for j in i + 1..current.len() {
+                    let ((a, mask_a), (b, mask_b)) = (current[i], current[j]);
+                    let diff = a ^ b;
+                    if mask_a == mask_b && diff.count_ones() == 1 {
+                        combined[i] = true;
+                        combined[j] = true;
+                        let merged = (a & !diff, mask_a | diff);
+                        if !next.contains(&merged) {
+                            next.push(merged);
+                        }
+                    }
+                }
+            }
+
+            primes.extend(current.iter().zip(&combined).filter(|(_, c)| !**c).map(|(p, _)| *p));
+            current = next;
+        }
+
+        primes
+    }
+
+    /// Essential implicants first, then whichever covers most of the rest
+    fn minimal_cover(primes: &[(u32, u32)], minterms: &[u32]) -> Vec<(u32, u32)> {
+        let covers = |&(value, mask): &(u32, u32), m: u32| m & !mask == value;
+        let mut uncovered: Vec<u32> = minterms.to_vec();
+        let mut chosen: Vec<(u32, u32)> = Vec::new();
+
+        for &m in minterms {
+            let covering: Vec<_> = primes.iter().filter(|p| covers(p, m)).collect();
+            if let [only] = covering.as_slice()
+                && !chosen.contains(only) {
+                chosen.push(**only);
+            }
+        }
+        uncovered.retain(|&m| !chosen.iter().any(|p| covers(p, m)));
+
+        while !uncovered.is_empty() {
+            let Some(best) = primes
+                .iter()
+                .max_by_key(|p| (uncovered.iter().filter(|&&m| covers(p, m)).count(), p.1.count_ones()))
+            else {
+                break;
+            };
+            chosen.push(*best);
+            uncovered.retain(|&m| !covers(best, m));
+        }
+
+        chosen
+    }
+}
+
+// ============================================================================
+// GUARD EVALUATION
+// ============================================================================
+
+/// Evaluate a raw PLC guard against variable values: true when every condition
+/// of at least one DNF term holds. Variables missing from `vars` never match.
+///
+/// ```
+/// use plc_fsm_analyzer::evaluate_guard;
+/// use std::collections::HashMap;
+///
+/// let vars = HashMap::from([("a".to_string(), "1".to_string())]);
+/// assert!(evaluate_guard("a = 1 OR b = 2", &vars).unwrap());
+/// assert!(!evaluate_guard("a = 1 AND b = 2", &vars).unwrap());
+/// assert!(evaluate_guard("a = 1 AND", &vars).is_err());
+/// assert!(evaluate_guard("a = 1 )", &vars).is_err());
+/// ```
+pub fn evaluate_guard(guard: &str, vars: &HashMap<String, String>) -> Result<bool, FsmError> {
+    let expr = ConditionExpr::parse_whole_with_limits(guard, &ProcessingLimits::default())?
+        .ok_or_else(|| FsmError::InvalidGuard(guard.to_string()))?;
+
+    Ok(expr
+        .to_dnf_with_limits(&ProcessingLimits::default())?
+        .into_iter()
+        .any(|conjunction| PathSignature::new(conjunction, 0).matches(vars)))
 }
 
 // ============================================================================
@@ -833,29 +1885,9 @@ impl SignatureGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::{cyclic_block, linear_block};
     use crate::fsm::{FunctionBlock, State, Transition};
 
-    fn create_test_fsm() -> FunctionBlock {
-        let mut fb = FunctionBlock::new("TestFB".to_string(), "state".to_string());
-        fb.add_state(State::new("10".to_string()));
-        fb.add_state(State::new("20".to_string()));
-        fb.add_state(State::new("30".to_string()));
-        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
-        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
-        fb
-    }
-
-    fn create_cyclic_fsm() -> FunctionBlock {
-        let mut fb = FunctionBlock::new("CyclicFB".to_string(), "state".to_string());
-        fb.add_state(State::new("10".to_string()));
-        fb.add_state(State::new("20".to_string()));
-        fb.add_state(State::new("30".to_string()));
-        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
-        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
-        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "reset = true".to_string()));
-        fb
-    }
-
     fn create_multi_path_fsm() -> FunctionBlock {
         let mut fb = FunctionBlock::new("MultiPathFB".to_string(), "state".to_string());
         fb.add_state(State::new("10".to_string()));
@@ -867,10 +1899,241 @@ mod tests {
         fb
     }
 
+    #[test]
+    fn test_linear_path_accumulates_conditions() {
+        let fsm = linear_block();
+        let table = SignatureGenerator::generate(&fsm).unwrap();
+        let sig_30 = table.get_signature("30").unwrap();
+        assert_eq!(sig_30.path_signatures.len(), 1);
+        assert_eq!(sig_30.path_signatures[0].conditions.len(), 2);
+    }
+
+    #[test]
+    fn test_condition_carries_origin_transition() {
+        let fsm = linear_block();
+        let table = SignatureGenerator::generate(&fsm).unwrap();
+
+        let sig_20 = table.get_signature("20").unwrap();
+        let condition = &sig_20.path_signatures[0].conditions[0];
+        assert_eq!(condition.to_string(), "sensor = low");
+        assert_eq!(condition.origin_transition_id.as_deref(), Some(fsm.transitions[0].id.as_str()));
+    }
+
+    #[test]
+    fn test_current_state_check_dropped() {
+        let mut fsm = FunctionBlock::new("TestFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fsm.add_state(State::new(id.to_string()));
+        }
+        fsm.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fsm.add_transition(Transition::new("20".to_string(), "30".to_string(), "state = 20 AND sensor = high".to_string()));
+        let table = SignatureGenerator::generate(&fsm).unwrap();
+
+        let sig_30 = table.get_signature("30").unwrap();
+        assert!(sig_30.path_signatures[0].conditions.iter().all(|c| c.variable != "state"));
+        assert!(sig_30.format_conditions().contains("sensor = high"));
+    }
+
+    #[test]
+    fn test_cyclic_fsm_terminates() {
+        let fsm = cyclic_block();
+        let table = SignatureGenerator::generate(&fsm).unwrap();
+        assert_eq!(table.state_count(), 3);
+    }
+
+    #[test]
+    fn test_reach_sequence_conjoins_guards() {
+        let fsm = linear_block();
+        let (states, conditions) = PathFinder::reach_sequence(&fsm, "30").unwrap();
+
+        assert_eq!(states, ["10", "20", "30"]);
+        assert_eq!(PathSignature::new(conditions, 0).format_conditions(), "sensor = low AND sensor = high");
+        assert_eq!(PathFinder::reach_sequence(&fsm, "10").unwrap(), (vec!["10".to_string()], vec![]));
+        assert!(PathFinder::reach_sequence(&fsm, "99").is_none());
+    }
+
+    #[test]
+    fn test_large_block_visits_each_state_once() {
+        // Binary tree of 500 states whose leaves reset to the root
+        let mut fb = FunctionBlock::new("LargeFB".to_string(), "state".to_string());
+        for i in 0..500 {
+            fb.add_state(State::new(format!("S{}", i)));
+        }
+        for i in 0..500usize {
+            let children: Vec<usize> = [2 * i + 1, 2 * i + 2].into_iter().filter(|&c| c < 500).collect();
+            for &child in &children {
+                fb.add_transition(Transition::new(format!("S{}", i), format!("S{}", child), format!("go{} = TRUE", child)));
+            }
+            if children.is_empty() {
+                fb.add_transition(Transition::new(format!("S{}", i), "S0".to_string(), "reset = TRUE".to_string()));
+            }
+        }
+
+        let paths = PathFinder::find_all_paths(&fb).unwrap();
+        let unreachable = crate::analysis::FsmValidator::find_unreachable_states(&fb);
+
+        // Every search step records a path, so this counts the states visited
+        assert_eq!(paths.len(), 500);
+        assert_eq!(paths.values().map(Vec::len).sum::<usize>(), 500);
+        assert!(unreachable.is_empty());
+    }
+
+    #[test]
+    fn test_path_limit_truncates_branchy_fsm() {
+        // Diamond ladder: two ways through each of 8 stages gives 256 paths to the end
+        let mut fb = FunctionBlock::new("BranchyFB".to_string(), "state".to_string());
+        fb.add_state(State::new("S0".to_string()));
+        for stage in 1..=8 {
+            for id in [format!("A{}", stage), format!("B{}", stage), format!("S{}", stage)] {
+                fb.add_state(State::new(id));
+            }
+            let (prev, join) = (format!("S{}", stage - 1), format!("S{}", stage));
+            for branch in ["A", "B"] {
+                let mid = format!("{}{}", branch, stage);
+                fb.add_transition(Transition::new(prev.clone(), mid.clone(), format!("{}{} = TRUE", branch.to_lowercase(), stage)));
+                fb.add_transition(Transition::new(mid, join.clone(), format!("done{} = TRUE", stage)));
+            }
+        }
+
+        let options = SignatureOptions {
+            path_limits: PathFinderOptions { max_paths_per_state: Some(10), max_depth: None },
+            ..SignatureOptions::default()
+        };
+        let table = SignatureGenerator::generate_with_options(&fb, &options).unwrap();
+
+        let end = table.get_signature("S8").unwrap();
+        assert!(end.paths_count <= 10);
+        assert!(end.truncated_by_path_limits);
+        assert!(!table.get_signature("S2").unwrap().truncated_by_path_limits);
+        assert!(table.limited_states().contains(&"S8"));
+
+        let unlimited = SignatureGenerator::generate(&fb).unwrap();
+        assert_eq!(unlimited.get_signature("S8").unwrap().paths_count, 256);
+        assert!(unlimited.limited_states().is_empty());
+    }
+
+    #[test]
+    fn test_verify_against_graph() {
+        let fsm = create_multi_path_fsm();
+        let mut table = SignatureGenerator::generate(&fsm).unwrap();
+        assert!(SignatureGenerator::verify_against_graph(&fsm, &table).is_empty());
+
+        // State 30 is only entered under `timer > 100`
+        let sig_30 = table.signatures.get_mut("30").unwrap();
+        sig_30.path_signatures = vec![PathSignature::new(
+            vec![Condition::new("sensor".to_string(), "=".to_string(), "low".to_string())],
+            0,
+        )];
+        assert_eq!(
+            SignatureGenerator::verify_against_graph(&fsm, &table),
+            [Inconsistency::NoMatchingIncoming { state_id: "30".to_string(), signature: "sensor = low".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_verify_unconditional_incoming_needs_source_path() {
+        let mut fsm = create_multi_path_fsm();
+        fsm.add_state(State::new("40".to_string()));
+        fsm.add_transition(Transition::unconditional("30".to_string(), "40".to_string()));
+        let mut table = SignatureGenerator::generate(&fsm).unwrap();
+        assert!(SignatureGenerator::verify_against_graph(&fsm, &table).is_empty());
+
+        // No path into 30 is only `button = pressed`
+        let sig_40 = table.signatures.get_mut("40").unwrap();
+        sig_40.path_signatures = vec![PathSignature::new(
+            vec![Condition::new("button".to_string(), "=".to_string(), "pressed".to_string())],
+            0,
+        )];
+        assert_eq!(
+            SignatureGenerator::verify_against_graph(&fsm, &table),
+            [Inconsistency::NoMatchingIncoming { state_id: "40".to_string(), signature: "button = pressed".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_cycle_truncation_flag() {
+        let table = SignatureGenerator::generate(&cyclic_block()).unwrap();
+        for state_id in ["10", "20", "30"] {
+            assert!(table.get_signature(state_id).unwrap().reached_via_cycle_truncation);
+        }
+        assert_eq!(table.partial_states(), vec!["10", "20", "30"]);
+
+        let table = SignatureGenerator::generate(&linear_block()).unwrap();
+        assert!(table.partial_states().is_empty());
+    }
+
+    #[test]
+    fn test_self_loop_guard_in_signature() {
+        let mut fb = linear_block();
+        fb.add_transition(Transition::new("30".to_string(), "30".to_string(), "hold = TRUE".to_string()));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig_30 = table.get_signature("30").unwrap();
+        let formatted = sig_30.format_conditions();
+
+        assert_eq!(sig_30.paths_count, 2);
+        assert!(formatted.contains("sensor = high"));
+        assert!(formatted.contains("hold = TRUE"));
+        assert!(!sig_30.reached_via_cycle_truncation);
+    }
+
+    #[test]
+    fn test_contradictory_signatures() {
+        let conflicting_equalities = SignatureGenerator::parse_transition_condition("A = 1 AND A = 2");
+        assert!(!PathSignature::new(conflicting_equalities[0].clone(), 0).is_satisfiable());
+        let empty_range = SignatureGenerator::parse_transition_condition("x > 5 AND x < 3");
+        assert!(!PathSignature::new(empty_range[0].clone(), 0).is_satisfiable());
+        let narrow_range = SignatureGenerator::parse_transition_condition("x > 5 AND x < 5.2 AND A <> 1");
+        assert!(PathSignature::new(narrow_range[0].clone(), 0).is_satisfiable());
+
+        let mut fb = FunctionBlock::new("ContradictFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "A = 1".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "A = 2".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "B = TRUE".to_string()));
+
+        let kept = SignatureGenerator::generate(&fb).unwrap();
+        assert_eq!(kept.get_signature("30").unwrap().contradictory_paths, 1);
+        assert_eq!(kept.get_signature("30").unwrap().path_signatures.len(), 2);
+
+        let options = SignatureOptions { drop_contradictory: true, ..SignatureOptions::default() };
+        let dropped = SignatureGenerator::generate_with_options(&fb, &options).unwrap();
+        let sig_30 = dropped.get_signature("30").unwrap();
+        assert_eq!(sig_30.contradictory_paths, 1);
+        assert_eq!(sig_30.format_conditions(), "B = TRUE");
+    }
+
+    #[test]
+    fn test_range_conditions_tightened() {
+        let simplify = |guard: &str| {
+            let conditions = SignatureGenerator::parse_transition_condition(guard).remove(0);
+            PathSignature::new(SignatureGenerator::remove_redundancy_in_path(conditions), 0).format_conditions()
+        };
+
+        assert_eq!(simplify("level >= 10 AND level > 10"), "level > 10");
+        assert_eq!(simplify("level = 10 AND level >= 10"), "level = 10");
+        assert_eq!(simplify("level > 2 AND level > 5 AND level <= 8 AND level < 8"), "level < 8 AND level > 5");
+        assert_eq!(simplify("level = 1 AND level > 5"), "level = 1 AND level > 5");
+
+        let mut fb = FunctionBlock::new("RangeFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "x > 5 AND x > 4".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "x < 3".to_string()));
+
+        let options = SignatureOptions { drop_contradictory: true, ..SignatureOptions::default() };
+        let table = SignatureGenerator::generate_with_options(&fb, &options).unwrap();
+        assert_eq!(table.get_signature("20").unwrap().format_conditions(), "x > 5");
+        assert_eq!(table.get_signature("30").unwrap().format_conditions(), "[unsatisfiable]");
+    }
+
     #[test]
     fn test_multiple_path_signatures() {
         let fsm = create_multi_path_fsm();
-        let table = SignatureGenerator::generate(&fsm);
+        let table = SignatureGenerator::generate(&fsm).unwrap();
         let sig_20 = table.get_signature("20").unwrap();
         assert_eq!(sig_20.path_signatures.len(), 2);
     }
@@ -878,7 +2141,7 @@ mod tests {
     #[test]
     fn test_runtime_verification() {
         let fsm = create_multi_path_fsm();
-        let table = SignatureGenerator::generate(&fsm);
+        let table = SignatureGenerator::generate(&fsm).unwrap();
 
         let mut runtime_vars_a = HashMap::new();
         runtime_vars_a.insert("sensor".to_string(), "low".to_string());
@@ -892,6 +2155,43 @@ mod tests {
         assert!(!table.verify_state("20", &runtime_vars_c));
     }
 
+    #[test]
+    fn test_lenient_missing_variables() {
+        let signature = PathSignature::new(vec![
+            Condition::new("sensor".to_string(), "=".to_string(), "low".to_string()),
+            Condition::new("button".to_string(), "=".to_string(), "pressed".to_string()),
+        ], 0);
+        let runtime_vars = HashMap::from([("sensor".to_string(), "low".to_string())]);
+
+        assert!(!signature.matches(&runtime_vars));
+        assert!(signature.matches_with_mode(&runtime_vars, MatchMode::LenientMissing));
+
+        let wrong = HashMap::from([("sensor".to_string(), "high".to_string())]);
+        assert!(!signature.matches_with_mode(&wrong, MatchMode::LenientMissing));
+    }
+
+    #[test]
+    fn test_numeric_values_canonicalized() {
+        let a = SignatureGenerator::parse_transition_condition("x = 100");
+        let b = SignatureGenerator::parse_transition_condition("x = 100.0");
+        assert_eq!(a, b);
+        assert_eq!(b[0][0].value, "100");
+        assert_eq!(b[0][0].raw_value.as_deref(), Some("100.0"));
+        assert_eq!(b[0][0].to_string(), "x = 100.0");
+        assert_eq!(SignatureGenerator::parse_transition_condition("x = 1e2"), a);
+
+        let mut fb = FunctionBlock::new("NumFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "x = 100".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "x = 100.0".to_string()));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig_20 = table.get_signature("20").unwrap();
+        assert_eq!(sig_20.path_signatures.len(), 1);
+        assert_eq!(sig_20.format_conditions(), "x = 100");
+    }
+
     #[test]
     fn test_parse_simple_and() {
         let dnf = SignatureGenerator::parse_transition_condition("A = 1 AND B = 2");
@@ -911,6 +2211,122 @@ mod tests {
         assert_eq!(dnf.len(), 2);
     }
 
+    #[test]
+    fn test_prune_constant_guards() {
+        let sensor_high = vec![vec![Condition::new("sensor".to_string(), "=".to_string(), "high".to_string())]];
+
+        let dnf = SignatureGenerator::parse_transition_condition("1 = 1 AND sensor = high");
+        assert_eq!(SignatureGenerator::prune_constant_guards(dnf), sensor_high);
+
+        let dnf = SignatureGenerator::parse_transition_condition("1 = 0 OR sensor = high");
+        assert_eq!(SignatureGenerator::prune_constant_guards(dnf), sensor_high);
+    }
+
+    #[test]
+    fn test_init_guard_in_initial_signature() {
+        let mut fb = linear_block();
+        fb.init_transition = Some(Transition::new(
+            crate::fsm::INIT_STATE.to_string(),
+            "10".to_string(),
+            "firstScan = TRUE".to_string(),
+        ));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig_10 = table.get_signature("10").unwrap();
+        assert_eq!(sig_10.format_conditions(), "firstScan = TRUE");
+    }
+
+    #[test]
+    fn test_parse_xor_expansion() {
+        let cond = |v: &str, op: &str| Condition::new(v.to_string(), op.to_string(), "TRUE".to_string());
+
+        let dnf = SignatureGenerator::parse_transition_condition("a XOR b");
+        assert_eq!(dnf, vec![
+            vec![cond("a", "="), cond("b", "<>")],
+            vec![cond("a", "<>"), cond("b", "=")],
+        ]);
+    }
+
+    #[test]
+    fn test_parse_arithmetic_operands() {
+        let dnf = SignatureGenerator::parse_transition_condition("count MOD 2 = 0");
+        assert_eq!(dnf, vec![vec![Condition::new("count MOD 2".to_string(), "=".to_string(), "0".to_string())]]);
+
+        let dnf = SignatureGenerator::parse_transition_condition("flags AND 16#0F = 0 AND ready = TRUE");
+        assert_eq!(dnf.len(), 1);
+        assert_eq!(dnf[0][0], Condition::new("flags AND 16#0F".to_string(), "=".to_string(), "0".to_string()));
+        assert_eq!(dnf[0][1].variable, "ready");
+    }
+
+    #[test]
+    fn test_evaluate_guard_rejects_trailing_tokens() {
+        let vars = HashMap::from([("a".to_string(), "TRUE".to_string()), ("b".to_string(), "TRUE".to_string())]);
+        assert!(evaluate_guard("a AND b", &vars).unwrap());
+        assert!(matches!(evaluate_guard("a AND b )", &vars), Err(FsmError::InvalidGuard(_))));
+    }
+
+    #[test]
+    fn test_deep_nesting_hits_recursion_limit() {
+        let guard = format!("{}a = 1{}", "(".repeat(10_000), ")".repeat(10_000));
+        let limits = ProcessingLimits::default();
+
+        assert!(matches!(
+            ConditionExpr::parse_with_limits(&guard, &limits),
+            Err(FsmError::RecursionLimitExceeded(256))
+        ));
+
+        let mut fb = FunctionBlock::new("DeepFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), guard));
+        assert!(matches!(
+            SignatureGenerator::generate(&fb),
+            Err(FsmError::RecursionLimitExceeded(_))
+        ));
+
+        let shallow = ProcessingLimits { max_recursion_depth: 1 };
+        assert!(ConditionExpr::parse_with_limits("(a = 1)", &shallow).unwrap().is_some());
+        assert!(ConditionExpr::parse_with_limits("((a = 1))", &shallow).is_err());
+    }
+
+    #[test]
+    fn test_non_ascii_identifiers() {
+        let dnf = SignatureGenerator::parse_transition_condition("Füllstand > 10 AND Tür_offen = FALSE OR Störung");
+        assert_eq!(dnf.len(), 2);
+        assert_eq!(dnf[0][0], Condition::new("Füllstand".to_string(), ">".to_string(), "10".to_string()));
+        assert_eq!(dnf[0][1].variable, "Tür_offen");
+        assert_eq!(dnf[1][0].variable, "Störung");
+    }
+
+    #[test]
+    fn test_long_chain_within_recursion_limit() {
+        let mut fb = FunctionBlock::new("ChainFB".to_string(), "state".to_string());
+        for i in 0..300 {
+            fb.add_state(State::new(i.to_string()));
+        }
+        for i in 0..299 {
+            fb.add_transition(Transition::new(i.to_string(), (i + 1).to_string(), format!("step{} = TRUE", i)));
+        }
+
+        let paths = PathFinder::find_all_paths(&fb).unwrap();
+        assert_eq!(paths["299"][0].len(), 300);
+    }
+
+    #[test]
+    fn test_natural_language_conditions() {
+        let cond = Condition::new("temp".to_string(), ">".to_string(), "50".to_string());
+        assert_eq!(cond.to_natural_language(), "temp is greater than 50");
+
+        let cond = Condition::new("ready".to_string(), "<>".to_string(), "TRUE".to_string());
+        assert_eq!(cond.to_natural_language(), "ready is FALSE");
+
+        let table = SignatureGenerator::generate(&create_multi_path_fsm()).unwrap();
+        let phrased = table.get_signature("20").unwrap().to_natural_language();
+        assert!(phrased.contains("when sensor equals low"));
+        assert!(phrased.contains("when button equals pressed"));
+        assert!(phrased.contains(", or when "));
+    }
+
     #[test]
     fn test_fsm_with_or_condition() {
         let mut fb = FunctionBlock::new("OrTestFB".to_string(), "state".to_string());
@@ -918,8 +2334,45 @@ mod tests {
         fb.add_state(State::new("20".to_string()));
         fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low OR button = pressed".to_string()));
 
-        let table = SignatureGenerator::generate(&fb);
+        let table = SignatureGenerator::generate(&fb).unwrap();
         let sig_20 = table.get_signature("20").unwrap();
         assert_eq!(sig_20.path_signatures.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_arithmetic_operands_normalized() {
+        let sum = parse_atomic_condition_str("a+b > c").unwrap();
+        assert_eq!(sum, Condition::new("a + b".to_string(), ">".to_string(), "c".to_string()));
+        assert_eq!(parse_atomic_condition_str("a  +  b>c"), Some(sum));
+
+        let difference = parse_atomic_condition_str("a - 1 = b").unwrap();
+        assert_eq!((difference.variable.as_str(), difference.value.as_str()), ("a - 1", "b"));
+
+        let grouped = parse_atomic_condition_str("(level + offset) > (limit - 1e-3)").unwrap();
+        assert_eq!((grouped.variable.as_str(), grouped.value.as_str()), ("level + offset", "limit - 1e-3"));
+        assert_eq!(parse_atomic_condition_str("x > -5").unwrap().value, "-5");
+
+        let dnf = SignatureGenerator::parse_transition_condition("a + b > c AND a - 1 = b");
+        let rendered: Vec<String> = dnf[0].iter().map(|c| c.to_string()).collect();
+        assert_eq!(rendered, ["a + b > c", "a - 1 = b"]);
+    }
+
+    #[test]
+    fn test_minimize_boolean_signatures() {
+        let literal = |variable: &str, value: &str| Condition::new(variable.to_string(), "=".to_string(), value.to_string());
+        let sigs = vec![
+            PathSignature::new(vec![literal("a", "TRUE"), literal("b", "TRUE")], 0),
+            PathSignature::new(vec![literal("a", "TRUE"), literal("b", "FALSE")], 1),
+        ];
+
+        let minimized = SignatureGenerator::minimize_boolean(&sigs);
+        assert_eq!(minimized.len(), 1);
+        assert_eq!(minimized[0].format_conditions(), "a = TRUE");
+
+        let mixed = vec![
+            PathSignature::new(vec![literal("a", "TRUE"), literal("level", "5")], 0),
+            PathSignature::new(vec![literal("a", "TRUE"), literal("b", "FALSE")], 1),
+        ];
+        assert_eq!(SignatureGenerator::minimize_boolean(&mixed).len(), 2);
+    }
+}
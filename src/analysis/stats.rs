@@ -1,4 +1,5 @@
-use crate::fsm::FunctionBlock;
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FsmStatistics {
@@ -6,17 +7,104 @@ pub struct FsmStatistics {
     pub total_transitions: usize,
     pub avg_transitions_per_state: f64,
     pub max_transitions_from_state: usize,
+    pub max_transitions_into_state: usize,
+    pub scc_count: usize,
+    /// Cyclomatic complexity of the transition graph (E - N + 2)
+    pub cyclomatic_complexity: i64,
     pub unreachable_states: Vec<String>,
     pub dead_states: Vec<String>,
+    /// States ending the machine on purpose, left out of `dead_states`
+    #[serde(default)]
+    pub terminal_states: Vec<String>,
     pub cycles: Vec<Vec<String>>,
+    /// `cycles` stops at the cycle cap; more exist
+    #[serde(default)]
+    pub cycles_truncated: bool,
+    /// State -> number of elementary cycles it is part of (cycle-free states omitted)
+    pub cycle_membership: HashMap<String, usize>,
+    /// No state has two outgoing transitions that can fire at once
+    pub is_deterministic: bool,
+    /// State -> fewest transitions needed to reach it from the start (unreachable states omitted)
+    #[serde(default)]
+    pub state_depths: HashMap<String, usize>,
+    /// Deepest of `state_depths`
+    #[serde(default)]
+    pub max_depth: usize,
+}
+
+/// Verdict on a block: `Problem` for dead-end states, `Review` for
+/// unreachable states, `Ok` otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    Ok,
+    Review,
+    Problem,
+}
+
+impl std::fmt::Display for BlockStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BlockStatus::Ok => "OK",
+            BlockStatus::Review => "REVIEW",
+            BlockStatus::Problem => "PROBLEM",
+        })
+    }
+}
+
+/// Totals over every function block of a `FiniteStateMachine`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct AggregateStatistics {
+    pub function_blocks: usize,
+    pub total_states: usize,
+    pub total_transitions: usize,
+    pub total_cycles: usize,
+    pub total_unreachable_states: usize,
+    pub total_dead_states: usize,
+    /// Highest cyclomatic complexity of any block
+    pub max_cyclomatic_complexity: i64,
+    /// Block with that complexity
+    pub most_complex_block: Option<String>,
 }
 
 impl FsmStatistics {
+    /// Analyze every block of `fsm` and sum the results
+    pub fn analyze_fsm(fsm: &FiniteStateMachine) -> AggregateStatistics {
+        let mut aggregate = AggregateStatistics::default();
+
+        for fb in &fsm.function_blocks {
+            let stats = Self::analyze(fb);
+
+            aggregate.function_blocks += 1;
+            aggregate.total_states += stats.total_states;
+            aggregate.total_transitions += stats.total_transitions;
+            aggregate.total_cycles += stats.cycles.len();
+            aggregate.total_unreachable_states += stats.unreachable_states.len();
+            aggregate.total_dead_states += stats.dead_states.len();
+
+            if aggregate.most_complex_block.is_none()
+                || stats.cyclomatic_complexity > aggregate.max_cyclomatic_complexity {
+                aggregate.max_cyclomatic_complexity = stats.cyclomatic_complexity;
+                aggregate.most_complex_block = Some(fb.name.clone());
+            }
+        }
+
+        aggregate
+    }
+
     pub fn analyze(fsm: &FunctionBlock) -> Self {
-        use super::{FsmValidator, CycleDetector};
+        Self::analyze_with_cycle_limit(fsm, None)
+    }
+
+    /// Like `analyze`, but stops elementary-cycle enumeration after `max_cycles`
+    /// (`DEFAULT_MAX_CYCLES` when `None`) so `cycles` and `cycle_membership`
+    /// stay cheap on highly cyclic blocks
+    pub fn analyze_with_cycle_limit(fsm: &FunctionBlock, max_cycles: Option<usize>) -> Self {
+        use super::{FsmValidator, CycleDetector, cycles::DEFAULT_MAX_CYCLES};
 
         let total_states = fsm.state_count();
         let total_transitions = fsm.transition_count();
+        let max_cycles = max_cycles.unwrap_or(DEFAULT_MAX_CYCLES);
+        let (cycles, cycles_truncated) = CycleDetector::find_elementary_cycles_limited(fsm, Some(max_cycles));
 
         let avg_transitions_per_state = if total_states > 0 {
             total_transitions as f64 / total_states as f64
@@ -30,14 +118,181 @@ impl FsmStatistics {
             .max()
             .unwrap_or(0);
 
+        let max_transitions_into_state = fsm.states
+            .values()
+            .map(|s| s.transitions_in.len())
+            .max()
+            .unwrap_or(0);
+
+        let cyclomatic_complexity = if total_states > 0 {
+            total_transitions as i64 - total_states as i64 + 2
+        } else {
+            0
+        };
+
+        let state_depths = FsmValidator::state_depths(fsm);
+
         Self {
             total_states,
             total_transitions,
             avg_transitions_per_state,
             max_transitions_from_state,
+            max_transitions_into_state,
+            scc_count: CycleDetector::strongly_connected_components(fsm).len(),
+            cyclomatic_complexity,
             unreachable_states: FsmValidator::find_unreachable_states(fsm),
             dead_states: FsmValidator::find_dead_states(fsm),
-            cycles: CycleDetector::find_cycles(fsm),
+            terminal_states: FsmValidator::find_terminal_states(fsm),
+            cycle_membership: CycleDetector::membership_of(&cycles),
+            cycles,
+            cycles_truncated,
+            is_deterministic: FsmValidator::is_deterministic(fsm),
+            max_depth: state_depths.values().copied().max().unwrap_or(0),
+            state_depths,
+        }
+    }
+
+    pub fn is_cycle_member(&self, state_id: &str) -> bool {
+        self.cycle_membership.contains_key(state_id)
+    }
+
+    pub fn status(&self) -> BlockStatus {
+        if !self.dead_states.is_empty() {
+            BlockStatus::Problem
+        } else if !self.unreachable_states.is_empty() {
+            BlockStatus::Review
+        } else {
+            BlockStatus::Ok
         }
     }
+
+    /// `Pump: 12 states, 2 cycles, 1 unreachable, 0 dead — REVIEW`
+    pub fn summary_line(&self, name: &str) -> String {
+        format!(
+            "{}: {} states, {}{} cycles, {} unreachable, {} dead — {}",
+            name,
+            self.total_states,
+            self.cycles.len(),
+            if self.cycles_truncated { "+" } else { "" },
+            self.unreachable_states.len(),
+            self.dead_states.len(),
+            self.status()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_cycle_membership_shared_node() {
+        let mut fb = FunctionBlock::new("LoopFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("30".to_string()));
+        fb.add_state(State::new("40".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "10".to_string(), "abort = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "ready = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "20".to_string(), "retry = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "40".to_string(), "done = TRUE".to_string()));
+
+        let stats = FsmStatistics::analyze(&fb);
+
+        assert_eq!(stats.cycle_membership.get("20"), Some(&2));
+        assert_eq!(stats.cycle_membership.get("10"), Some(&1));
+        assert_eq!(stats.cycle_membership.get("30"), Some(&1));
+        assert!(!stats.is_cycle_member("40"));
+    }
+
+    #[test]
+    fn test_summary_line_flags_unreachable_state() {
+        let mut fb = FunctionBlock::new("Pump".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "10".to_string(), "stop = TRUE".to_string()));
+        // A loop nothing enters: the run starts from the first state
+        fb.add_transition(Transition::new("30".to_string(), "40".to_string(), "flush = TRUE".to_string()));
+        fb.add_transition(Transition::new("40".to_string(), "30".to_string(), "refill = TRUE".to_string()));
+
+        let stats = FsmStatistics::analyze(&fb);
+        assert_eq!(stats.unreachable_states.len(), 2);
+        assert_ne!(stats.status(), BlockStatus::Ok);
+        assert_eq!(stats.summary_line("Pump"), "Pump: 4 states, 2 cycles, 2 unreachable, 0 dead — REVIEW");
+    }
+
+    #[test]
+    fn test_analyze_fsm_sums_blocks() {
+        let mut pump = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        pump.add_state(State::new("10".to_string()));
+        pump.add_state(State::new("20".to_string()));
+        pump.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        pump.add_transition(Transition::new("20".to_string(), "10".to_string(), "stop = TRUE".to_string()));
+
+        let mut valve = FunctionBlock::new("ValveFB".to_string(), "step".to_string());
+        valve.add_state(State::new("0".to_string()));
+        valve.add_state(State::new("1".to_string()));
+        valve.add_state(State::new("2".to_string()));
+        valve.add_state(State::new("9".to_string()));
+        valve.add_transition(Transition::new("0".to_string(), "1".to_string(), "open = TRUE".to_string()));
+        valve.add_transition(Transition::new("1".to_string(), "2".to_string(), "opened = TRUE".to_string()));
+
+        let per_block = [FsmStatistics::analyze(&pump), FsmStatistics::analyze(&valve)];
+        let fsm = machine("plant.xml", vec![pump, valve]);
+
+        let aggregate = FsmStatistics::analyze_fsm(&fsm);
+
+        assert_eq!(aggregate.function_blocks, 2);
+        assert_eq!(aggregate.total_states, per_block.iter().map(|s| s.total_states).sum::<usize>());
+        assert_eq!(aggregate.total_transitions, per_block.iter().map(|s| s.total_transitions).sum::<usize>());
+        assert_eq!(aggregate.total_cycles, per_block.iter().map(|s| s.cycles.len()).sum::<usize>());
+        assert_eq!(aggregate.total_unreachable_states, per_block.iter().map(|s| s.unreachable_states.len()).sum::<usize>());
+        assert_eq!(aggregate.total_dead_states, per_block.iter().map(|s| s.dead_states.len()).sum::<usize>());
+        assert_eq!(aggregate.max_cyclomatic_complexity, 2);
+        assert_eq!(aggregate.most_complex_block.as_deref(), Some("PumpFB"));
+    }
+
+    #[test]
+    fn test_state_depths_from_initial() {
+        let mut fb = FunctionBlock::new("TestFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "99"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+        fb.add_transition(Transition::new("99".to_string(), "99".to_string(), "hold = TRUE".to_string()));
+
+        let stats = FsmStatistics::analyze(&fb);
+        let expected: HashMap<String, usize> = [("10", 0), ("20", 1), ("30", 2)]
+            .into_iter()
+            .map(|(id, depth)| (id.to_string(), depth))
+            .collect();
+        assert_eq!(stats.state_depths, expected);
+        assert_eq!(stats.max_depth, 2);
+    }
+
+    #[test]
+    fn test_state_depths_start_at_init_target() {
+        let mut fb = FunctionBlock::new("LoopFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "ready = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "done = TRUE".to_string()));
+        fb.init_transition = Some(Transition::unconditional(crate::fsm::INIT_STATE.to_string(), "20".to_string()));
+
+        let stats = FsmStatistics::analyze(&fb);
+        let expected: HashMap<String, usize> = [("20", 0), ("30", 1), ("10", 2)]
+            .into_iter()
+            .map(|(id, depth)| (id.to_string(), depth))
+            .collect();
+        assert_eq!(stats.state_depths, expected);
+        assert_eq!(stats.max_depth, 2);
+    }
 }
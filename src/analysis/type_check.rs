@@ -0,0 +1,111 @@
+use crate::analysis::signatures::{Condition, SignatureGenerator};
+use crate::fsm::FunctionBlock;
+use indexmap::IndexMap;
+
+/// Category of the value a guard compares a variable against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Bool,
+    Int,
+    Float,
+    String,
+    /// Bare identifier: an enumeration value, constant or another variable
+    Enum,
+}
+
+impl ValueKind {
+    pub fn classify(value: &str) -> Self {
+        let value = value.trim();
+        let upper = value.to_ascii_uppercase();
+
+        if upper == "TRUE" || upper == "FALSE" {
+            ValueKind::Bool
+        } else if value.starts_with('\'') || value.starts_with('"') {
+            ValueKind::String
+        } else if value.parse::<i64>().is_ok() || Self::is_based_integer(value) {
+            ValueKind::Int
+        } else if value.parse::<f64>().is_ok() {
+            ValueKind::Float
+        } else {
+            ValueKind::Enum
+        }
+    }
+
+    /// Integers and reals compare fine; an identifier may hold any type
+    fn compatible(self, other: Self) -> bool {
+        let numeric = |kind| matches!(kind, ValueKind::Int | ValueKind::Float);
+        self == other
+            || (numeric(self) && numeric(other))
+            || self == ValueKind::Enum
+            || other == ValueKind::Enum
+    }
+
+    /// `16#FF`, `2#1010`, `8#17`
+    fn is_based_integer(value: &str) -> bool {
+        value.split_once('#').is_some_and(|(base, digits)| {
+            base.parse::<u32>().is_ok_and(|base| (2..=36).contains(&base))
+                && !digits.is_empty()
+                && digits.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    }
+}
+
+/// Variables compared against values of incompatible kinds in `fb`'s guards,
+/// each with the conditions involved, sorted by variable name
+pub fn find_type_inconsistent_variables(fb: &FunctionBlock) -> Vec<(String, Vec<String>)> {
+    let mut comparisons: IndexMap<String, Vec<(ValueKind, Condition)>> = IndexMap::new();
+
+    let guards = fb.init_transition.iter().chain(&fb.transitions).filter_map(|t| t.condition.as_deref());
+    for guard in guards {
+        for cond in SignatureGenerator::parse_transition_condition(guard).into_iter().flatten() {
+            let seen = comparisons.entry(cond.variable.clone()).or_default();
+            if !seen.iter().any(|(_, existing)| *existing == cond) {
+                seen.push((ValueKind::classify(&cond.value), cond));
+            }
+        }
+    }
+
+    let mut inconsistent: Vec<(String, Vec<String>)> = comparisons
+        .into_iter()
+        .filter(|(_, seen)| {
+            seen.iter().any(|(a, _)| seen.iter().any(|(b, _)| !a.compatible(*b)))
+        })
+        .map(|(variable, seen)| {
+            let conditions = seen
+                .into_iter()
+                .filter(|(kind, _)| *kind != ValueKind::Enum)
+                .map(|(_, cond)| cond.to_string())
+                .collect();
+            (variable, conditions)
+        })
+        .collect();
+
+    inconsistent.sort_by(|a, b| a.0.cmp(&b.0));
+    inconsistent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_bool_and_int_comparisons_flagged() {
+        let mut fb = FunctionBlock::new("MixFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "flag = TRUE AND speed > 1.5".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "flag = 5 OR speed < 3".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "mode = AUTO".to_string()));
+
+        let inconsistent = find_type_inconsistent_variables(&fb);
+
+        assert_eq!(inconsistent, vec![(
+            "flag".to_string(),
+            vec!["flag = TRUE".to_string(), "flag = 5".to_string()],
+        )]);
+        assert_eq!(ValueKind::classify("16#0F"), ValueKind::Int);
+        assert_eq!(ValueKind::classify("'on'"), ValueKind::String);
+    }
+}
@@ -1,8 +1,55 @@
+use crate::analysis::signatures::{Condition, PathSignature, SignatureGenerator};
 use crate::fsm::FunctionBlock;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub struct FsmValidator;
 
+/// A guard condition comparing a variable with itself, usually a copy-paste slip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfComparison {
+    pub transition_id: String,
+    pub condition: String,
+    /// Always true (`x = x`) rather than always false (`x <> x`)
+    pub tautology: bool,
+}
+
+/// Entry states of a function block: those without incoming transitions plus
+/// the target of the initialization ahead of the CASE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryReport {
+    pub initial_states: Vec<String>,
+}
+
+impl EntryReport {
+    pub fn is_single_entry(&self) -> bool {
+        self.initial_states.len() == 1
+    }
+
+    /// Every state has an incoming edge, so the machine is a pure cycle
+    pub fn has_no_entry(&self) -> bool {
+        self.initial_states.is_empty()
+    }
+
+    pub fn has_multiple_entries(&self) -> bool {
+        self.initial_states.len() > 1
+    }
+}
+
+/// Mismatches between the variables a block declares and those its guards read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceReport {
+    /// Declared inputs no guard reads, in declaration order
+    pub unused_inputs: Vec<String>,
+    /// Variables read by guards that the block doesn't declare, sorted
+    pub undeclared_variables: Vec<String>,
+}
+
+impl InterfaceReport {
+    pub fn is_clean(&self) -> bool {
+        self.unused_inputs.is_empty() && self.undeclared_variables.is_empty()
+    }
+}
+
 impl FsmValidator {
     pub fn find_unreachable_states(fsm: &FunctionBlock) -> Vec<String> {
         if fsm.states.is_empty() {
@@ -10,39 +57,17 @@ impl FsmValidator {
         }
 
         let mut reachable = HashSet::new();
-        let mut queue = VecDeque::new();
-
-        // Find initial states (states with no incoming transitions)
-        let initial_states: Vec<_> = fsm.states
-            .values()
-            .filter(|s| s.transitions_in.is_empty())
-            .map(|s| s.id.clone())
-            .collect();
-
-        // If no initial states found, use the first state or state "100"
-        if initial_states.is_empty() {
-            if fsm.states.contains_key("100") {
-                queue.push_back("100".to_string());
-            } else if let Some(first) = fsm.states.keys().next() {
-                queue.push_back(first.clone());
-            }
-        } else {
-            for state in initial_states {
-                queue.push_back(state);
-            }
-        }
+        let mut queue: VecDeque<String> = Self::initial_states(fsm).into();
 
         // BFS to find all reachable states
+        let outgoing = fsm.outgoing_transitions();
         while let Some(state_id) = queue.pop_front() {
             if !reachable.insert(state_id.clone()) {
                 continue;
             }
 
-            // Find all transitions from this state
-            for transition in &fsm.transitions {
-                if transition.from_state == state_id {
-                    queue.push_back(transition.to_state.clone());
-                }
+            for &idx in outgoing.get(&state_id).into_iter().flatten() {
+                queue.push_back(fsm.transitions[idx].to_state.clone());
             }
         }
 
@@ -54,14 +79,383 @@ impl FsmValidator {
             .collect()
     }
 
+    /// Indices of transitions repeating an earlier one exactly: same source,
+    /// target, guard and actions
+    pub fn find_duplicate_transitions(fsm: &FunctionBlock) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        fsm.transitions
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| !seen.insert((&t.from_state, &t.to_state, &t.condition, &t.actions)))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Indices of transitions whose id an earlier transition with a different
+    /// guard already has, so looking them up by id finds the wrong one
+    pub fn find_transition_id_collisions(fsm: &FunctionBlock) -> Vec<usize> {
+        let mut first_with_id: HashMap<&str, &Option<String>> = HashMap::new();
+        fsm.transitions
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| {
+                let first = *first_with_id.entry(&t.id).or_insert(&t.condition);
+                first != &t.condition
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// States without outgoing transitions, other than those marked terminal
     pub fn find_dead_states(fsm: &FunctionBlock) -> Vec<String> {
         fsm.states
             .values()
-            .filter(|s| s.transitions_out.is_empty())
+            .filter(|s| s.transitions_out.is_empty() && !s.terminal)
+            .map(|s| s.id.clone())
+            .collect()
+    }
+
+    /// States marked as intentionally ending the machine that indeed have no
+    /// way out
+    pub fn find_terminal_states(fsm: &FunctionBlock) -> Vec<String> {
+        fsm.states
+            .values()
+            .filter(|s| s.terminal && s.transitions_out.is_empty())
+            .map(|s| s.id.clone())
+            .collect()
+    }
+
+    /// States from which no state in `safe` can be reached, found by walking
+    /// transitions backwards from the safe set
+    pub fn find_safety_violations(fsm: &FunctionBlock, safe: &HashSet<String>) -> Vec<String> {
+        let mut can_reach_safe = HashSet::new();
+        let mut queue: VecDeque<String> = safe
+            .iter()
+            .filter(|id| fsm.states.contains_key(*id))
+            .cloned()
+            .collect();
+
+        while let Some(state_id) = queue.pop_front() {
+            if !can_reach_safe.insert(state_id.clone()) {
+                continue;
+            }
+
+            for transition in &fsm.transitions {
+                if transition.to_state == state_id {
+                    queue.push_back(transition.from_state.clone());
+                }
+            }
+        }
+
+        let mut violations: Vec<String> = fsm.states
+            .keys()
+            .filter(|id| !can_reach_safe.contains(*id))
+            .cloned()
+            .collect();
+        violations.sort();
+        violations
+    }
+
+    /// States the block may start in: the init target first, then the states
+    /// without incoming transitions; when there are neither, state "100", "10"
+    /// or the first state
+    pub fn initial_states(fsm: &FunctionBlock) -> Vec<String> {
+        let mut initial: Vec<String> = fsm.init_transition
+            .iter()
+            .map(|init| init.to_state.clone())
+            .filter(|target| fsm.states.contains_key(target))
+            .collect();
+        initial.extend(
+            fsm.states
+                .values()
+                .filter(|s| s.transitions_in.is_empty() && !initial.contains(&s.id))
+                .map(|s| s.id.clone())
+                .collect::<Vec<_>>(),
+        );
+        if !initial.is_empty() {
+            return initial;
+        }
+
+        ["100", "10"]
+            .into_iter()
+            .find(|id| fsm.states.contains_key(*id))
+            .map(str::to_string)
+            .or_else(|| fsm.states.keys().next().cloned())
+            .into_iter()
+            .collect()
+    }
+
+    /// The state the block starts in: the first of `initial_states`
+    pub fn initial_state(fsm: &FunctionBlock) -> Option<String> {
+        Self::initial_states(fsm).into_iter().next()
+    }
+
+    pub fn check_single_entry(fsm: &FunctionBlock) -> EntryReport {
+        let mut initial_states: Vec<String> = fsm.states
+            .values()
+            .filter(|s| s.transitions_in.is_empty())
             .map(|s| s.id.clone())
+            .collect();
+
+        if let Some(init) = &fsm.init_transition
+            && fsm.states.contains_key(&init.to_state)
+            && !initial_states.contains(&init.to_state) {
+            initial_states.push(init.to_state.clone());
+        }
+
+        initial_states.sort();
+        EntryReport { initial_states }
+    }
+
+    /// No state has two outgoing transitions that can fire at once
+    pub fn is_deterministic(fsm: &FunctionBlock) -> bool {
+        Self::find_nondeterministic_states(fsm).is_empty()
+    }
+
+    /// States with two outgoing transitions that can fire in the same scan:
+    /// identical guards (including two unconditional ones), or guards leading to
+    /// different states that some input satisfies together. Transitions from
+    /// separate IF statements never conflict, as the last one taken wins
+    pub fn find_nondeterministic_states(fsm: &FunctionBlock) -> Vec<String> {
+        Self::find_nondeterministic_transitions(fsm)
+            .into_iter()
+            .map(|(state_id, _)| state_id)
             .collect()
     }
 
+    /// Indices into `fsm.transitions` of the transitions out of each
+    /// nondeterministic state that conflict with another one, in block order
+    pub fn find_nondeterministic_transitions(fsm: &FunctionBlock) -> Vec<(String, Vec<usize>)> {
+        let mut found = Vec::new();
+
+        for state_id in fsm.states.keys() {
+            let outgoing: Vec<_> = fsm.transitions
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| &t.from_state == state_id)
+                .map(|(idx, t)| {
+                    let guard = t.condition.as_deref().unwrap_or_default().trim();
+                    (idx, t.chain, guard, &t.to_state, SignatureGenerator::parse_transition_condition(guard))
+                })
+                .collect();
+
+            let mut conflicting = Vec::new();
+            for (i, (idx_a, chain_a, guard_a, to_a, dnf_a)) in outgoing.iter().enumerate() {
+                for (idx_b, chain_b, guard_b, to_b, dnf_b) in &outgoing[i + 1..] {
+                    // Separate IF statements run one after the other, so the later assignment wins
+                    if chain_a.is_some() && chain_b.is_some() && chain_a != chain_b {
+                        continue;
+                    }
+                    let conflict = guard_a == guard_b
+                        || (to_a != to_b
+                            && dnf_a.iter().any(|a| dnf_b.iter().any(|b| Self::jointly_satisfiable(a, b))));
+                    if conflict {
+                        conflicting.extend([*idx_a, *idx_b]);
+                    }
+                }
+            }
+
+            if !conflicting.is_empty() {
+                conflicting.sort_unstable();
+                conflicting.dedup();
+                found.push((state_id.clone(), conflicting));
+            }
+        }
+
+        found
+    }
+
+    /// Whether some input meets both conjunctions at once
+    fn jointly_satisfiable(a: &[Condition], b: &[Condition]) -> bool {
+        PathSignature::new(a.iter().chain(b).cloned().collect(), 0).is_satisfiable()
+    }
+
+    /// States visited by a shortest route (fewest transitions) from `from` to
+    /// `to`, both included; `None` when `to` can't be reached
+    pub fn shortest_path(fsm: &FunctionBlock, from: &str, to: &str) -> Option<Vec<String>> {
+        let transitions = Self::shortest_path_transitions(fsm, from, to)?;
+        let mut states = vec![from.to_string()];
+        states.extend(transitions.iter().map(|&idx| fsm.transitions[idx].to_state.clone()));
+        Some(states)
+    }
+
+    /// Indices into `fsm.transitions` along a shortest route from `from` to `to`,
+    /// empty when they're the same state
+    pub fn shortest_path_transitions(fsm: &FunctionBlock, from: &str, to: &str) -> Option<Vec<usize>> {
+        if !fsm.states.contains_key(from) || !fsm.states.contains_key(to) {
+            return None;
+        }
+
+        let outgoing = fsm.outgoing_transitions();
+        // State -> transition it was first reached by
+        let mut reached_by: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut route = Vec::new();
+                let mut state = current;
+                while let Some(Some(idx)) = reached_by.get(state) {
+                    route.push(*idx);
+                    state = &fsm.transitions[*idx].from_state;
+                }
+                route.reverse();
+                return Some(route);
+            }
+
+            for &idx in outgoing.get(current).into_iter().flatten() {
+                let next = fsm.transitions[idx].to_state.as_str();
+                if !reached_by.contains_key(next) {
+                    reached_by.insert(next, Some(idx));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Fewest transitions from a starting state to each state, by BFS from
+    /// `initial_states`. States that can't be reached are left out.
+    pub fn state_depths(fsm: &FunctionBlock) -> HashMap<String, usize> {
+        let starts = Self::initial_states(fsm);
+        let outgoing = fsm.outgoing_transitions();
+        let mut depths: HashMap<String, usize> = starts.iter().map(|s| (s.clone(), 0)).collect();
+        let mut queue: VecDeque<String> = starts.into_iter().collect();
+
+        while let Some(current) = queue.pop_front() {
+            let depth = depths[&current];
+            for &idx in outgoing.get(&current).into_iter().flatten() {
+                let next = &fsm.transitions[idx].to_state;
+                if fsm.states.contains_key(next) && !depths.contains_key(next) {
+                    depths.insert(next.clone(), depth + 1);
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+
+        depths
+    }
+
+    /// Compare the guard variables of `fsm` and its sub-machines, on either side
+    /// of a comparison, with its declarations. Members (`timer.Q`) and elements (`arr[1]`) count as uses
+    /// of the variable they belong to. A block without any declarations is
+    /// reported clean, the source having carried no interface for it.
+    pub fn check_interface_usage(fsm: &FunctionBlock) -> InterfaceReport {
+        if fsm.declared_variables.is_empty() {
+            return InterfaceReport::default();
+        }
+
+        let mut used = HashSet::new();
+        Self::collect_guard_variables(fsm, &mut used);
+
+        let unused_inputs = fsm.declared_inputs
+            .iter()
+            .filter(|input| !used.contains(input.as_str()))
+            .cloned()
+            .collect();
+        let mut undeclared_variables: Vec<String> = used
+            .into_iter()
+            .filter(|variable| !fsm.declared_variables.iter().any(|d| d == variable))
+            .collect();
+        undeclared_variables.sort();
+
+        InterfaceReport { unused_inputs, undeclared_variables }
+    }
+
+    fn collect_guard_variables(fsm: &FunctionBlock, used: &mut HashSet<String>) {
+        let guards = fsm.transitions.iter().chain(&fsm.init_transition).filter_map(|t| t.condition.as_deref());
+        for guard in guards {
+            for cond in SignatureGenerator::parse_transition_condition(guard).into_iter().flatten() {
+                for variable in std::iter::once(cond.variable.as_str()).chain(cond.value_variable()) {
+                    let root = variable.split(['.', '[']).next().unwrap_or_default().trim();
+                    if !root.is_empty() {
+                        used.insert(root.to_string());
+                    }
+                }
+            }
+        }
+
+        for sub in &fsm.sub_machines {
+            Self::collect_guard_variables(sub, used);
+        }
+    }
+
+    pub fn find_self_comparisons(fsm: &FunctionBlock) -> Vec<SelfComparison> {
+        let mut found = Vec::new();
+
+        for transition in &fsm.transitions {
+            let Some(guard) = transition.guard_ast() else {
+                continue;
+            };
+
+            for condition in guard.to_dnf().into_iter().flatten() {
+                let Some(tautology) = condition.self_comparison() else {
+                    continue;
+                };
+                let finding = SelfComparison {
+                    transition_id: transition.id.clone(),
+                    condition: condition.to_string(),
+                    tautology,
+                };
+                if !found.contains(&finding) {
+                    found.push(finding);
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Indices of transitions that can never fire because an earlier branch of
+    /// the same IF/ELSIF chain is taken whenever their guard holds
+    pub fn find_masked_transitions(fsm: &FunctionBlock) -> Vec<usize> {
+        let guards: Vec<Vec<Vec<Condition>>> = fsm.transitions
+            .iter()
+            .map(|t| SignatureGenerator::parse_transition_condition(t.condition.as_deref().unwrap_or_default().trim()))
+            .collect();
+
+        (0..fsm.transitions.len())
+            .filter(|&low| {
+                let masked = &fsm.transitions[low];
+                fsm.transitions.iter().enumerate().any(|(high, t)| {
+                    t.from_state == masked.from_state
+                        && t.chain.is_some()
+                        && t.chain == masked.chain
+                        && t.priority < masked.priority
+                        && Self::guard_implies(&guards[low], &guards[high])
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every input meeting DNF guard `a` also meets `b`: each term of
+    /// `a` has to imply some term of `b`
+    fn guard_implies(a: &[Vec<Condition>], b: &[Vec<Condition>]) -> bool {
+        a.iter().all(|term_a| {
+            b.iter().any(|term_b| {
+                term_b.iter().all(|cond| {
+                    Self::negate(cond).is_some_and(|negated| {
+                        !PathSignature::new(term_a.iter().cloned().chain([negated]).collect(), 0).is_satisfiable()
+                    })
+                })
+            })
+        })
+    }
+
+    fn negate(cond: &Condition) -> Option<Condition> {
+        let operator = match cond.operator.as_str() {
+            "=" => "<>",
+            "<>" => "=",
+            "<" => ">=",
+            ">=" => "<",
+            ">" => "<=",
+            "<=" => ">",
+            _ => return None,
+        };
+        Some(Condition::new(cond.variable.clone(), operator.to_string(), cond.value.clone()))
+    }
+
     pub fn validate_references(fsm: &FunctionBlock) -> anyhow::Result<()> {
         for transition in &fsm.transitions {
             if !fsm.states.contains_key(&transition.from_state) {
@@ -70,7 +464,7 @@ impl FsmValidator {
                     transition.from_state
                 ));
             }
-            if !fsm.states.contains_key(&transition.to_state) {
+            if !transition.computed_target && !fsm.states.contains_key(&transition.to_state) {
                 return Err(anyhow::anyhow!(
                     "Invalid state reference in transition: to_state '{}'",
                     transition.to_state
@@ -79,4 +473,293 @@ impl FsmValidator {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_interface_usage_reported() {
+        let xml = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PumpFB</derived-function-block-name>
+<input-declarations>
+<input-declaration><var1-list><variable-name>start</variable-name><variable-name>spare</variable-name><variable-name>setpoint</variable-name></var1-list></input-declaration>
+</input-declarations>
+<var-declarations>
+<var1-list><variable-name>state</variable-name></var1-list>
+<fb-name>timer</fb-name>
+</var-declarations>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>level_ok</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><variable-name>timer.Q</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><expression><variable-name>timer.PT</variable-name></expression><greater-than/><expression><variable-name>setpoint</variable-name></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>40</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><expression><variable-name>timer.ET</variable-name></expression><greater-than/><expression><variable-name>limit</variable-name></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>50</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, xml.as_bytes()).unwrap();
+        let fsm = crate::fsm::FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let report = FsmValidator::check_interface_usage(&fsm.function_blocks[0]);
+        assert_eq!(report.unused_inputs, ["spare"]);
+        // Variables compared against count as used, like those compared
+        assert_eq!(report.undeclared_variables, ["level_ok", "limit"]);
+        assert!(!report.is_clean());
+
+        let undeclared = FunctionBlock::new("BareFB".to_string(), "state".to_string());
+        assert!(FsmValidator::check_interface_usage(&undeclared).is_clean());
+    }
+
+    #[test]
+    fn test_nondeterministic_transitions_grouped() {
+        let mut fb = FunctionBlock::new("MixerFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 5".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "40".to_string(), "level > 8".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "run = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "40".to_string(), "run = FALSE".to_string()));
+
+        let found = FsmValidator::find_nondeterministic_transitions(&fb);
+        assert_eq!(found, [("10".to_string(), vec![0, 1]), ("20".to_string(), vec![2, 3])]);
+    }
+
+    #[test]
+    fn test_separate_ifs_are_deterministic() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        // IF level > 5 THEN state := 20; END_IF; IF level > 8 THEN state := 30; END_IF
+        for (priority, (to, guard)) in [("20", "level > 5"), ("30", "level > 8")].into_iter().enumerate() {
+            let mut transition = Transition::new("10".to_string(), to.to_string(), guard.to_string());
+            transition.priority = priority;
+            transition.chain = Some(priority);
+            fb.add_transition(transition);
+        }
+        assert!(FsmValidator::find_nondeterministic_transitions(&fb).is_empty());
+
+        // The same two guards as branches of one chain still overlap
+        fb.transitions[1].chain = Some(0);
+        assert_eq!(FsmValidator::find_nondeterministic_transitions(&fb), [("10".to_string(), vec![0, 1])]);
+    }
+
+    #[test]
+    fn test_duplicate_and_colliding_transitions() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        assert_eq!(FsmValidator::find_duplicate_transitions(&fb), [1]);
+        assert!(FsmValidator::find_transition_id_collisions(&fb).is_empty());
+
+        // `add_transition` keeps ids unique, so a collision only comes in from outside (e.g. old JSON)
+        let mut colliding = Transition::new("10".to_string(), "20".to_string(), "manual = TRUE".to_string());
+        colliding.id = fb.transitions[0].id.clone();
+        fb.transitions.push(colliding);
+        assert_eq!(FsmValidator::find_duplicate_transitions(&fb), [1]);
+        assert_eq!(FsmValidator::find_transition_id_collisions(&fb), [2]);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut fb = FunctionBlock::new("LineFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+
+        assert_eq!(FsmValidator::shortest_path(&fb, "10", "30").unwrap(), ["10", "20", "30"]);
+        assert_eq!(FsmValidator::shortest_path_transitions(&fb, "10", "30").unwrap(), [0, 1]);
+        assert_eq!(FsmValidator::shortest_path(&fb, "20", "20").unwrap(), ["20"]);
+        assert_eq!(FsmValidator::shortest_path(&fb, "30", "10"), None);
+    }
+
+    #[test]
+    fn test_trap_state_violates_safety() {
+        let mut fb = FunctionBlock::new("SafetyFB".to_string(), "state".to_string());
+        for id in ["100", "10", "20", "99"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("100".to_string(), "10".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "100".to_string(), "stop = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "fault = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "99".to_string(), "latch = TRUE".to_string()));
+
+        let safe = HashSet::from(["100".to_string()]);
+        let violations = FsmValidator::find_safety_violations(&fb, &safe);
+
+        assert_eq!(violations, vec!["20", "99"]);
+        assert!(!violations.contains(&"10".to_string()));
+    }
+
+    #[test]
+    fn test_reset_transitions_on_cycle() {
+        let mut fb = FunctionBlock::new("CyclicFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "reset = true".to_string()));
+
+        let analyzer = crate::analysis::FsmAnalyzer::new();
+        let resets = analyzer.reset_transitions(&fb);
+        assert_eq!(resets.len(), 1);
+        assert_eq!((resets[0].from_state.as_str(), resets[0].to_state.as_str()), ("30", "10"));
+        assert!(analyzer.states_without_reset_path(&fb).is_empty());
+
+        fb.add_state(State::new("99".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "99".to_string(), "fault".to_string()));
+        assert_eq!(analyzer.states_without_reset_path(&fb), ["99"]);
+    }
+
+    #[test]
+    fn test_single_entry_cases() {
+        let mut fb = FunctionBlock::new("EntryFB".to_string(), "state".to_string());
+        for id in ["0", "10", "20"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("0".to_string(), "10".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "done = TRUE".to_string()));
+
+        let report = FsmValidator::check_single_entry(&fb);
+        assert!(report.is_single_entry());
+        assert_eq!(report.initial_states, vec!["0"]);
+
+        let mut cyclic = fb.clone();
+        cyclic.add_transition(Transition::new("20".to_string(), "0".to_string(), "reset = TRUE".to_string()));
+        let report = FsmValidator::check_single_entry(&cyclic);
+        assert!(report.has_no_entry());
+        assert!(!report.has_multiple_entries());
+
+        fb.add_state(State::new("99".to_string()));
+        fb.add_transition(Transition::new("99".to_string(), "20".to_string(), "skip = TRUE".to_string()));
+        let report = FsmValidator::check_single_entry(&fb);
+        assert!(report.has_multiple_entries());
+        assert_eq!(report.initial_states, vec!["0", "99"]);
+    }
+
+    #[test]
+    fn test_self_comparison_flagged() {
+        let mut fb = FunctionBlock::new("CopyFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "x = x AND sensor = high".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "10".to_string(), "y <> y".to_string()));
+
+        let found = FsmValidator::find_self_comparisons(&fb);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].transition_id, "10_to_20");
+        assert_eq!(found[0].condition, "x = x");
+        assert!(found[0].tautology);
+        assert!(!found[1].tautology);
+    }
+
+    #[test]
+    fn test_dangling_reference_in_imported_block() {
+        let mut fb = FunctionBlock::new("ImportedFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "go = TRUE".to_string()));
+        assert!(FsmValidator::validate_references(&fb).is_ok());
+
+        let mut json = serde_json::to_value(&fb).unwrap();
+        json["states"].as_object_mut().unwrap().remove("20");
+        let imported: FunctionBlock = serde_json::from_value(json).unwrap();
+
+        let err = FsmValidator::validate_references(&imported).unwrap_err();
+        assert!(err.to_string().contains("to_state '20'"));
+    }
+
+    #[test]
+    fn test_broad_earlier_guard_masks_later_one() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        // IF level > 5 THEN ... ELSIF level > 8 THEN ... ELSIF level < 2 THEN ...
+        for (priority, (to, guard)) in [("20", "level > 5"), ("30", "level > 8"), ("40", "level < 2")].into_iter().enumerate() {
+            let mut transition = Transition::new("10".to_string(), to.to_string(), guard.to_string());
+            transition.priority = priority;
+            transition.chain = Some(0);
+            fb.add_transition(transition);
+        }
+        assert_eq!(FsmValidator::find_masked_transitions(&fb), [1]);
+
+        // Narrow first, broad second: both can fire
+        fb.transitions.swap(0, 1);
+        fb.transitions[0].priority = 0;
+        fb.transitions[1].priority = 1;
+        assert!(FsmValidator::find_masked_transitions(&fb).is_empty());
+    }
+
+    #[test]
+    fn test_separate_ifs_do_not_mask() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        // IF level > 5 THEN state := 20; END_IF; IF level > 8 THEN state := 30; END_IF
+        // The second IF runs after the first, so its assignment wins
+        for (priority, (to, guard)) in [("20", "level > 5"), ("30", "level > 8")].into_iter().enumerate() {
+            let mut transition = Transition::new("10".to_string(), to.to_string(), guard.to_string());
+            transition.priority = priority;
+            transition.chain = Some(priority);
+            fb.add_transition(transition);
+        }
+        assert!(FsmValidator::find_masked_transitions(&fb).is_empty());
+    }
+
+    #[test]
+    fn test_is_deterministic() {
+        let mut fb = FunctionBlock::new("MultiPathFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "button = pressed".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "timer > 100".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "10".to_string(), "timer < 50".to_string()));
+        assert!(FsmValidator::is_deterministic(&fb));
+        assert!(crate::analysis::FsmStatistics::analyze(&fb).is_deterministic);
+
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "reset = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "20".to_string(), "reset = TRUE".to_string()));
+        assert!(!FsmValidator::is_deterministic(&fb));
+        assert_eq!(FsmValidator::find_nondeterministic_states(&fb), ["30"]);
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Outcome of processing one input of a batch
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    pub input: PathBuf,
+    pub outcome: Result<T>,
+}
+
+/// Worker count when `--jobs` isn't given
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Replace directories with the `.xml` files directly inside them and glob
+/// patterns with their matches; anything else is kept as given. A pattern
+/// matching nothing is warned about rather than treated as an error.
+pub fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut files = Vec::new();
+            for entry in std::fs::read_dir(input).with_context(|| format!("Failed to read {}", input.display()))? {
+                let path = entry?.path();
+                if path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xml")) {
+                    files.push(path);
+                }
+            }
+            files.sort();
+            expanded.extend(files);
+        } else if input.to_str().is_some_and(|s| s.contains(['*', '?', '['])) {
+            let pattern = input.to_string_lossy();
+            let matches = glob::glob(&pattern).with_context(|| format!("Invalid pattern {}", pattern))?;
+            let before = expanded.len();
+            for matched in matches {
+                // An entry that can't be read is kept so it fails on its own
+                // instead of aborting the whole batch
+                expanded.push(matched.unwrap_or_else(|e| e.path().to_path_buf()));
+            }
+            if expanded.len() == before {
+                eprintln!("warning: {} matches no files", pattern);
+            }
+        } else {
+            expanded.push(input.clone());
+        }
+    }
+    Ok(expanded)
+}
+
+/// `<base>.<extension>`, or `<base>_2.<extension>` and so on when `taken`
+/// already holds the stem. Stems compare case-insensitively, as some file
+/// systems do, so no output overwrites another.
+pub fn unique_file_name(base: &str, extension: &str, taken: &mut HashSet<String>) -> String {
+    let mut stem = base.to_string();
+    let mut suffix = 2;
+    while !taken.insert(stem.to_lowercase()) {
+        stem = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    format!("{}.{}", stem, extension)
+}
+
+/// One output path in `dir` per input, named after its file stem, fixed
+/// before any worker starts so inputs sharing a stem (`a/pump.xml`,
+/// `b/pump.xml`) never write the same file
+pub fn output_paths(inputs: &[PathBuf], dir: &Path, extension: &str) -> HashMap<PathBuf, PathBuf> {
+    let mut taken = HashSet::new();
+    inputs
+        .iter()
+        .map(|input| {
+            let stem = input.file_stem().unwrap_or(input.as_os_str()).to_string_lossy();
+            (input.clone(), dir.join(unique_file_name(&stem, extension, &mut taken)))
+        })
+        .collect()
+}
+
+/// Run `process` over every input on up to `jobs` threads. A failing input
+/// doesn't stop the others; results come back sorted by path whatever the
+/// completion order.
+pub fn run_parallel<T, F>(inputs: &[PathBuf], jobs: usize, process: F) -> Vec<BatchResult<T>>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(inputs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, inputs.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(input) = inputs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let outcome = process(input);
+                    results
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(BatchResult { input: input.clone(), outcome });
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.sort_by(|a, b| a.input.cmp(&b.input));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::PUMP_XML;
+    use crate::fsm::FsmExtractor;
+    use crate::output::{OutputFormat, OutputWriter};
+
+    #[test]
+    fn test_parallel_outputs_in_stable_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("out");
+        std::fs::create_dir(&out_dir).unwrap();
+
+        let mut inputs = Vec::new();
+        for name in ["line5", "line1", "line4", "broken", "line2", "line3"] {
+            let input = dir.path().join(format!("{name}.xml"));
+            let content = if name == "broken" { "<project>" } else { PUMP_XML };
+            std::fs::write(&input, content).unwrap();
+            inputs.push(input);
+        }
+
+        let writer = OutputWriter::new(OutputFormat::Json);
+        let results = run_parallel(&inputs, 4, |input| {
+            let fsm = FsmExtractor::new(input)?.extract()?;
+            let output = out_dir.join(input.file_stem().unwrap()).with_extension("json");
+            writer.write(&fsm, Some(&output))?;
+            Ok(output)
+        });
+
+        let order: Vec<_> = results.iter().map(|r| r.input.file_stem().unwrap().to_str().unwrap()).collect();
+        assert_eq!(order, ["broken", "line1", "line2", "line3", "line4", "line5"]);
+        assert!(results[0].outcome.is_err());
+        for result in &results[1..] {
+            assert!(result.outcome.as_ref().unwrap().exists());
+        }
+    }
+
+    #[test]
+    fn test_directory_expands_to_xml_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("out");
+        std::fs::create_dir(&out_dir).unwrap();
+        std::fs::write(dir.path().join("a.xml"), PUMP_XML).unwrap();
+        std::fs::write(dir.path().join("b.xml"), PUMP_XML).unwrap();
+        std::fs::write(dir.path().join("bad.xml"), "<project>").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not xml").unwrap();
+
+        let from_dir = expand_inputs(&[dir.path().to_path_buf()]).unwrap();
+        let from_glob = expand_inputs(&[dir.path().join("*.xml")]).unwrap();
+        assert_eq!(from_dir.len(), 3);
+        assert_eq!(from_dir, from_glob);
+        assert!(expand_inputs(&[dir.path().join("*.st")]).unwrap().is_empty());
+
+        let writer = OutputWriter::new(OutputFormat::Json);
+        let results = run_parallel(&from_dir, 2, |input| {
+            let fsm = FsmExtractor::new(input)?.extract()?;
+            let output = out_dir.join(input.file_stem().unwrap()).with_extension("json");
+            writer.write(&fsm, Some(&output))?;
+            Ok(output)
+        });
+
+        let failed: Vec<_> = results.iter().filter(|r| r.outcome.is_err()).map(|r| r.input.file_name().unwrap()).collect();
+        assert_eq!(failed, ["bad.xml"]);
+        assert_eq!(std::fs::read_dir(&out_dir).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,54 @@
+pub use crate::analysis::Check;
+use crate::output::{GuardStyle, OutputFormat};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Looked up in the working directory when `--config` isn't given
+pub const CONFIG_FILE_NAME: &str = "fsm-extractor.toml";
+
+/// Project defaults for CLI options; flags given on the command line win
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Output format of `extract` and `batch`
+    pub format: Option<OutputFormat>,
+    /// Checks `analyze` runs in addition to those passed as flags
+    pub checks: Vec<Check>,
+    /// Function blocks `extract`, `visualize` and `tui` keep
+    pub function_blocks: Option<Vec<String>>,
+    pub boolean_shorthand: bool,
+    pub guard_style: Option<GuardStyle>,
+    pub unconditional_label: Option<String>,
+    /// Paths recorded per state when building signatures
+    pub max_paths: Option<usize>,
+    /// Transitions a signature path may take
+    pub max_path_length: Option<usize>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// The given file, else `fsm-extractor.toml` in `dir` if there is one,
+    /// else no defaults
+    pub fn discover(explicit: Option<&Path>, dir: &Path) -> Result<Self> {
+        if let Some(path) = explicit {
+            return Self::load(path);
+        }
+        let path = dir.join(CONFIG_FILE_NAME);
+        if path.is_file() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
@@ -17,6 +17,15 @@ pub enum FsmError {
     #[error("No case statement found in function block '{0}'")]
     NoCaseStatement(String),
 
+    #[error("Function block '{0}' yielded no transitions (rerun with --verbose to see parse warnings)")]
+    NoTransitions(String),
+
+    #[error("Recursion limit of {0} exceeded (input nested too deeply)")]
+    RecursionLimitExceeded(usize),
+
+    #[error("Cannot parse guard: {0}")]
+    InvalidGuard(String),
+
     #[error("Invalid state reference: {0}")]
     InvalidStateReference(String),
 
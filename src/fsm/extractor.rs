@@ -1,24 +1,119 @@
+use crate::analysis::FsmValidator;
 use crate::error::FsmError;
-use crate::xml_parser::{XmlParser, FunctionBlockData};
-use crate::fsm::{FiniteStateMachine, FunctionBlock, State, Transition, Metadata};
+use crate::xml_parser::{Assignment, BlockCall, BlockSummary, XmlParser, XmlPreprocessor, FunctionBlockData, IfStatement};
+use crate::fsm::{FiniteStateMachine, FunctionBlock, State, Transition, Metadata, WarningKind, WarningLog, COMPUTED_STATE, INIT_STATE};
 use anyhow::Result;
 use chrono::Utc;
+use std::io::Read;
 use std::path::Path;
+use std::str::FromStr;
+
+/// Switches controlling how strictly extraction treats parse problems
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Fail when a block with a CASE statement yields no transitions
+    pub require_transitions: bool,
+    /// Print parse warnings for skipped or transition-less blocks to stderr
+    pub verbose: bool,
+    /// Variable pairs holding the same state, for aliasing the parser can't detect
+    pub state_aliases: Vec<StateAlias>,
+    /// Fail instead of warning when a transition references a missing state
+    pub strict: bool,
+    /// States that end the machine on purpose, kept out of dead-state reports
+    pub terminal_states: Vec<String>,
+    /// Keep one of each set of transitions repeated exactly in the source
+    pub dedup_transitions: bool,
+    /// Flags whose test marks an IF outside the CASE as first-scan
+    /// initialization, matched as whole identifiers ignoring case;
+    /// `DEFAULT_INIT_FLAGS` when empty
+    pub init_flags: Vec<String>,
+    /// Receives every warning, whether or not it is printed
+    pub warnings: WarningLog,
+}
+
+/// `alias` (e.g. a VAR_TEMP copy the CASE switches on) stands for `variable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateAlias {
+    pub alias: String,
+    pub variable: String,
+}
+
+/// Parses `OLD=NEW` as given to `--state-alias`
+impl FromStr for StateAlias {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((alias, variable)) if !alias.is_empty() && !variable.is_empty() => Ok(Self {
+                alias: alias.to_string(),
+                variable: variable.to_string(),
+            }),
+            _ => Err(format!("expected OLD=NEW, got '{}'", s)),
+        }
+    }
+}
+
+/// `Metadata::source_file` of XML read from standard input
+pub const STDIN_SOURCE: &str = "<stdin>";
+
+/// Usual names of first-scan flags
+pub const DEFAULT_INIT_FLAGS: [&str; 8] = [
+    "firstScan", "first_scan", "bFirstScan", "xFirstScan", "firstCycle", "bFirstCycle", "init", "bInit",
+];
 
 pub struct FsmExtractor {
     parser: XmlParser,
     source_path: std::path::PathBuf,
+    options: ExtractOptions,
 }
 
 impl FsmExtractor {
     pub fn new(xml_path: &Path) -> Result<Self> {
-        let parser = XmlParser::new(xml_path)?;
+        Self::with_preprocessor(xml_path, &XmlPreprocessor::default())
+    }
+
+    pub fn with_preprocessor(xml_path: &Path, preprocessor: &XmlPreprocessor) -> Result<Self> {
+        let parser = XmlParser::with_preprocessor(xml_path, preprocessor)?;
         Ok(Self {
             parser,
             source_path: xml_path.to_path_buf(),
+            options: ExtractOptions::default(),
+        })
+    }
+
+    /// Extractor over XML already in memory; `source_label` stands in for the
+    /// file name in `Metadata::source_file`
+    pub fn from_content(content: &str, source_label: &str, preprocessor: &XmlPreprocessor) -> Result<Self> {
+        Ok(Self {
+            parser: XmlParser::from_content(content, preprocessor)?,
+            source_path: source_label.into(),
+            options: ExtractOptions::default(),
         })
     }
 
+    /// Like `from_content`, reading the XML from `reader` (e.g. stdin)
+    pub fn from_reader(mut reader: impl Read, source_label: &str, preprocessor: &XmlPreprocessor) -> Result<Self> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        Self::from_content(&content, source_label, preprocessor)
+    }
+
+    pub fn with_options(mut self, options: ExtractOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Block-level call graph of the document
+    pub fn extract_block_calls(&self) -> Vec<BlockCall> {
+        self.parser.find_block_calls()
+    }
+
+    /// Blocks of the document with their case variable and state count,
+    /// without extracting transitions
+    pub fn list_function_blocks(&self) -> Vec<BlockSummary> {
+        self.parser.summarize_function_blocks()
+    }
+
     pub fn extract(&self) -> Result<FiniteStateMachine> {
         let function_block_names = self.parser.find_function_blocks();
 
@@ -34,14 +129,11 @@ impl FsmExtractor {
         // KEEP AND MODIFY THIS LOOP TO PROCESS ALL BLOCKS
         for name in &function_block_names {
 
-            if let Ok(fb_data) = self.parser.extract_function_block(name) {
-                if let Ok(fb) = self.build_function_block(fb_data) {
-                    if fb.state_count() > 0 || fb.transition_count() > 0 {
-                        total_states += fb.state_count();
-                        total_transitions += fb.transition_count();
-                        function_blocks.push(fb);
-                    }
-                }
+            if let Some(fb) = self.load_function_block(name)?
+                && (fb.state_count() > 0 || fb.transition_count() > 0) {
+                total_states += fb.state_count();
+                total_transitions += fb.transition_count();
+                function_blocks.push(fb);
             }
         }
 
@@ -70,12 +162,10 @@ impl FsmExtractor {
                 continue;
             }
 
-            if let Ok(fb_data) = self.parser.extract_function_block(name) {
-                if let Ok(fb) = self.build_function_block(fb_data) {
-                    total_states += fb.state_count();
-                    total_transitions += fb.transition_count();
-                    function_blocks.push(fb);
-                }
+            if let Some(fb) = self.load_function_block(name)? {
+                total_states += fb.state_count();
+                total_transitions += fb.transition_count();
+                function_blocks.push(fb);
             }
         }
 
@@ -92,21 +182,84 @@ impl FsmExtractor {
         })
     }
 
-    fn build_function_block(&self, fb_data: FunctionBlockData) -> Result<FunctionBlock> {
-        let mut function_block = FunctionBlock::new(
-            fb_data.name.clone(),
-            fb_data.case_variable.clone(),
-        );
+    /// Parse and build a single block; unparseable blocks are skipped (`None`)
+    fn load_function_block(&self, name: &str) -> Result<Option<FunctionBlock>> {
+        let fb = match self.parser
+            .extract_function_block(name)
+            .and_then(|fb_data| self.build_function_block(fb_data))
+        {
+            Ok(fb) => fb,
+            Err(e) => {
+                self.options.warnings.push(WarningKind::SkippedBlock, name, e.to_string());
+                if self.options.verbose {
+                    eprintln!("warning: skipping function block '{}': {}", name, e);
+                }
+                return Ok(None);
+            }
+        };
+
+        self.check_references(&fb)?;
+
+        if fb.transition_count() == 0 {
+            if self.options.require_transitions {
+                return Err(FsmError::NoTransitions(name.to_string()).into());
+            }
+            self.options.warnings.push(
+                WarningKind::NoTransitions,
+                name,
+                format!("CASE on '{}' yields no extractable transitions", fb.case_variable),
+            );
+            if self.options.verbose {
+                eprintln!(
+                    "warning: function block '{}' has a CASE on '{}' but no extractable transitions",
+                    name, fb.case_variable
+                );
+            }
+        }
+
+        Ok(Some(fb))
+    }
+
+    /// Fail on a transition to or from a missing state under `strict`,
+    /// otherwise record a warning
+    fn check_references(&self, fb: &FunctionBlock) -> Result<()> {
+        if let Err(e) = FsmValidator::validate_references(fb) {
+            if self.options.strict {
+                return Err(e.context(format!("Function block '{}' is inconsistent", fb.name)));
+            }
+            self.options.warnings.push(WarningKind::InvalidStateReference, &fb.name, e.to_string());
+            if self.options.verbose {
+                eprintln!("warning: function block '{}': {}", fb.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn build_function_block(&self, mut fb_data: FunctionBlockData) -> Result<FunctionBlock> {
+        let state_variables = self.state_variables(&fb_data);
+        let mut function_block = FunctionBlock::new(fb_data.name.clone(), self.persistent_state_variable(&fb_data));
+        function_block.declared_inputs = std::mem::take(&mut fb_data.declarations.inputs);
+        function_block.declared_variables = std::mem::take(&mut fb_data.declarations.variables);
 
         // First pass: create all states
         for element in &fb_data.case_elements {
-            let state = State::new(element.state_id.clone());
+            let mut state = State::new(element.state_id.clone());
+            state.actions = Self::actions(&element.assignments, &state_variables);
             function_block.add_state(state);
         }
 
+        for element in &mut fb_data.case_elements {
+            for sub_data in std::mem::take(&mut element.sub_machines) {
+                let mut sub_machine = self.build_function_block(sub_data)?;
+                sub_machine.parent_state = Some(element.state_id.clone());
+                function_block.sub_machines.push(sub_machine);
+            }
+        }
+
         // Second pass: extract transitions
         for element in &fb_data.case_elements {
             let current_state = element.state_id.clone();
+            let mut priority = 0;
 
             // Handle case with no if statements
             if element.if_statements.is_empty() {
@@ -115,35 +268,1051 @@ impl FsmExtractor {
             }
 
             for if_stmt in &element.if_statements {
-                // Look for assignments to the case variable
-                for assignment in &if_stmt.assignments {
-                    if assignment.variable == fb_data.case_variable {
-                        let next_state = assignment.value.clone();
-
-                        // Create the transition
-                        let condition = if if_stmt.condition.is_empty() {
-                            "No Check".to_string()
-                        } else {
-                            if_stmt.condition.clone()
-                        };
-
-                        let transition = Transition::new(
-                            current_state.clone(),
-                            next_state.clone(),
-                            condition,
-                        );
-
-                        // Ensure the target state exists
-                        if !function_block.states.contains_key(&next_state) {
-                            function_block.add_state(State::new(next_state));
-                        }
-
-                        function_block.add_transition(transition);
+                // Only the last assignment to the case variable takes effect at runtime
+                let targets: Vec<&Assignment> = if_stmt.assignments
+                    .iter()
+                    .filter(|a| Self::is_state_write(a, &state_variables))
+                    .collect();
+
+                let Some((&target, shadowed)) = targets.split_last() else {
+                    continue;
+                };
+
+                if !shadowed.is_empty() {
+                    let shadowed: Vec<&str> = shadowed.iter().map(|a| a.value.as_str()).collect();
+                    let detail = format!(
+                        "state {}: assignment(s) {} to '{}' overridden by {}",
+                        current_state, shadowed.join(", "), function_block.case_variable, target.value
+                    );
+                    if self.options.verbose {
+                        eprintln!("warning: {} {}", fb_data.name, detail);
                     }
+                    self.options.warnings.push(WarningKind::ShadowedAssignment, &fb_data.name, detail);
                 }
+
+                let next_state = Self::target_state(target);
+
+                // Create the transition
+                let mut transition = if if_stmt.condition.is_empty() {
+                    Transition::unconditional(current_state.clone(), next_state.clone())
+                } else {
+                    Transition::new(current_state.clone(), next_state.clone(), if_stmt.condition.clone())
+                };
+                transition.actions = Self::actions(&if_stmt.assignments, &state_variables);
+                Self::mark_computed(&mut transition, target);
+
+                if transition.condition.is_some() && transition.guard_ast().is_none() {
+                    let detail = format!("state {}: cannot parse guard '{}'", current_state, if_stmt.condition);
+                    if self.options.verbose {
+                        eprintln!("warning: {} {}", fb_data.name, detail);
+                    }
+                    self.options.warnings.push(WarningKind::MalformedGuard, &fb_data.name, detail);
+                }
+
+                // Ensure the target state exists; a computed target is no state
+                if !transition.computed_target && !function_block.states.contains_key(&next_state) {
+                    function_block.add_state(State::new(next_state));
+                }
+
+                transition.priority = priority;
+                transition.chain = Some(if_stmt.chain);
+                priority += 1;
+                function_block.add_transition(transition);
+            }
+        }
+
+        // Outside the CASE, the first first-scan/init guarded IF is the entry point;
+        // any other write to the case variable forces the machine from every state
+        let init_idx = fb_data.outer_statements.iter().position(|stmt| self.is_init_statement(stmt));
+        let case_states: Vec<String> = function_block.states.keys().cloned().collect();
+
+        for (idx, stmt) in fb_data.outer_statements.iter().enumerate() {
+            if Some(idx) == init_idx {
+                function_block.init_transition = Self::build_outer_transition(stmt, INIT_STATE, &state_variables);
+                if let Some(init_transition) = &function_block.init_transition
+                    && !init_transition.computed_target
+                    && !function_block.states.contains_key(&init_transition.to_state) {
+                    function_block.add_state(State::new(init_transition.to_state.clone()));
+                }
+                continue;
+            }
+
+            for from_state in &case_states {
+                let Some(mut transition) = Self::build_outer_transition(stmt, from_state, &state_variables) else {
+                    continue;
+                };
+                if transition.to_state == *from_state {
+                    continue;
+                }
+
+                transition.is_external_force = true;
+                if !transition.computed_target && !function_block.states.contains_key(&transition.to_state) {
+                    function_block.add_state(State::new(transition.to_state.clone()));
+                }
+                function_block.add_transition(transition);
             }
         }
 
+        // `state := <itself>` parks the machine for good, unless a later IF moves
+        // it on (the default-stay idiom)
+        for element in &fb_data.case_elements {
+            let parks = element.assignments
+                .iter()
+                .any(|a| Self::is_state_write(a, &state_variables) && !a.computed && a.value == element.state_id);
+            if let Some(state) = function_block.states.get_mut(&element.state_id)
+                && parks
+                && state.transitions_out.is_empty() {
+                state.terminal = true;
+            }
+        }
+
+        for state_id in &self.options.terminal_states {
+            if let Some(state) = function_block.states.get_mut(state_id) {
+                state.terminal = true;
+            }
+        }
+
+        if self.options.dedup_transitions {
+            Self::remove_duplicate_transitions(&mut function_block);
+        }
+
+        if function_block.init_transition.is_none()
+            && let Some(initial) = &fb_data.initial_state {
+            function_block.init_transition = Some(Transition::unconditional(INIT_STATE.to_string(), initial.clone()));
+        }
+
         Ok(function_block)
     }
-}
\ No newline at end of file
+
+    fn remove_duplicate_transitions(function_block: &mut FunctionBlock) {
+        let duplicates = FsmValidator::find_duplicate_transitions(function_block);
+        if duplicates.is_empty() {
+            return;
+        }
+
+        // Rebuild the per-state id lists along with the transitions
+        let transitions = std::mem::take(&mut function_block.transitions);
+        for state in function_block.states.values_mut() {
+            state.transitions_in.clear();
+            state.transitions_out.clear();
+        }
+        for (idx, transition) in transitions.into_iter().enumerate() {
+            if !duplicates.contains(&idx) {
+                function_block.add_transition(transition);
+            }
+        }
+    }
+
+    /// The case variable followed by every variable aliased to it, detected or
+    /// configured through `ExtractOptions::state_aliases`
+    fn state_variables(&self, fb_data: &FunctionBlockData) -> Vec<String> {
+        let mut variables = vec![fb_data.case_variable.clone()];
+        variables.extend(fb_data.state_aliases.iter().cloned());
+
+        for StateAlias { alias, variable } in &self.options.state_aliases {
+            if variables.contains(alias) && !variables.contains(variable) {
+                variables.push(variable.clone());
+            } else if variables.contains(variable) && !variables.contains(alias) {
+                variables.push(alias.clone());
+            }
+        }
+
+        variables
+    }
+
+    /// Variable keeping the state between scans: a CASE over a temp copy really
+    /// operates on the variable it was copied from
+    fn persistent_state_variable(&self, fb_data: &FunctionBlockData) -> String {
+        fb_data.state_source
+            .clone()
+            .or_else(|| {
+                self.options.state_aliases
+                    .iter()
+                    .find(|StateAlias { alias, .. }| *alias == fb_data.case_variable)
+                    .map(|StateAlias { variable, .. }| variable.clone())
+            })
+            .unwrap_or_else(|| fb_data.case_variable.clone())
+    }
+
+    /// Whether `stmt`'s guard reads one of the init flags
+    fn is_init_statement(&self, stmt: &IfStatement) -> bool {
+        let flags: Vec<&str> = if self.options.init_flags.is_empty() {
+            DEFAULT_INIT_FLAGS.to_vec()
+        } else {
+            self.options.init_flags.iter().map(String::as_str).collect()
+        };
+        stmt.condition
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .any(|identifier| flags.iter().any(|flag| flag.eq_ignore_ascii_case(identifier)))
+    }
+
+    /// Transition for an IF outside the CASE; assignments to anything but the
+    /// state variables become its actions
+    fn build_outer_transition(stmt: &IfStatement, from_state: &str, state_variables: &[String]) -> Option<Transition> {
+        let assignment = stmt.assignments
+            .iter()
+            .rfind(|a| Self::is_state_write(a, state_variables))?;
+        let target = Self::target_state(assignment);
+
+        // A bare flag such as `firstScan` is a test for TRUE
+        let is_identifier = !stmt.condition.is_empty()
+            && stmt.condition.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        let mut transition = if stmt.condition.is_empty() {
+            Transition::unconditional(from_state.to_string(), target)
+        } else if is_identifier {
+            Transition::new(from_state.to_string(), target, format!("{} = TRUE", stmt.condition))
+        } else {
+            Transition::new(from_state.to_string(), target, stmt.condition.clone())
+        };
+        transition.actions = Self::actions(&stmt.assignments, state_variables);
+        Self::mark_computed(&mut transition, assignment);
+
+        Some(transition)
+    }
+
+    /// Assignment to a state variable, other than copying another one
+    fn is_state_write(assignment: &Assignment, state_variables: &[String]) -> bool {
+        state_variables.contains(&assignment.variable)
+            && !(assignment.computed && state_variables.contains(&assignment.value))
+    }
+
+    fn target_state(assignment: &Assignment) -> String {
+        if assignment.computed {
+            COMPUTED_STATE.to_string()
+        } else {
+            assignment.value.clone()
+        }
+    }
+
+    fn mark_computed(transition: &mut Transition, assignment: &Assignment) {
+        if assignment.computed {
+            transition.computed_target = true;
+            transition.target_expression = Some(assignment.value.clone());
+        }
+    }
+
+    /// Assignments to anything but the state variables, as `var := value`
+    fn actions(assignments: &[Assignment], state_variables: &[String]) -> Vec<String> {
+        assignments
+            .iter()
+            .filter(|a| !state_variables.contains(&a.variable))
+            .map(|a| format!("{} := {}", a.variable, a.value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::analysis::signatures::Condition;
+    use crate::analysis::{FsmAnalyzer, FsmStatistics, SignatureGenerator};
+    use crate::output::{OutputFormat, OutputWriter};
+
+    // State changes happen through a WHILE loop, which the parser doesn't follow
+    const UNSUPPORTED_CASE_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>LoopFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<while-statement>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</while-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // State 20 parks itself with `state := 20`; 30 has no CASE element at all;
+    // 40 stays by default until `resume`
+    const TERMINAL_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>BatchFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><variable-name>abort</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><variable-name>hold</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>40</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>40</integer-literal></case-list-element>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>40</integer-literal></expression></assignment-statement>
+<if-statement>
+<expression><variable-name>resume</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const DOUBLE_ASSIGNMENT_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PressFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>c</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const INIT_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>StartupFB</derived-function-block-name>
+<if-statement>
+<expression><variable-name>firstScan</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>100</integer-literal></expression></assignment-statement>
+<assignment-statement><variable-name>motor</variable-name><expression><boolean-literal>FALSE</boolean-literal></expression></assignment-statement>
+</if-statement>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>100</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>200</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const NESTED_IF_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>GateFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>a</variable-name></expression>
+<if-statement>
+<expression><variable-name>b</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const ESTOP_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PressFB</derived-function-block-name>
+<if-statement>
+<expression><variable-name>estop</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>0</integer-literal></expression></assignment-statement>
+</if-statement>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>0</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // The CASE switches on a temp copy while some branches write the original
+    const ALIASED_TEMP_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>AliasFB</derived-function-block-name>
+<assignment-statement><variable-name>tmpState</variable-name><expression><variable-name>state</variable-name></expression></assignment-statement>
+<case-statement>
+<expression><variable-name>tmpState</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>tmpState</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+<assignment-statement><variable-name>state</variable-name><expression><variable-name>tmpState</variable-name></expression></assignment-statement>
+</function-block-declaration>
+</project>"#;
+
+    // `prevState := state` only records where the machine was last scan
+    const HISTORY_COPY_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>HistoryFB</derived-function-block-name>
+<assignment-statement><variable-name>prevState</variable-name><expression><variable-name>state</variable-name></expression></assignment-statement>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>prevState</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // One sub-FSM per axis, selected by indexing an array of states
+    const ARRAY_INDEXED_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>AxisFB</derived-function-block-name>
+<case-statement>
+<expression><array-variable><variable-name>states</variable-name><subscript-list><expression><variable-name>axis</variable-name></expression></subscript-list></array-variable></expression>
+<case-element>
+<case-list-element><integer-literal>0</integer-literal></case-list-element>
+<if-statement>
+<expression><array-variable><variable-name>enable</variable-name><subscript-list><expression><variable-name>axis</variable-name></expression></subscript-list></array-variable></expression>
+<assignment-statement><array-variable><variable-name>states</variable-name><subscript-list><expression><variable-name>axis</variable-name></expression></subscript-list></array-variable><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>homed</variable-name></expression>
+<assignment-statement><array-variable><variable-name>states</variable-name><subscript-list><expression><variable-name>axis</variable-name></expression></subscript-list></array-variable><expression><integer-literal>0</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // The comparison's right-hand side is a call the parser doesn't render
+    const MALFORMED_GUARD_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>ValveFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>speed</variable-name></expression><greater-than/><expression><function-call><derived-function-name>LIMIT</derived-function-name></function-call></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // The same IF appears twice, as copy-pasted PLC code often does
+    const DUPLICATE_IF_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PumpFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const OUTPUTS_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>MixerFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>ready</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+<assignment-statement><variable-name>motor</variable-name><expression><integer-literal>1</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>30</integer-literal></case-list-element>
+<assignment-statement><variable-name>valve</variable-name><expression><boolean-literal>TRUE</boolean-literal></expression></assignment-statement>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    // Textual SFC: INITIAL_STEP Idle, STEP Filling, two TRANSITION FROM .. TO ..
+    const SFC_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>FillSFC</derived-function-block-name>
+<sequential-function-chart>
+<initial-step><step-name>Idle</step-name></initial-step>
+<step><step-name>Filling</step-name></step>
+<transition>
+<steps><step-name>Idle</step-name></steps>
+<steps><step-name>Filling</step-name></steps>
+<transition-condition><expression><expression><variable-name>level</variable-name></expression><less-than/><expression><integer-literal>10</integer-literal></expression></expression></transition-condition>
+</transition>
+<transition>
+<steps><step-name>Filling</step-name></steps>
+<steps><step-name>Idle</step-name></steps>
+<transition-condition><expression><variable-name>full</variable-name></expression></transition-condition>
+</transition>
+</sequential-function-chart>
+</function-block-declaration>
+</project>"#;
+
+    // IF a = 1 THEN state := 20; ELSE state := 30; END_IF
+    const ELSE_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>BranchFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>a</variable-name></expression><equal/><expression><integer-literal>1</integer-literal></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+<else-clause>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+</else-clause>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const NESTED_CASE_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PressFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<case-statement>
+<expression><variable-name>substate</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>clamped</variable-name></expression>
+<assignment-statement><variable-name>substate</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>pressed</variable-name></expression>
+<assignment-statement><variable-name>substate</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>10</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const REAL_GUARD_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>HeaterFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>temp</variable-name></expression><greater-or-equal/><expression><real-literal>37.5</real-literal></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const TIME_GUARD_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>TimerFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>timer</variable-name></expression><greater-or-equal/><expression><time-literal><duration>T#5s</duration></time-literal></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    const COMPUTED_TARGET_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>SequencerFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><expression><variable-name>base</variable-name></expression><adding/><expression><integer-literal>10</integer-literal></expression></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>done</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><expression><integer-literal>10</integer-literal></expression><adding/><expression><integer-literal>20</integer-literal></expression></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    fn write_fixture(xml: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(xml.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_extract_from_in_memory_xml() {
+        let extractor = FsmExtractor::from_reader(
+            DOUBLE_ASSIGNMENT_XML.as_bytes(),
+            STDIN_SOURCE,
+            &XmlPreprocessor::default(),
+        )
+        .unwrap();
+        let fsm = extractor.extract().unwrap();
+
+        assert_eq!(fsm.metadata.source_file, Path::new("<stdin>"));
+        assert_eq!(fsm.function_blocks[0].name, "PressFB");
+        assert_eq!(fsm.function_blocks[0].transition_count(), 1);
+    }
+
+    #[test]
+    fn test_reanalyze_from_exported_json() {
+        let file = write_fixture(ESTOP_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let out = tempfile::NamedTempFile::new().unwrap();
+        OutputWriter::new(OutputFormat::Json).write(&fsm, Some(out.path())).unwrap();
+        let reloaded = FiniteStateMachine::from_json_file(out.path()).unwrap();
+
+        let analyzer = FsmAnalyzer::new();
+        let fresh = serde_json::to_value(analyzer.analyze_all(&fsm)).unwrap();
+        assert_eq!(serde_json::to_value(analyzer.analyze_all(&reloaded)).unwrap(), fresh);
+        assert!(fresh.as_object().is_some_and(|blocks| !blocks.is_empty()));
+    }
+
+    #[test]
+    fn test_last_case_assignment_wins() {
+        let file = write_fixture(DOUBLE_ASSIGNMENT_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.transition_count(), 1);
+        assert_eq!(fb.transitions[0].from_state, "10");
+        assert_eq!(fb.transitions[0].to_state, "30");
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("c"));
+        assert!(fb.get_state("20").is_none());
+    }
+
+    #[test]
+    fn test_nested_ifs_conjoin_guards() {
+        let file = write_fixture(NESTED_IF_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.transition_count(), 1);
+        assert_eq!(fb.transitions[0].to_state, "20");
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("a AND b"));
+    }
+
+    #[test]
+    fn test_init_transition_before_case() {
+        let file = write_fixture(INIT_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        let init = fb.init_transition.as_ref().unwrap();
+        assert_eq!(init.from_state, INIT_STATE);
+        assert_eq!(init.to_state, "100");
+        assert_eq!(init.condition.as_deref(), Some("firstScan = TRUE"));
+        assert_eq!(init.actions, vec!["motor := FALSE".to_string()]);
+        assert_eq!(fb.transition_count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_reference_logged_or_fatal() {
+        let file = write_fixture(INIT_XML);
+        let mut fb = FunctionBlock::new("BrokenFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "go = TRUE".to_string()));
+
+        let warnings = WarningLog::default();
+        let extractor = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { warnings: warnings.clone(), ..Default::default() });
+        assert!(extractor.check_references(&fb).is_ok());
+        let logged = warnings.entries();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].kind, WarningKind::InvalidStateReference);
+        assert_eq!(logged[0].block, "BrokenFB");
+
+        let strict = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { strict: true, ..Default::default() });
+        let err = strict.check_references(&fb).unwrap_err();
+        assert!(err.to_string().contains("BrokenFB"));
+    }
+
+    #[test]
+    fn test_init_flag_matched_as_whole_identifier() {
+        let file = write_fixture(&INIT_XML.replace("firstScan", "firstFault"));
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+        let fb = &fsm.function_blocks[0];
+        assert!(fb.init_transition.is_none());
+        assert!(fb.transitions.iter().any(|t| t.is_external_force && t.to_state == "100"));
+
+        let fsm = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { init_flags: vec!["FirstFault".to_string()], ..Default::default() })
+            .extract()
+            .unwrap();
+        let init = fsm.function_blocks[0].init_transition.as_ref().unwrap();
+        assert_eq!(init.condition.as_deref(), Some("firstFault = TRUE"));
+    }
+
+    #[test]
+    fn test_external_force_from_every_state() {
+        let file = write_fixture(ESTOP_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert!(fb.init_transition.is_none());
+
+        let forced: Vec<_> = fb.transitions.iter().filter(|t| t.is_external_force).collect();
+        assert_eq!(forced.len(), 2);
+        for transition in &forced {
+            assert_eq!(transition.to_state, "0");
+            assert_eq!(transition.condition.as_deref(), Some("estop = TRUE"));
+        }
+        let mut sources: Vec<&str> = forced.iter().map(|t| t.from_state.as_str()).collect();
+        sources.sort();
+        assert_eq!(sources, vec!["10", "20"]);
+    }
+
+    #[test]
+    fn test_require_transitions_rejects_empty_block() {
+        let file = write_fixture(UNSUPPORTED_CASE_XML);
+
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+        assert_eq!(fsm.metadata.total_transitions, 0);
+
+        let err = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { require_transitions: true, ..Default::default() })
+            .extract()
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("LoopFB"));
+        assert!(message.contains("--verbose"));
+    }
+
+    #[test]
+    fn test_dedup_transitions_collapses_repeats() {
+        let file = write_fixture(DUPLICATE_IF_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+        assert_eq!(fsm.function_blocks[0].transition_count(), 2);
+
+        let fsm = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { dedup_transitions: true, ..Default::default() })
+            .extract()
+            .unwrap();
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.transition_count(), 1);
+        assert_eq!(fb.states["10"].transitions_out.len(), 1);
+        assert_eq!(fb.states["20"].transitions_in.len(), 1);
+    }
+
+    #[test]
+    fn test_terminal_states_not_dead() {
+        let file = write_fixture(TERMINAL_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+        let stats = FsmStatistics::analyze(&fsm.function_blocks[0]);
+        assert_eq!(stats.dead_states, ["30"]);
+        // 40 stays put by default but can still resume
+        assert_eq!(stats.terminal_states, ["20"]);
+        assert!(!fsm.function_blocks[0].states["40"].terminal);
+
+        let fsm = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions { terminal_states: vec!["30".to_string()], ..Default::default() })
+            .extract()
+            .unwrap();
+        let stats = FsmStatistics::analyze(&fsm.function_blocks[0]);
+        assert!(stats.dead_states.is_empty());
+        assert_eq!(stats.terminal_states, ["20", "30"]);
+    }
+
+    #[test]
+    fn test_aliased_temp_state_variable() {
+        let file = write_fixture(ALIASED_TEMP_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.case_variable, "state");
+        assert_eq!(fb.transition_count(), 2);
+        assert_eq!((fb.transitions[0].from_state.as_str(), fb.transitions[0].to_state.as_str()), ("10", "20"));
+        assert_eq!((fb.transitions[1].from_state.as_str(), fb.transitions[1].to_state.as_str()), ("20", "10"));
+
+        // Without the copy statements only a manual alias connects the two
+        let unaliased = ALIASED_TEMP_XML
+            .lines()
+            .filter(|line| !line.starts_with("<assignment-statement>") || line.contains("-literal>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let file = write_fixture(&unaliased);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+        assert_eq!(fsm.function_blocks[0].transition_count(), 1);
+
+        let fsm = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(ExtractOptions {
+                state_aliases: vec!["tmpState=state".parse().unwrap()],
+                ..Default::default()
+            })
+            .extract()
+            .unwrap();
+        assert_eq!(fsm.function_blocks[0].transition_count(), 2);
+        assert_eq!(fsm.function_blocks[0].case_variable, "state");
+    }
+
+    #[test]
+    fn test_history_copy_is_not_an_alias() {
+        let file = write_fixture(HISTORY_COPY_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.case_variable, "state");
+        assert_eq!(fb.transition_count(), 1);
+        assert_eq!((fb.transitions[0].from_state.as_str(), fb.transitions[0].to_state.as_str()), ("20", "10"));
+    }
+
+    #[test]
+    fn test_array_indexed_case_variable() {
+        let file = write_fixture(ARRAY_INDEXED_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.case_variable, "states[axis]");
+        assert_eq!(fb.transition_count(), 2);
+        assert_eq!((fb.transitions[0].from_state.as_str(), fb.transitions[0].to_state.as_str()), ("0", "10"));
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("enable[axis]"));
+        let guard = SignatureGenerator::parse_transition_condition("enable[axis]");
+        assert_eq!(guard[0][0].variable, "enable[axis]");
+        assert_eq!((fb.transitions[1].from_state.as_str(), fb.transitions[1].to_state.as_str()), ("10", "0"));
+    }
+
+    #[test]
+    fn test_malformed_guard_warning_json() {
+        let file = write_fixture(MALFORMED_GUARD_XML);
+        let options = ExtractOptions::default();
+        let fsm = FsmExtractor::new(file.path())
+            .unwrap()
+            .with_options(options.clone())
+            .extract()
+            .unwrap();
+        assert_eq!(fsm.function_blocks[0].transition_count(), 1);
+
+        let out = tempfile::NamedTempFile::new().unwrap();
+        options.warnings.write_json(out.path()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(out.path()).unwrap()).unwrap();
+
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["kind"], "MalformedGuard");
+        assert_eq!(entries[0]["block"], "ValveFB");
+        assert!(entries[0]["detail"].as_str().unwrap().contains("speed >"));
+    }
+
+    #[test]
+    fn test_state_outputs_from_entry_and_body() {
+        let file = write_fixture(OUTPUTS_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let outputs = fsm.function_blocks[0].state_outputs();
+        let motor = Assignment { variable: "motor".to_string(), value: "1".to_string(), computed: false };
+        let valve = Assignment { variable: "valve".to_string(), value: "TRUE".to_string(), computed: false };
+        assert_eq!(outputs["30"], vec![valve, motor]);
+        assert!(outputs["20"].is_empty());
+    }
+
+    #[test]
+    fn test_sfc_steps_and_transitions() {
+        let file = write_fixture(SFC_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.name, "FillSFC");
+        assert_eq!(fb.states.keys().collect::<Vec<_>>(), vec!["Idle", "Filling"]);
+        assert_eq!(fb.transition_count(), 2);
+        assert_eq!((fb.transitions[0].from_state.as_str(), fb.transitions[0].to_state.as_str()), ("Idle", "Filling"));
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("level < 10"));
+        assert_eq!((fb.transitions[1].from_state.as_str(), fb.transitions[1].to_state.as_str()), ("Filling", "Idle"));
+        assert_eq!(fb.transitions[1].condition.as_deref(), Some("full"));
+        assert_eq!(fb.init_transition.as_ref().map(|t| t.to_state.as_str()), Some("Idle"));
+    }
+
+    #[test]
+    fn test_else_branch_default_transition() {
+        let file = write_fixture(ELSE_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.transition_count(), 2);
+        assert_eq!(fb.transitions[0].to_state, "20");
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("a = 1"));
+        assert_eq!(fb.transitions[1].to_state, "30");
+        assert_eq!(fb.transitions[1].condition.as_deref(), Some("NOT (a = 1)"));
+
+        let guard = SignatureGenerator::parse_transition_condition("NOT (a = 1)");
+        assert_eq!(guard, vec![vec![Condition::new("a".to_string(), "<>".to_string(), "1".to_string())]]);
+    }
+
+    #[test]
+    fn test_nested_case_as_sub_machine() {
+        let file = write_fixture(NESTED_CASE_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.states.keys().collect::<Vec<_>>(), ["10", "20"]);
+        assert_eq!(fb.transition_count(), 2);
+        assert_eq!(fb.transitions[1].condition.as_deref(), Some("done"));
+
+        assert_eq!(fb.sub_machines.len(), 1);
+        let sub = &fb.sub_machines[0];
+        assert_eq!(sub.name, "PressFB.20");
+        assert_eq!(sub.case_variable, "substate");
+        assert_eq!(sub.parent_state.as_deref(), Some("20"));
+        let edges: Vec<_> = sub.transitions.iter().map(|t| (t.from_state.as_str(), t.to_state.as_str())).collect();
+        assert_eq!(edges, [("10", "20"), ("20", "10")]);
+
+        let dot = crate::output::dot::render_function_block(fb, &crate::output::OutputOptions::default());
+        assert!(dot.contains("subgraph \"cluster_20\""));
+        assert!(dot.contains("\"20.10\" -> \"20.20\""));
+    }
+
+    #[test]
+    fn test_real_literal_guard_condition() {
+        let file = write_fixture(REAL_GUARD_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let table = SignatureGenerator::generate(&fsm.function_blocks[0]).unwrap();
+        let signature = &table.get_signature("20").unwrap().path_signatures[0];
+        assert_eq!(signature.conditions, [Condition::new("temp".to_string(), ">=".to_string(), "37.5".to_string())]);
+        assert_eq!(signature.conditions[0].value, "37.5");
+
+        let runtime = |temp: &str| std::collections::HashMap::from([("temp".to_string(), temp.to_string())]);
+        assert!(signature.matches(&runtime("37.50")));
+        assert!(!signature.matches(&runtime("37.4")));
+    }
+
+    #[test]
+    fn test_time_literal_guard_condition() {
+        let file = write_fixture(TIME_GUARD_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        assert_eq!(fb.transitions[0].condition.as_deref(), Some("timer >= T#5s"));
+
+        let table = SignatureGenerator::generate(fb).unwrap();
+        let signature = &table.get_signature("20").unwrap().path_signatures[0];
+        assert_eq!(signature.conditions, [Condition::new("timer".to_string(), ">=".to_string(), "T#5s".to_string())]);
+
+        let runtime = |timer: &str| std::collections::HashMap::from([("timer".to_string(), timer.to_string())]);
+        assert!(signature.matches(&runtime("T#1m")));
+        assert!(signature.matches(&runtime("5000")));
+        assert!(!signature.matches(&runtime("T#4s_999ms")));
+    }
+
+    #[test]
+    fn test_computed_target_recorded() {
+        let file = write_fixture(COMPUTED_TARGET_XML);
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let fb = &fsm.function_blocks[0];
+        let computed = &fb.transitions[0];
+        assert_eq!(computed.to_state, COMPUTED_STATE);
+        assert!(computed.computed_target);
+        assert_eq!(computed.target_expression.as_deref(), Some("base + 10"));
+
+        // The runtime target is no state of its own, so it is no dead end either
+        assert!(!fb.states.contains_key(COMPUTED_STATE));
+        let stats = FsmStatistics::analyze(fb);
+        assert_eq!(stats.total_states, 3);
+        assert_eq!(stats.dead_states, ["30"]);
+        assert!(FsmValidator::validate_references(fb).is_ok());
+        let signatures = SignatureGenerator::generate(fb).unwrap();
+        assert!(signatures.get_signature(COMPUTED_STATE).is_none());
+
+        // Arithmetic over constants is folded into an ordinary target
+        let folded = &fb.transitions[1];
+        assert_eq!(folded.to_state, "30");
+        assert!(!folded.computed_target);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::fsm::{State, Transition};
+use crate::xml_parser::Assignment;
 use indexmap::IndexMap;
 use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionBlock {
@@ -8,6 +10,21 @@ pub struct FunctionBlock {
     pub case_variable: String,
     pub states: IndexMap<String, State>,
     pub transitions: Vec<Transition>,
+    /// Initialization ahead of the CASE, modelled as `INIT_STATE -> initial state`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_transition: Option<Transition>,
+    /// Machines of CASE statements nested inside one of the states
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_machines: Vec<FunctionBlock>,
+    /// For a sub-machine, the state of the enclosing machine it runs in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_state: Option<String>,
+    /// `VAR_INPUT` and `VAR_IN_OUT` variables of the block's interface
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declared_inputs: Vec<String>,
+    /// Every variable the block declares, inputs included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declared_variables: Vec<String>,
 }
 
 impl FunctionBlock {
@@ -17,6 +34,11 @@ impl FunctionBlock {
             case_variable,
             states: IndexMap::new(),
             transitions: Vec::new(),
+            init_transition: None,
+            sub_machines: Vec::new(),
+            parent_state: None,
+            declared_inputs: Vec::new(),
+            declared_variables: Vec::new(),
         }
     }
 
@@ -24,7 +46,17 @@ impl FunctionBlock {
         self.states.insert(state.id.clone(), state);
     }
 
-    pub fn add_transition(&mut self, transition: Transition) {
+    /// Add `transition`, suffixing its id with `_2`, `_3`, ... when another
+    /// transition between the same states already has it, so ids stay unique
+    pub fn add_transition(&mut self, mut transition: Transition) {
+        if self.transitions.iter().any(|t| t.id == transition.id) {
+            let base = transition.id.clone();
+            transition.id = (2..)
+                .map(|n| format!("{}_{}", base, n))
+                .find(|id| !self.transitions.iter().any(|t| &t.id == id))
+                .unwrap_or(base);
+        }
+
         // Update state references
         if let Some(from_state) = self.states.get_mut(&transition.from_state) {
             from_state.transitions_out.push(transition.id.clone());
@@ -40,6 +72,11 @@ impl FunctionBlock {
         self.states.get(id)
     }
 
+    /// Transition with the given id, as listed in `State::transitions_out`/`transitions_in`
+    pub fn get_transition(&self, id: &str) -> Option<&Transition> {
+        self.transitions.iter().find(|t| t.id == id)
+    }
+
     pub fn state_count(&self) -> usize {
         self.states.len()
     }
@@ -47,4 +84,173 @@ impl FunctionBlock {
     pub fn transition_count(&self) -> usize {
         self.transitions.len()
     }
-}
\ No newline at end of file
+
+    /// Indices into `transitions` by source state, in transition order
+    pub fn outgoing_transitions(&self) -> HashMap<String, Vec<usize>> {
+        let mut outgoing: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, transition) in self.transitions.iter().enumerate() {
+            outgoing.entry(transition.from_state.clone()).or_default().push(idx);
+        }
+        outgoing
+    }
+
+    /// Moore-style outputs: for each state, the assignments holding while in it,
+    /// made by the state itself or by any transition entering it
+    pub fn state_outputs(&self) -> HashMap<String, Vec<Assignment>> {
+        let entering = self.transitions.iter().chain(self.init_transition.as_ref());
+
+        let mut outputs: HashMap<String, Vec<Assignment>> = self.states
+            .values()
+            .map(|state| (state.id.clone(), state.actions.iter().filter_map(|a| Self::parse_action(a)).collect()))
+            .collect();
+
+        for transition in entering {
+            let Some(assignments) = outputs.get_mut(&transition.to_state) else {
+                continue;
+            };
+            for assignment in transition.actions.iter().filter_map(|a| Self::parse_action(a)) {
+                if !assignments.contains(&assignment) {
+                    assignments.push(assignment);
+                }
+            }
+        }
+
+        outputs
+    }
+
+    /// Copy for drawing: with `hide_passthrough`, every state with exactly one
+    /// incoming and one outgoing transition is left out and each chain through
+    /// such states becomes a single edge recording them in `via_states`. A
+    /// cycle made only of such states keeps its first state
+    pub fn diagram_view(&self, hide_passthrough: bool) -> FunctionBlock {
+        if !hide_passthrough {
+            return self.clone();
+        }
+
+        let mut incoming: HashMap<&str, usize> = HashMap::new();
+        let mut outgoing: HashMap<&str, Vec<&Transition>> = HashMap::new();
+        for transition in &self.transitions {
+            *incoming.entry(&transition.to_state).or_default() += 1;
+            outgoing.entry(&transition.from_state).or_default().push(transition);
+        }
+
+        let entry = self.init_transition.as_ref().map(|t| t.to_state.as_str());
+        let mut hidden: HashSet<&str> = self.states
+            .keys()
+            .map(String::as_str)
+            .filter(|id| {
+                Some(*id) != entry
+                    && incoming.get(id) == Some(&1)
+                    && outgoing.get(id).is_some_and(|out| out.len() == 1 && out[0].to_state != *id && !out[0].computed_target)
+            })
+            .collect();
+
+        for id in self.states.keys() {
+            let mut next = id.as_str();
+            while hidden.contains(next) {
+                next = &outgoing[next][0].to_state;
+                if next == id {
+                    hidden.remove(next);
+                }
+            }
+        }
+        let is_passthrough = |id: &str| hidden.contains(id);
+
+        let mut view = FunctionBlock::new(self.name.clone(), self.case_variable.clone());
+        view.init_transition = self.init_transition.clone();
+        view.parent_state = self.parent_state.clone();
+        view.declared_inputs = self.declared_inputs.clone();
+        view.declared_variables = self.declared_variables.clone();
+        view.sub_machines = self.sub_machines.iter().map(|sub| sub.diagram_view(true)).collect();
+
+        for state in self.states.values().filter(|s| !is_passthrough(&s.id)) {
+            let mut state = state.clone();
+            state.transitions_in.clear();
+            state.transitions_out.clear();
+            view.add_state(state);
+        }
+
+        for transition in self.transitions.iter().filter(|t| !is_passthrough(&t.from_state)) {
+            let mut via_states = Vec::new();
+            let mut target = transition.to_state.as_str();
+            while is_passthrough(target) && !via_states.iter().any(|s| s == target) {
+                via_states.push(target.to_string());
+                target = &outgoing[target][0].to_state;
+            }
+
+            if via_states.is_empty() {
+                view.add_transition(transition.clone());
+            } else {
+                let mut shortcut = Transition::unconditional(transition.from_state.clone(), target.to_string());
+                shortcut.via_states = via_states;
+                view.add_transition(shortcut);
+            }
+        }
+
+        view
+    }
+
+    fn parse_action(action: &str) -> Option<Assignment> {
+        let (variable, value) = action.split_once(" := ")?;
+        Some(Assignment { variable: variable.to_string(), value: value.to_string(), computed: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::FsmStatistics;
+
+    #[test]
+    fn test_diagram_view_hides_passthrough_state() {
+        let mut fb = FunctionBlock::new("LineFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "done".to_string()));
+
+        let view = fb.diagram_view(true);
+        assert_eq!(view.states.keys().collect::<Vec<_>>(), ["10", "30"]);
+        assert_eq!(view.transition_count(), 1);
+        assert_eq!((view.transitions[0].from_state.as_str(), view.transitions[0].to_state.as_str()), ("10", "30"));
+        assert_eq!(view.transitions[0].via_states, ["20"]);
+
+        let dot = crate::output::dot::render_function_block(&view, &crate::output::OutputOptions::default());
+        assert!(dot.contains("\"10\" -> \"30\" [label=\"(via 1 state)\", style=dashed]"));
+
+        assert_eq!(FsmStatistics::analyze(&fb).total_states, 3);
+    }
+
+    #[test]
+    fn test_diagram_view_keeps_a_state_of_each_cycle() {
+        let mut fb = FunctionBlock::new("LoopFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "a".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "b".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "c".to_string()));
+
+        let view = fb.diagram_view(true);
+        assert_eq!(view.states.keys().collect::<Vec<_>>(), ["10"]);
+        assert_eq!(view.transition_count(), 1);
+        assert_eq!((view.transitions[0].from_state.as_str(), view.transitions[0].to_state.as_str()), ("10", "10"));
+        assert_eq!(view.transitions[0].via_states, ["20", "30"]);
+    }
+
+    #[test]
+    fn test_parallel_transitions_get_distinct_ids() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "manual = TRUE".to_string()));
+
+        let ids: Vec<&str> = fb.transitions.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["10_to_20", "10_to_20_2"]);
+        assert_eq!(fb.states["10"].transitions_out, ids);
+        assert_eq!(fb.states["20"].transitions_in, ids);
+        assert_eq!(fb.get_transition("10_to_20_2").and_then(|t| t.condition.as_deref()), Some("manual = TRUE"));
+    }
+}
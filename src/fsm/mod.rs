@@ -2,15 +2,24 @@ pub mod state;
 pub mod transition;
 pub mod function_block;
 pub mod extractor;
+pub mod warnings;
 
 pub use state::State;
-pub use transition::Transition;
+pub use transition::{GuardMetrics, Transition};
 pub use function_block::FunctionBlock;
-pub use extractor::FsmExtractor;
+pub use extractor::{ExtractOptions, FsmExtractor, StateAlias, STDIN_SOURCE};
+pub use warnings::{ExtractionWarning, WarningKind, WarningLog};
 
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Synthetic source node of `FunctionBlock::init_transition`
+pub const INIT_STATE: &str = "__init__";
+
+/// Target of transitions whose next state is computed at runtime
+pub const COMPUTED_STATE: &str = "<computed>";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FiniteStateMachine {
@@ -24,4 +33,34 @@ pub struct Metadata {
     pub extraction_date: DateTime<Utc>,
     pub total_states: usize,
     pub total_transitions: usize,
+}
+
+impl FiniteStateMachine {
+    /// Load a machine previously written by the JSON output, so it can be
+    /// re-analyzed without the original XML
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("{} is not an FSM JSON file", path.display()))
+    }
+
+    /// Standalone machine holding just `fb`, with totals for that block and
+    /// the source file of the full extraction
+    pub fn single_block(&self, fb: &FunctionBlock) -> FiniteStateMachine {
+        FiniteStateMachine {
+            function_blocks: vec![fb.clone()],
+            metadata: Metadata {
+                source_file: self.metadata.source_file.clone(),
+                extraction_date: self.metadata.extraction_date,
+                total_states: fb.state_count(),
+                total_transitions: fb.transition_count(),
+            },
+        }
+    }
+
+    /// One standalone machine per function block, in block order
+    pub fn split_by_block(&self) -> Vec<FiniteStateMachine> {
+        self.function_blocks.iter().map(|fb| self.single_block(fb)).collect()
+    }
 }
\ No newline at end of file
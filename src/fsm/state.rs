@@ -6,6 +6,14 @@ pub struct State {
     pub name: Option<String>,
     pub transitions_out: Vec<String>,  // IDs of outgoing transitions
     pub transitions_in: Vec<String>,   // IDs of incoming transitions
+    /// Assignments made on every scan while in the state, as `var := value`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+    /// Intentional final state, not reported as a dead end: it has no outgoing
+    /// transitions and assigns the case variable its own value, or was listed
+    /// in `ExtractOptions::terminal_states`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub terminal: bool,
 }
 
 impl State {
@@ -15,6 +23,8 @@ impl State {
             name: None,
             transitions_out: Vec::new(),
             transitions_in: Vec::new(),
+            actions: Vec::new(),
+            terminal: false,
         }
     }
 }
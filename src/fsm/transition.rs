@@ -1,12 +1,60 @@
+use crate::analysis::ConditionExpr;
 use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Size of a transition guard, for sizing or coloring edges downstream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct GuardMetrics {
+    /// Distinct variables compared in the guard
+    pub guard_variable_count: usize,
+    /// Guard combines conditions with AND, OR or XOR
+    pub guard_is_compound: bool,
+    /// Conjunctions in the guard's disjunctive normal form
+    pub dnf_term_count: usize,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transition {
     pub id: String,
     pub from_state: String,
     pub to_state: String,
-    pub condition: String,
+    /// Guard expression, `None` for an unconditional transition
+    #[serde(default, deserialize_with = "deserialize_condition")]
+    pub condition: Option<String>,
     pub raw_expression: String,
+    /// Assignments performed alongside the transition, as `var := value`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+    /// Assignment to the case variable outside the CASE, firing from any state
+    #[serde(default)]
+    pub is_external_force: bool,
+    /// The next state is an expression evaluated at runtime, held in
+    /// `target_expression`; `to_state` is `COMPUTED_STATE`
+    #[serde(default)]
+    pub computed_target: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_expression: Option<String>,
+    /// Pass-through states a diagram-only edge stands in for, in path order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub via_states: Vec<String>,
+    /// Position among the transitions out of the same state in source order;
+    /// at runtime an earlier IF/ELSIF branch wins over a later one
+    #[serde(default)]
+    pub priority: usize,
+    /// IF/ELSIF chain within the source state the transition is a branch of;
+    /// only branches of the same chain exclude each other
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<usize>,
+    /// Parsed `condition`, filled on first call to `guard_ast`
+    #[serde(skip)]
+    guard: OnceLock<Option<ConditionExpr>>,
+}
+
+/// Older exports wrote unconditional transitions as `"No Check"`
+fn deserialize_condition<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let condition = Option::<String>::deserialize(deserializer)?;
+    Ok(condition.filter(|c| c != "No Check"))
 }
 
 impl Transition {
@@ -16,8 +64,108 @@ impl Transition {
             id,
             from_state: from,
             to_state: to,
-            condition: condition.clone(),
-            raw_expression: condition,
+            raw_expression: condition.clone(),
+            condition: Some(condition),
+            actions: Vec::new(),
+            is_external_force: false,
+            computed_target: false,
+            target_expression: None,
+            via_states: Vec::new(),
+            priority: 0,
+            chain: None,
+            guard: OnceLock::new(),
+        }
+    }
+
+    pub fn unconditional(from: String, to: String) -> Self {
+        let mut transition = Self::new(from, to, String::new());
+        transition.condition = None;
+        transition
+    }
+
+    pub fn is_unconditional(&self) -> bool {
+        self.condition.is_none()
+    }
+
+    /// Guard text for display, with `unconditional_label` standing in for a missing guard
+    pub fn condition_label<'a>(&'a self, unconditional_label: &'a str) -> &'a str {
+        self.condition.as_deref().unwrap_or(unconditional_label)
+    }
+
+    /// Label of a diagram edge standing in for hidden pass-through states
+    pub fn via_label(&self) -> Option<String> {
+        match self.via_states.len() {
+            0 => None,
+            1 => Some("(via 1 state)".to_string()),
+            n => Some(format!("(via {} states)", n)),
+        }
+    }
+
+    /// Edge annotation naming the runtime target expression, for a
+    /// transition whose `to_state` is no real state
+    pub fn computed_target_label(&self) -> Option<String> {
+        self.computed_target
+            .then(|| format!("→ {}", self.target_expression.as_deref().unwrap_or("?")))
+    }
+
+    /// Parsed guard tree, or `None` if the transition is unconditional or the
+    /// condition couldn't be parsed
+    pub fn guard_ast(&self) -> Option<&ConditionExpr> {
+        self.guard
+            .get_or_init(|| self.condition.as_deref().and_then(ConditionExpr::parse))
+            .as_ref()
+    }
+
+    /// Complexity of the parsed guard; all zero when there is none
+    pub fn guard_metrics(&self) -> GuardMetrics {
+        let Some(guard) = self.guard_ast() else {
+            return GuardMetrics::default();
+        };
+
+        let dnf = guard.to_dnf();
+        let variables: HashSet<&str> = dnf
+            .iter()
+            .flatten()
+            .map(|cond| cond.variable.as_str())
+            .collect();
+
+        GuardMetrics {
+            guard_variable_count: variables.len(),
+            guard_is_compound: matches!(
+                guard,
+                ConditionExpr::And(..) | ConditionExpr::Or(..) | ConditionExpr::Xor(..)
+            ),
+            dnf_term_count: dnf.len(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_ast_or_node() {
+        let transition = Transition::new(
+            "10".to_string(),
+            "20".to_string(),
+            "sensor = low OR button = pressed".to_string(),
+        );
+
+        assert!(matches!(transition.guard_ast(), Some(ConditionExpr::Or(_, _))));
+
+        let json = serde_json::to_string(&transition).unwrap();
+        assert!(!json.contains("guard"));
+    }
+
+    #[test]
+    fn test_legacy_no_check_is_unconditional() {
+        let json = r#"{"id": "10_to_20", "from_state": "10", "to_state": "20", "condition": "No Check", "raw_expression": ""}"#;
+        let transition: Transition = serde_json::from_str(json).unwrap();
+        assert!(transition.is_unconditional());
+
+        let json = r#"{"id": "10_to_20", "from_state": "10", "to_state": "20", "raw_expression": ""}"#;
+        let transition: Transition = serde_json::from_str(json).unwrap();
+        assert!(transition.is_unconditional());
+    }
+}
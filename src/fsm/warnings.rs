@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// What an extraction warning is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WarningKind {
+    /// The block could not be parsed and was left out
+    SkippedBlock,
+    /// The block has a CASE but no transitions were extracted
+    NoTransitions,
+    /// A transition references a state the block doesn't declare
+    InvalidStateReference,
+    /// A later assignment to the case variable overrides earlier ones
+    ShadowedAssignment,
+    /// A guard that doesn't parse as a condition expression
+    MalformedGuard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtractionWarning {
+    pub kind: WarningKind,
+    pub block: String,
+    pub detail: String,
+}
+
+/// Warnings collected across extractions; clones share the same list
+#[derive(Debug, Clone, Default)]
+pub struct WarningLog(Arc<Mutex<Vec<ExtractionWarning>>>);
+
+impl WarningLog {
+    pub fn push(&self, kind: WarningKind, block: &str, detail: impl Into<String>) {
+        self.lock().push(ExtractionWarning {
+            kind,
+            block: block.to_string(),
+            detail: detail.into(),
+        });
+    }
+
+    pub fn entries(&self) -> Vec<ExtractionWarning> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<ExtractionWarning>> {
+        // A panic while pushing can't leave the list half-written
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Write all warnings as a JSON array of `{ kind, block, detail }`
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.lock())?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write warnings {}", path.display()))
+    }
+}
@@ -3,7 +3,15 @@ pub mod xml_parser;
 pub mod fsm;
 pub mod analysis;
 pub mod output;
+pub mod manifest;
+pub mod batch;
+pub mod config;
+pub mod trace;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(test)]
+mod test_fixtures;
 
 pub use fsm::{FsmExtractor, FiniteStateMachine, FunctionBlock, State, Transition};
-pub use analysis::{FsmAnalyzer, FsmStatistics, StateSignatureTable};
+pub use analysis::{evaluate_guard, AggregateStatistics, FsmAnalyzer, FsmStatistics, StateSignatureTable};
 pub use output::{OutputFormat, OutputWriter};
\ No newline at end of file
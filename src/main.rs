@@ -1,16 +1,14 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-mod error;
-mod xml_parser;
-mod fsm;
-mod analysis;
-mod output;
-
-use crate::fsm::FsmExtractor;
-use crate::output::{OutputFormat, OutputWriter};
-use crate::analysis::{FsmAnalyzer, AnalysisOptions};
+use plc_fsm_analyzer::fsm::{ExtractOptions, FiniteStateMachine, FsmExtractor, StateAlias, WarningLog, STDIN_SOURCE};
+use plc_fsm_analyzer::output::{dot, stats_csv, text, AnalyzeFormat, DotLayout, GuardStyle, ListFormat, OutputFormat, OutputOptions, OutputWriter, DEFAULT_UNCONDITIONAL_LABEL};
+use plc_fsm_analyzer::analysis::{FsmAnalyzer, AnalysisOptions, MatchMode, PathFinderOptions, SignatureDiff, SignatureGenerator, SignatureOptions, StateSignatureTable};
+use plc_fsm_analyzer::config::{Check, Config};
+use plc_fsm_analyzer::{batch, manifest, trace};
+use plc_fsm_analyzer::xml_parser::{RewriteRule, XmlPreprocessor};
 
 #[derive(Parser)]
 #[command(name = "plc-fsm-analyzer")]
@@ -19,19 +17,80 @@ use crate::analysis::{FsmAnalyzer, AnalysisOptions};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Extra text replacement applied to the XML before parsing (repeatable)
+    #[arg(long = "xml-rewrite", value_name = "FROM=TO", global = true)]
+    xml_rewrite: Vec<RewriteRule>,
+
+    /// Fail if a function block with a CASE statement yields no transitions
+    #[arg(long, global = true)]
+    require_transitions: bool,
+
+    /// Fail when an extracted transition references a missing state
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Print parse warnings to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Treat OLD as holding the same state as NEW (repeatable)
+    #[arg(long = "state-alias", value_name = "OLD=NEW", global = true)]
+    state_alias: Vec<StateAlias>,
+
+    /// States that end the machine on purpose, not reported as dead ends (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "STATES", global = true)]
+    terminal_states: Vec<String>,
+
+    /// Collapse transitions repeated exactly (same source, target, guard and actions)
+    #[arg(long, global = true)]
+    dedup_transitions: bool,
+
+    /// Flags marking an IF outside the CASE as first-scan initialization
+    /// (comma-separated, default: firstScan, first_scan, bFirstScan, xFirstScan, firstCycle, bFirstCycle, init, bInit)
+    #[arg(long, value_delimiter = ',', value_name = "FLAGS", global = true)]
+    init_flags: Vec<String>,
+
+    /// Also write extraction warnings to FILE as a JSON array
+    #[arg(long, value_name = "FILE", global = true)]
+    warnings_json: Option<PathBuf>,
+
+    /// Text shown for transitions without a guard [default: "No Check"]
+    #[arg(long, global = true)]
+    unconditional_label: Option<String>,
+
+    /// Show `var = TRUE` as `var` and `var = FALSE` as `NOT var` in guards and signatures
+    #[arg(long, global = true)]
+    boolean_shorthand: bool,
+
+    /// Operator spelling of displayed guards and signatures [default: st]
+    #[arg(long, value_enum, global = true)]
+    guard_style: Option<GuardStyle>,
+
+    /// Paths recorded per state when building signatures [default: 1000]
+    #[arg(long, value_name = "N", global = true)]
+    max_paths: Option<usize>,
+
+    /// Transitions a signature path may take [default: 1000]
+    #[arg(long, value_name = "N", global = true)]
+    max_path_length: Option<usize>,
+
+    /// Read option defaults from FILE instead of ./fsm-extractor.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Extract FSM from XML
     Extract {
-        /// Input XML file
+        /// Input XML file (`-` for stdin)
         #[arg(value_name = "FILE")]
         input: PathBuf,
 
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "text")]
-        format: OutputFormat,
+        /// Output format [default: text]
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
 
         /// Output file (stdout if not specified)
         #[arg(short, long)]
@@ -48,13 +107,77 @@ enum Commands {
         /// Generate state signatures
         #[arg(short = 's', long)]
         signatures: bool,
+
+        /// Render per-state decision tables of outgoing guards (markdown)
+        #[arg(long)]
+        decision_tables: bool,
+
+        /// Drop constant comparisons (e.g. `1 = 1`) from signature guards
+        #[arg(long)]
+        prune_guards: bool,
+
+        /// Minimize signatures over boolean variables (Quine–McCluskey)
+        #[arg(long)]
+        minimize_signatures: bool,
+
+        /// Leave out path signatures whose conditions contradict each other
+        #[arg(long)]
+        drop_contradictory: bool,
+
+        /// Render DOT guards as HTML-like tables
+        #[arg(long)]
+        rich_labels: bool,
+
+        /// Sorted, uncolored text output suitable for committing
+        #[arg(long)]
+        plain: bool,
+
+        /// Phrase guards and signatures in English (text/markdown)
+        #[arg(long)]
+        condition_language: bool,
+
+        /// Embed each block's DOT graph in the markdown report
+        #[arg(long)]
+        with_diagram: bool,
+
+        /// Shorten displayed signatures to N characters (text/markdown)
+        #[arg(long, value_name = "N")]
+        max_signature_width: Option<usize>,
+
+        /// Hide single-in/single-out states in diagrams (dot/drawio)
+        #[arg(long)]
+        compact_states: bool,
+
+        /// List at most N transitions per source state (text/markdown)
+        #[arg(long, value_name = "N")]
+        max_transitions_per_state: Option<usize>,
+
+        /// Draw all function blocks as clusters of one DOT graph
+        #[arg(long)]
+        single_graph: bool,
+
+        /// Leave out the legend of analysis colors and markers (dot/markdown)
+        #[arg(long)]
+        no_legend: bool,
+
+        /// Write one standalone file per function block into the --output directory
+        #[arg(long, requires = "output")]
+        split_by_block: bool,
+
+        /// Write a JSON index of the produced files
+        #[arg(long, value_name = "FILE", requires = "output")]
+        manifest_out: Option<PathBuf>,
     },
 
     /// Analyze FSM structure
     Analyze {
-        /// Input XML file
+        /// Input XML file (`-` for stdin), or FSM JSON with --from-json
         input: PathBuf,
 
+        /// Input is JSON written by `extract --format json` instead of XML
+        #[arg(long)]
+        from_json: bool,
+
         /// Check for cycles
         #[arg(long)]
         check_cycles: bool,
@@ -71,14 +194,182 @@ enum Commands {
         #[arg(long)]
         show_signatures: bool,
 
+        /// Check that a safe state stays reachable from every state
+        #[arg(long)]
+        check_safety: bool,
+
+        /// States considered safe (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        safe_states: Vec<String>,
+
+        /// Check guards compare each variable against one type of value
+        #[arg(long)]
+        check_types: bool,
+
+        /// Check each block has exactly one initial state
+        #[arg(long)]
+        check_single_entry: bool,
+
+        /// Check for guards comparing a variable with itself
+        #[arg(long)]
+        check_self_compare: bool,
+
+        /// Report reset transitions and states that can't return to the initial state
+        #[arg(long)]
+        resets: bool,
+
+        /// Check each state's outgoing guards cover every input, with example gaps
+        #[arg(long)]
+        check_partition: bool,
+
+        /// List transitions whose target state is computed at runtime
+        #[arg(long)]
+        check_computed_targets: bool,
+
+        /// Check declared inputs are read by guards and guard variables are declared
+        #[arg(long)]
+        check_interface: bool,
+
+        /// Report states with two transitions that can be enabled at once
+        #[arg(long)]
+        check_nondeterminism: bool,
+
+        /// Report IF/ELSIF branches an earlier branch always pre-empts
+        #[arg(long)]
+        check_masked: bool,
+
+        /// Report repeated transitions and transitions sharing an id
+        #[arg(long)]
+        check_duplicates: bool,
+
         /// Show all checks
         #[arg(long)]
         all: bool,
+
+        /// Cross-check generated signatures against the transition graph
+        #[arg(long, hide = true)]
+        self_check: bool,
+
+        /// Drop constant comparisons (e.g. `1 = 1`) from signature guards
+        #[arg(long)]
+        prune_guards: bool,
+
+        /// Minimize signatures over boolean variables (Quine–McCluskey)
+        #[arg(long)]
+        minimize_signatures: bool,
+
+        /// Leave out path signatures whose conditions contradict each other
+        #[arg(long)]
+        drop_contradictory: bool,
+
+        /// Stop listing cycles after N (default 1000) and summarize instead
+        #[arg(long, value_name = "N")]
+        max_cycles: Option<usize>,
+
+        /// Print a shortest route between two states and the guards along it
+        #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+        path: Option<Vec<String>>,
+
+        /// Print state walks from the initial state that together take every transition
+        #[arg(long)]
+        coverage: bool,
+
+        /// Print a shortest route from the initial state to STATE and the conditions it needs
+        #[arg(long, value_name = "STATE")]
+        reach: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: AnalyzeFormat,
+
+        /// Output file for --format stats-csv (stdout if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Extract FSMs from many XML files
+    Batch {
+        /// Input XML files, directories of them, or glob patterns like `plc/*.xml`
+        #[arg(value_name = "FILES")]
+        inputs: Vec<PathBuf>,
+
+        /// File listing inputs one per line (`-` reads stdin)
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+
+        /// Output format [default: text]
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Directory for one output file per input (stdout if not specified)
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
+
+        /// Write a JSON index of the produced files
+        #[arg(long, value_name = "FILE", requires = "output_dir")]
+        manifest_out: Option<PathBuf>,
+
+        /// Files processed in parallel (defaults to the number of CPUs; 1 without --output-dir)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+
+    /// Report states whose reaching conditions differ between two XML files
+    DiffSignatures {
+        /// Original XML file
+        old: PathBuf,
+
+        /// Changed XML file
+        new: PathBuf,
+
+        /// Only compare this function block
+        #[arg(short, long)]
+        block: Option<String>,
+
+        /// Drop constant comparisons (e.g. `1 = 1`) from signature guards
+        #[arg(long)]
+        prune_guards: bool,
+    },
+
+    /// List function blocks with their case variable and state count
+    List {
+        /// Input XML file (`-` for stdin)
+        input: PathBuf,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: ListFormat,
+    },
+
+    /// Check whether runtime variable values satisfy a state's signature
+    Verify {
+        /// Input XML file (`-` for stdin)
+        input: PathBuf,
+
+        /// Function block containing the state
+        #[arg(short = 'F', long)]
+        function_block: String,
+
+        /// State to verify
+        #[arg(short, long, required_unless_present = "trace")]
+        state: Option<String>,
+
+        /// Verify every record of a trace of `{state_id, variables}` (JSON, or CSV with a state_id column)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["state", "vars"])]
+        trace: Option<PathBuf>,
+
+        /// Runtime variable value (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+
+        /// Treat conditions on variables not given with --var as satisfied
+        #[arg(long)]
+        lenient: bool,
     },
 
     /// Generate visualization
     Visualize {
-        /// Input XML file
+        /// Input XML file (`-` for stdin)
         input: PathBuf,
 
         /// Output image file
@@ -88,82 +379,500 @@ enum Commands {
         /// Filter function blocks (comma-separated)
         #[arg(short = 'f', long, value_delimiter = ',')]
         function_block: Option<Vec<String>>,
+
+        /// Render guards as HTML-like tables
+        #[arg(long)]
+        rich_labels: bool,
+
+        /// Graphviz layout engine to target
+        #[arg(long, value_enum, default_value = "dot")]
+        layout: DotLayout,
+
+        /// Draw one node per function block with edges for block calls
+        #[arg(long)]
+        summary_graph: bool,
+
+        /// Hide single-in/single-out states, drawing a dashed "(via N states)" edge
+        #[arg(long)]
+        compact_states: bool,
+
+        /// Draw all function blocks as clusters of one graph
+        #[arg(long)]
+        single_graph: bool,
     },
+
+    /// Browse function blocks, states and signatures in a terminal UI
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Input XML file (`-` for stdin)
+        input: PathBuf,
+
+        /// Filter function blocks (comma-separated)
+        #[arg(short = 'f', long, value_delimiter = ',')]
+        function_block: Option<Vec<String>>,
+    },
+}
+
+/// Extractor over `input`, or over standard input when it is `-`
+fn open_extractor(input: &Path, preprocessor: &XmlPreprocessor) -> Result<FsmExtractor> {
+    if input == Path::new("-") {
+        FsmExtractor::from_reader(std::io::stdin().lock(), STDIN_SOURCE, preprocessor)
+    } else {
+        FsmExtractor::with_preprocessor(input, preprocessor)
+    }
+}
+
+/// `<block>.<ext>`, with characters unsafe in file names replaced and a
+/// suffix where two blocks would share a file
+fn split_file_name(block: &str, format: OutputFormat, taken: &mut HashSet<String>) -> String {
+    let base: String = block
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "_-.".contains(c) { c } else { '_' })
+        .collect();
+    batch::unique_file_name(&base, format.extension(), taken)
+}
+
+fn parse_var(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got '{}'", s)),
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let config = Config::discover(cli.config.as_deref(), Path::new("."))?;
+    let preprocessor = XmlPreprocessor::default().with_rules(cli.xml_rewrite);
+    let extract_options = ExtractOptions {
+        require_transitions: cli.require_transitions,
+        verbose: cli.verbose,
+        state_aliases: cli.state_alias,
+        strict: cli.strict,
+        terminal_states: cli.terminal_states,
+        dedup_transitions: cli.dedup_transitions,
+        init_flags: cli.init_flags,
+        warnings: WarningLog::default(),
+    };
+    let output_options = OutputOptions {
+        unconditional_label: cli.unconditional_label
+            .or_else(|| config.unconditional_label.clone())
+            .unwrap_or_else(|| DEFAULT_UNCONDITIONAL_LABEL.to_string()),
+        boolean_shorthand: cli.boolean_shorthand || config.boolean_shorthand,
+        guard_style: cli.guard_style.or(config.guard_style).unwrap_or_default(),
+        ..OutputOptions::default()
+    };
+    let path_limits = PathFinderOptions {
+        max_paths_per_state: Some(cli.max_paths
+            .or(config.max_paths)
+            .unwrap_or(PathFinderOptions::DEFAULT_MAX_PATHS_PER_STATE)),
+        max_depth: Some(cli.max_path_length
+            .or(config.max_path_length)
+            .unwrap_or(PathFinderOptions::DEFAULT_MAX_DEPTH)),
+    };
+
+    let warnings = extract_options.warnings.clone();
+    let result = run(cli.command, &config, &preprocessor, extract_options, output_options, path_limits);
 
-    match cli.command {
-        Commands::Extract { input, format, output, function_block, analyze, signatures } => {
-            let extractor = FsmExtractor::new(&input)?;
-            let fsm = if let Some(filters) = function_block {
+    // Written even when the command fails, since the warnings may explain why
+    if let Some(path) = cli.warnings_json {
+        warnings.write_json(&path)?;
+    }
+
+    result
+}
+
+fn run(
+    command: Commands,
+    config: &Config,
+    preprocessor: &XmlPreprocessor,
+    extract_options: ExtractOptions,
+    output_options: OutputOptions,
+    path_limits: PathFinderOptions,
+) -> Result<()> {
+    match command {
+        Commands::Extract { input, format, output, function_block, analyze, signatures, decision_tables, prune_guards, minimize_signatures, drop_contradictory, rich_labels, plain, condition_language, with_diagram, max_signature_width, compact_states, max_transitions_per_state, single_graph, no_legend, split_by_block, manifest_out } => {
+            let format = format.or(config.format).unwrap_or(OutputFormat::Text);
+            let extractor = open_extractor(&input, preprocessor)?
+                .with_options(extract_options);
+            let fsm = if let Some(filters) = function_block.or_else(|| config.function_blocks.clone()) {
                 extractor.extract_filtered(&filters)?
             } else {
                 extractor.extract()?
             };
 
-            let writer = OutputWriter::new(format);
-            let analyzer = FsmAnalyzer::new();
+            let options = OutputOptions {
+                decision_tables,
+                rich_labels,
+                plain,
+                condition_language,
+                with_diagram,
+                max_signature_width,
+                compact_states,
+                max_transitions_per_state,
+                legend: !no_legend,
+                single_graph,
+                ..output_options
+            };
+            let writer = OutputWriter::new(format).with_options(options);
+            let analyzer = FsmAnalyzer::new()
+                .with_signature_options(SignatureOptions {
+                    prune_guards,
+                    minimize_boolean: minimize_signatures,
+                    drop_contradictory,
+                    path_limits: path_limits.clone(),
+                    ..SignatureOptions::default()
+                });
 
-            // Handle different output combinations
-            match (analyze, signatures) {
-                (false, false) => {
-                    // Just FSM output
-                    writer.write(&fsm, output.as_deref())?;
-                },
-                (true, false) => {
-                    // FSM + Analysis
-                    let stats = analyzer.analyze_all(&fsm);
-                    writer.write_with_analysis(&fsm, &stats, output.as_deref())?;
-                },
-                (false, true) => {
-                    // FSM + Signatures
-                    let signatures = analyzer.generate_signatures(&fsm);
-                    writer.write_with_signatures(&fsm, &signatures, output.as_deref())?;
-                },
-                (true, true) => {
-                    // FSM + Analysis + Signatures
-                    let stats = analyzer.analyze_all(&fsm);
-                    let signatures = analyzer.generate_signatures(&fsm);
-                    writer.write_with_full_analysis(&fsm, &stats, &signatures, output.as_deref())?;
+            // SQL renders the signatures, so it honours the signature options too
+            let signatures = signatures || matches!(format, OutputFormat::Sql);
+
+            // Each block on its own, as a single-block machine, or everything at once
+            let targets = match (&output, split_by_block) {
+                (Some(dir), true) => {
+                    std::fs::create_dir_all(dir)?;
+                    let mut taken = HashSet::new();
+                    fsm.split_by_block()
+                        .into_iter()
+                        .map(|single| {
+                            let path = dir.join(split_file_name(&single.function_blocks[0].name, format, &mut taken));
+                            (single, Some(path))
+                        })
+                        .collect()
                 }
+                _ => vec![(fsm, output)],
+            };
+
+            let mut produced = Vec::new();
+            for (fsm, output) in targets {
+                // Handle different output combinations
+                match (analyze, signatures) {
+                    (false, false) => {
+                        // Just FSM output
+                        writer.write(&fsm, output.as_deref())?;
+                    },
+                    (true, false) => {
+                        // FSM + Analysis
+                        let stats = analyzer.analyze_all(&fsm);
+                        writer.write_with_analysis(&fsm, &stats, output.as_deref())?;
+                    },
+                    (false, true) => {
+                        // FSM + Signatures
+                        let signatures = analyzer.generate_signatures(&fsm)?;
+                        writer.write_with_signatures(&fsm, &signatures, output.as_deref())?;
+                    },
+                    (true, true) => {
+                        // FSM + Analysis + Signatures
+                        let stats = analyzer.analyze_all(&fsm);
+                        let signatures = analyzer.generate_signatures(&fsm)?;
+                        writer.write_with_full_analysis(&fsm, &stats, &signatures, output.as_deref())?;
+                    }
+                }
+                if let Some(output) = &output {
+                    produced.extend(manifest::output_entries(&fsm, output));
+                }
+            }
+
+            if let Some(manifest_out) = manifest_out {
+                manifest::write_output_manifest(&produced, &manifest_out)?;
             }
         },
         Commands::Analyze {
             input,
+            from_json,
             check_cycles,
             check_unreachable,
             check_dead_states,
             show_signatures,
-            all
+            check_safety,
+            safe_states,
+            check_types,
+            check_single_entry,
+            check_self_compare,
+            resets,
+            check_partition,
+            check_computed_targets,
+            check_interface,
+            check_nondeterminism,
+            check_masked,
+            check_duplicates,
+            all,
+            self_check,
+            prune_guards,
+            minimize_signatures,
+            drop_contradictory,
+            max_cycles,
+            path,
+            coverage,
+            reach,
+            format,
+            output,
         } => {
-            let extractor = FsmExtractor::new(&input)?;
-            let fsm = extractor.extract()?;
+            if output.is_some() && matches!(format, AnalyzeFormat::Text) {
+                anyhow::bail!("--output needs --format stats-csv; the text report is printed to stdout");
+            }
+
+            let fsm = if from_json {
+                FiniteStateMachine::from_json_file(&input)?
+            } else {
+                open_extractor(&input, preprocessor)?
+                    .with_options(extract_options)
+                    .extract()?
+            };
+
+            let flagged = [
+                (check_cycles, Check::Cycles),
+                (check_unreachable, Check::Unreachable),
+                (check_dead_states, Check::DeadStates),
+                (show_signatures, Check::Signatures),
+                (check_safety, Check::Safety),
+                (check_types, Check::Types),
+                (check_single_entry, Check::SingleEntry),
+                (check_self_compare, Check::SelfCompare),
+                (resets, Check::Resets),
+                (check_partition, Check::Partition),
+                (check_computed_targets, Check::ComputedTargets),
+                (check_interface, Check::Interface),
+                (check_nondeterminism, Check::Nondeterminism),
+                (check_masked, Check::Masked),
+                (check_duplicates, Check::Duplicates),
+            ];
+            let mut checks = config.checks.clone();
+            checks.extend(flagged.into_iter().filter(|(enabled, _)| *enabled).map(|(_, check)| check));
+            if all {
+                // Safety needs to be told which states are safe
+                checks.extend(Check::ALL.into_iter().filter(|check| *check != Check::Safety || !safe_states.is_empty()));
+            }
 
             let options = AnalysisOptions {
-                check_cycles: check_cycles || all,
-                check_unreachable: check_unreachable || all,
-                check_dead_states: check_dead_states || all,
-                show_signatures: show_signatures || all,
+                checks,
+                max_cycles,
+                safe_states: safe_states.into_iter().collect(),
+                path: path.map(|states| (states[0].clone(), states[1].clone())),
+                coverage,
+                reach,
             };
 
-            let analyzer = FsmAnalyzer::new();
-            analyzer.analyze_and_report(&fsm, &options)?;
+            let analyzer = FsmAnalyzer::new()
+                .with_signature_options(SignatureOptions {
+                    prune_guards,
+                    minimize_boolean: minimize_signatures,
+                    drop_contradictory,
+                    path_limits: path_limits.clone(),
+                    ..SignatureOptions::default()
+                });
+            if self_check {
+                let tables = analyzer.generate_signatures(&fsm)?;
+                let mut inconsistencies = 0;
+                for fb in &fsm.function_blocks {
+                    let Some(table) = tables.get(&fb.name) else {
+                        continue;
+                    };
+                    for inconsistency in SignatureGenerator::verify_against_graph(fb, table) {
+                        eprintln!("{}: {}", fb.name, inconsistency);
+                        inconsistencies += 1;
+                    }
+                }
+                if inconsistencies > 0 {
+                    anyhow::bail!("Self-check found {} inconsistencies", inconsistencies);
+                }
+            }
+
+            match format {
+                AnalyzeFormat::Text => analyzer.analyze_and_report(&fsm, &options)?,
+                AnalyzeFormat::StatsCsv => {
+                    let stats = analyzer.analyze_all(&fsm);
+                    stats_csv::export_stats_csv(&stats, output.as_deref())?;
+                },
+            }
+        },
+        Commands::Batch { inputs, manifest, format, output_dir, manifest_out, jobs } => {
+            let mut inputs = batch::expand_inputs(&inputs)?;
+            if let Some(manifest) = manifest {
+                inputs.extend(manifest::read_manifest(&manifest)?);
+            }
+            if inputs.is_empty() {
+                anyhow::bail!("No input files given (pass FILES or --manifest)");
+            }
+            // Overlapping patterns list a file once
+            let mut seen = HashSet::new();
+            inputs.retain(|input| seen.insert(input.clone()));
+            let format = format.or(config.format).unwrap_or(OutputFormat::Text);
+            let outputs = output_dir
+                .as_ref()
+                .map(|dir| batch::output_paths(&inputs, dir, format.extension()))
+                .unwrap_or_default();
+
+            let writer = OutputWriter::new(format).with_options(output_options);
+            // Output shared on stdout must not interleave
+            let jobs = match &output_dir {
+                Some(_) => jobs.unwrap_or_else(batch::default_jobs),
+                None => 1,
+            };
+
+            let results = batch::run_parallel(&inputs, jobs, |input| {
+                let fsm = FsmExtractor::with_preprocessor(input, preprocessor)?
+                    .with_options(extract_options.clone())
+                    .extract()?;
+
+                let output = outputs.get(input).cloned();
+                writer.write(&fsm, output.as_deref())?;
+
+                Ok(output.map(|output| manifest::output_entries(&fsm, &output)).unwrap_or_default())
+            });
+
+            let mut produced = Vec::new();
+            let mut failed = 0;
+            for result in results {
+                match result.outcome {
+                    Ok(entries) => {
+                        eprintln!("ok     {}", result.input.display());
+                        produced.extend(entries);
+                    }
+                    Err(e) => {
+                        eprintln!("error  {}: {:#}", result.input.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if let Some(manifest_out) = manifest_out {
+                manifest::write_output_manifest(&produced, &manifest_out)?;
+            }
+
+            eprintln!("Processed {} file(s)", inputs.len());
+            if failed > 0 {
+                anyhow::bail!("{} of {} file(s) failed", failed, inputs.len());
+            }
+        },
+        Commands::DiffSignatures { old, new, block, prune_guards } => {
+            let analyzer = FsmAnalyzer::new()
+                .with_signature_options(SignatureOptions { prune_guards, path_limits, ..SignatureOptions::default() });
+
+            let mut tables = Vec::new();
+            for input in [&old, &new] {
+                let extractor = open_extractor(input, preprocessor)?
+                    .with_options(extract_options.clone());
+                let fsm = match &block {
+                    Some(block) => extractor.extract_filtered(std::slice::from_ref(block))?,
+                    None => extractor.extract()?,
+                };
+                tables.push(analyzer.generate_signatures(&fsm)?);
+            }
+            let (new_tables, old_tables) = (tables.pop().unwrap_or_default(), tables.pop().unwrap_or_default());
+
+            let mut names: Vec<&String> = old_tables.keys().chain(new_tables.keys()).collect();
+            names.sort();
+            names.dedup();
+
+            let diffs: Vec<SignatureDiff> = names
+                .into_iter()
+                .map(|name| {
+                    let empty = StateSignatureTable::new(name.clone(), String::new());
+                    SignatureDiff::compare(
+                        old_tables.get(name).unwrap_or(&empty),
+                        new_tables.get(name).unwrap_or(&empty),
+                    )
+                })
+                .collect();
+
+            text::print_signature_diffs(&diffs);
         },
-        Commands::Visualize { input, output, function_block } => {
-            let extractor = FsmExtractor::new(&input)?;
-            let fsm = if let Some(filters) = function_block {
+        Commands::List { input, format } => {
+            let extractor = open_extractor(&input, preprocessor)?;
+            let blocks = extractor.list_function_blocks();
+
+            match format {
+                ListFormat::Text => {
+                    for block in &blocks {
+                        println!(
+                            "{}  case: {}  states: {}",
+                            block.name,
+                            block.case_variable.as_deref().unwrap_or("-"),
+                            block.state_count
+                        );
+                    }
+                }
+                ListFormat::Json => println!("{}", serde_json::to_string_pretty(&blocks)?),
+            }
+        }
+        Commands::Verify { input, function_block, state, trace, vars, lenient } => {
+            let fsm = open_extractor(&input, preprocessor)?
+                .with_options(extract_options)
+                .extract_filtered(std::slice::from_ref(&function_block))?;
+            let signatures = FsmAnalyzer::new().generate_signatures(&fsm)?;
+            let table = signatures.get(&function_block)
+                .ok_or_else(|| anyhow::anyhow!("Function block '{}' not found", function_block))?;
+
+            let mode = if lenient { MatchMode::LenientMissing } else { MatchMode::Strict };
+
+            if let Some(trace) = trace {
+                let records = trace::read_trace(&trace)?;
+                let verdicts = trace::verify_trace(table, &records, mode);
+                for (idx, (record, matched)) in records.iter().zip(&verdicts).enumerate() {
+                    let verdict = if *matched { "ok" } else { "MISMATCH" };
+                    println!("record {}: state {} {}", idx + 1, record.state_id, verdict);
+                }
+
+                let failed = verdicts.iter().filter(|matched| !**matched).count();
+                if failed > 0 {
+                    anyhow::bail!("{} of {} trace record(s) don't match their state", failed, records.len());
+                }
+                println!("All {} trace record(s) match their state", records.len());
+                return Ok(());
+            }
+
+            let state = state.expect("clap requires --state without --trace");
+            let runtime_vars: HashMap<String, String> = vars.into_iter().collect();
+            if table.verify_state_with_mode(&state, &runtime_vars, mode) {
+                println!("State {} matches the given variables", state);
+            } else {
+                anyhow::bail!("State {} does not match the given variables", state);
+            }
+        },
+        Commands::Visualize { input, output, function_block, rich_labels, layout, summary_graph, compact_states, single_graph } => {
+            let extractor = open_extractor(&input, preprocessor)?
+                .with_options(extract_options);
+            let fsm = if let Some(filters) = function_block.or_else(|| config.function_blocks.clone()) {
                 extractor.extract_filtered(&filters)?
             } else {
                 extractor.extract()?
             };
 
-            let writer = OutputWriter::new(OutputFormat::Dot);
-            writer.write(&fsm, Some(&output))?;
+            if summary_graph {
+                let calls: Vec<_> = extractor.extract_block_calls()
+                    .into_iter()
+                    .filter(|call| {
+                        [&call.caller, &call.callee]
+                            .iter()
+                            .all(|name| fsm.function_blocks.iter().any(|fb| fb.name == **name))
+                    })
+                    .collect();
+                dot::export_summary_graph(&fsm, &calls, Some(&output))?;
+            } else {
+                let writer = OutputWriter::new(OutputFormat::Dot)
+                    .with_options(OutputOptions { rich_labels, layout, compact_states, single_graph, ..output_options });
+                writer.write(&fsm, Some(&output))?;
+            }
 
             println!("Visualization saved to: {}", output.display());
-            println!("Generate image with: dot -Tpng {} -o {}.png", output.display(), output.display());
+            println!(
+                "Generate image with: {} -Tpng {} -o {}.png",
+                layout.engine(),
+                output.display(),
+                output.display()
+            );
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui { input, function_block } => {
+            let extractor = open_extractor(&input, preprocessor)?
+                .with_options(extract_options);
+            let fsm = if let Some(filters) = function_block.or_else(|| config.function_blocks.clone()) {
+                extractor.extract_filtered(&filters)?
+            } else {
+                extractor.extract()?
+            };
+
+            plc_fsm_analyzer::tui::run(fsm)?;
         }
     }
 
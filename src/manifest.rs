@@ -0,0 +1,131 @@
+use crate::fsm::FiniteStateMachine;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Read the input list from a manifest file, or from stdin when `path` is `-`.
+///
+/// Relative entries resolve against the manifest's directory (the current
+/// directory for stdin).
+pub fn read_manifest(path: &Path) -> Result<Vec<PathBuf>> {
+    if path == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        return Ok(parse_manifest(&content, Path::new(".")));
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    Ok(parse_manifest(&content, base_dir))
+}
+
+/// One path per line; blank lines and `#` comments are ignored
+pub fn parse_manifest(content: &str, base_dir: &Path) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let entry = Path::new(line);
+            if entry.is_absolute() {
+                entry.to_path_buf()
+            } else {
+                base_dir.join(entry)
+            }
+        })
+        .collect()
+}
+
+/// One produced output file and the function block it describes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputEntry {
+    pub block: String,
+    pub file: PathBuf,
+    pub states: usize,
+    pub transitions: usize,
+}
+
+/// Entries for every function block of `fsm` written to `file`
+pub fn output_entries(fsm: &FiniteStateMachine, file: &Path) -> Vec<OutputEntry> {
+    fsm.function_blocks
+        .iter()
+        .map(|fb| OutputEntry {
+            block: fb.name.clone(),
+            file: file.to_path_buf(),
+            states: fb.state_count(),
+            transitions: fb.transition_count(),
+        })
+        .collect()
+}
+
+/// Write the index of produced files as a JSON array
+pub fn write_output_manifest(entries: &[OutputEntry], path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(entries)?;
+    fs::write(path, json)
+        .with_context(|| format!("Failed to write manifest {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::PUMP_XML;
+    use crate::fsm::FsmExtractor;
+    use crate::output::{OutputFormat, OutputWriter};
+
+    #[test]
+    fn test_manifest_resolves_relative_and_absolute_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("exports")).unwrap();
+        let relative = dir.path().join("exports").join("line1.xml");
+        let absolute = dir.path().join("line2.xml");
+        fs::write(&relative, PUMP_XML).unwrap();
+        fs::write(&absolute, PUMP_XML).unwrap();
+
+        let manifest = dir.path().join("inputs.txt");
+        fs::write(
+            &manifest,
+            format!("# nightly exports\n\nexports/line1.xml\n  {}  \n", absolute.display()),
+        ).unwrap();
+
+        let inputs = read_manifest(&manifest).unwrap();
+        assert_eq!(inputs, vec![relative, absolute]);
+
+        for input in &inputs {
+            let fsm = FsmExtractor::new(input).unwrap().extract().unwrap();
+            assert_eq!(fsm.metadata.total_transitions, 1);
+        }
+    }
+
+    #[test]
+    fn test_output_manifest_lists_produced_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = OutputWriter::new(OutputFormat::Json);
+        let mut entries = Vec::new();
+
+        for name in ["line1", "line2"] {
+            let input = dir.path().join(format!("{name}.xml"));
+            fs::write(&input, PUMP_XML).unwrap();
+
+            let fsm = FsmExtractor::new(&input).unwrap().extract().unwrap();
+            let output = dir.path().join(name).with_extension(OutputFormat::Json.extension());
+            writer.write(&fsm, Some(&output)).unwrap();
+            entries.extend(output_entries(&fsm, &output));
+        }
+
+        let manifest = dir.path().join("index.json");
+        write_output_manifest(&entries, &manifest).unwrap();
+
+        let listed: Vec<OutputEntry> = serde_json::from_str(&fs::read_to_string(&manifest).unwrap()).unwrap();
+        assert_eq!(listed, entries);
+        assert_eq!(listed.len(), 2);
+        for entry in &listed {
+            assert_eq!(entry.block, "PumpFB");
+            assert_eq!((entry.states, entry.transitions), (2, 1));
+            assert!(entry.file.exists());
+        }
+    }
+}
@@ -1,10 +1,67 @@
-use crate::fsm::FiniteStateMachine;
+use crate::fsm::{FiniteStateMachine, FunctionBlock, Transition, INIT_STATE};
+use crate::analysis::{FsmStatistics, FsmValidator, SignatureGenerator};
+use crate::output::{condition_text, guard_label, DotLayout, OutputOptions};
+use crate::xml_parser::BlockCall;
 use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
-pub fn export_graphviz(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
+pub fn export_graphviz(fsm: &FiniteStateMachine, options: &OutputOptions, output_path: Option<&Path>) -> Result<()> {
+    write_output(&render_graphviz(fsm, None, options), output_path)
+}
+
+/// DOT output with states colored by role (initial green, dead-end grey,
+/// unreachable red, cycle member orange), edgeless states grouped into an
+/// "Isolated/Unreachable" cluster, and a legend unless `options.legend` is off
+pub fn export_with_analysis(
+    fsm: &FiniteStateMachine,
+    stats: &HashMap<String, FsmStatistics>,
+    options: &OutputOptions,
+    output_path: Option<&Path>
+) -> Result<()> {
+    write_output(&render_graphviz(fsm, Some(stats), options), output_path)
+}
+
+/// One node per function block, with an edge for each block invoking another
+pub fn export_summary_graph(fsm: &FiniteStateMachine, calls: &[BlockCall], output_path: Option<&Path>) -> Result<()> {
+    write_output(&render_summary_graph(fsm, calls), output_path)
+}
+
+fn render_summary_graph(fsm: &FiniteStateMachine, calls: &[BlockCall]) -> String {
+    let mut dot = String::from("digraph \"summary\" {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=box, style=filled, fillcolor=lightblue];\n\n");
+
+    for fb in &fsm.function_blocks {
+        dot.push_str(&format!(
+            "    \"{}\" [label=\"{}\\n{} states\"];\n",
+            fb.name,
+            fb.name,
+            fb.state_count()
+        ));
+    }
+
+    dot.push('\n');
+
+    for call in calls {
+        dot.push_str(&format!("    \"{}\" -> \"{}\";\n", call.caller, call.callee));
+    }
+
+    dot.push('}');
+    dot
+}
+
+fn render_graphviz(
+    fsm: &FiniteStateMachine,
+    stats: Option<&HashMap<String, FsmStatistics>>,
+    options: &OutputOptions,
+) -> String {
+    if options.single_graph {
+        return render_single_graph(fsm, stats, options);
+    }
+
     let mut dot = String::new();
 
     for (idx, fb) in fsm.function_blocks.iter().enumerate() {
@@ -12,35 +69,320 @@ pub fn export_graphviz(fsm: &FiniteStateMachine, output_path: Option<&Path>) ->
             dot.push_str("\n\n");
         }
 
-        dot.push_str(&format!("digraph \"{}\" {{\n", fb.name));
-        dot.push_str("    rankdir=LR;\n");
-        dot.push_str("    node [shape=circle, style=filled, fillcolor=lightblue];\n");
-        dot.push_str("    edge [fontsize=10];\n\n");
+        write_function_block(&mut dot, fb, stats.and_then(|s| s.get(&fb.name)), options);
+    }
+
+    dot
+}
+
+/// One digraph holding each block as a cluster, node ids prefixed with the
+/// block name since state ids repeat across blocks
+fn render_single_graph(
+    fsm: &FiniteStateMachine,
+    stats: Option<&HashMap<String, FsmStatistics>>,
+    options: &OutputOptions,
+) -> String {
+    let mut dot = format!("digraph \"{}\" {{\n", escape_label(&fsm.metadata.source_file.display().to_string()));
+    write_graph_attributes(&mut dot, options);
+
+    for (idx, fb) in fsm.function_blocks.iter().enumerate() {
+        let mut body = String::new();
+        write_block_body(&mut body, fb, stats.and_then(|s| s.get(&fb.name)), options, &block_namespace(&fb.name));
+
+        dot.push_str(&format!("\n    subgraph cluster_{} {{\n", idx));
+        dot.push_str(&format!("        label=\"{}\";\n", escape_label(&fb.name)));
+        for line in body.lines() {
+            if !line.is_empty() {
+                dot.push_str("    ");
+            }
+            dot.push_str(line);
+            dot.push('\n');
+        }
+        dot.push_str("    }\n");
+    }
+
+    if stats.is_some() && options.legend {
+        write_legend(&mut dot);
+    }
+
+    dot.push('}');
+    dot
+}
+
+/// `<block>_` with anything but ASCII alphanumerics replaced, so it can also
+/// prefix cluster names
+fn block_namespace(block: &str) -> String {
+    let mut ns: String = block.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    ns.push('_');
+    ns
+}
 
-        // Add nodes
-        for state in fb.states.keys() {
-            dot.push_str(&format!("    \"{}\" [label=\"{}\"];\n", state, state));
+/// Standalone digraph for a single function block
+pub(crate) fn render_function_block(fb: &FunctionBlock, options: &OutputOptions) -> String {
+    let mut dot = String::new();
+    write_function_block(&mut dot, fb, None, options);
+    dot
+}
+
+fn write_function_block(
+    dot: &mut String,
+    fb: &FunctionBlock,
+    stat: Option<&FsmStatistics>,
+    options: &OutputOptions,
+) {
+    dot.push_str(&format!("digraph \"{}\" {{\n", fb.name));
+    write_graph_attributes(dot, options);
+    write_block_body(dot, fb, stat, options, "");
+
+    if stat.is_some() && options.legend {
+        write_legend(dot);
+    }
+
+    dot.push('}');
+}
+
+fn write_graph_attributes(dot: &mut String, options: &OutputOptions) {
+    match options.layout {
+        DotLayout::Dot => dot.push_str("    rankdir=LR;\n"),
+        DotLayout::Neato | DotLayout::Fdp => {
+            dot.push_str(&format!("    layout={};\n", options.layout.engine()));
+            dot.push_str("    overlap=false;\n");
+            dot.push_str("    splines=true;\n");
         }
+        DotLayout::Circo => dot.push_str("    layout=circo;\n"),
+    }
+    dot.push_str("    node [shape=circle, style=filled, fillcolor=lightblue];\n");
+    dot.push_str("    edge [fontsize=10];\n");
+}
 
-        dot.push_str("\n");
+/// Nodes and edges of `fb`, each node id prefixed with `ns`
+fn write_block_body(
+    dot: &mut String,
+    fb: &FunctionBlock,
+    stat: Option<&FsmStatistics>,
+    options: &OutputOptions,
+    ns: &str,
+) {
+    let compact;
+    let fb = if options.compact_states {
+        compact = fb.diagram_view(true);
+        &compact
+    } else {
+        fb
+    };
 
-        // Add edges
-        for transition in &fb.transitions {
-            let label = transition.condition
-                .replace('\"', "\\\"")
-                .replace('\n', "\\n");
+    dot.push('\n');
+    let mut isolated = Vec::new();
+    let initial_states = FsmValidator::initial_states(fb);
 
+    // Add nodes
+    for (state, info) in &fb.states {
+        let Some(stat) = stat else {
+            dot.push_str(&format!("    \"{}{}\" [label=\"{}\"];\n", ns, state, state));
+            continue;
+        };
+
+        if info.transitions_in.is_empty() && info.transitions_out.is_empty() {
+            isolated.push(state);
+            continue;
+        }
+
+        let fill = if stat.unreachable_states.contains(state) {
+            ", fillcolor=red"
+        } else if initial_states.contains(state) {
+            ", fillcolor=green"
+        } else if stat.dead_states.contains(state) {
+            ", fillcolor=grey"
+        } else if stat.cycle_membership.contains_key(state) {
+            ", fillcolor=orange"
+        } else {
+            ""
+        };
+        dot.push_str(&format!("    \"{}{}\" [label=\"{}\"{}];\n", ns, state, state, fill));
+    }
+
+    // States equally far from the start line up in the hierarchical layout
+    if let Some(stat) = stat
+        && options.layout == DotLayout::Dot {
+        let mut ranks: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+        for state in fb.states.keys().filter(|s| !isolated.contains(s)) {
+            if let Some(&depth) = stat.state_depths.get(state) {
+                ranks.entry(depth).or_default().push(state);
+            }
+        }
+        for states in ranks.values().filter(|states| states.len() > 1) {
+            let nodes: Vec<String> = states.iter().map(|state| format!("\"{}{}\";", ns, state)).collect();
+            dot.push_str(&format!("    {{ rank=same; {} }}\n", nodes.join(" ")));
+        }
+    }
+
+    if !isolated.is_empty() {
+        dot.push_str(&format!("\n    subgraph cluster_{}isolated {{\n", ns));
+        dot.push_str("        label=\"Isolated/Unreachable\";\n");
+        dot.push_str("        style=dashed;\n");
+        dot.push_str("        rank=sink;\n");
+        for state in isolated {
+            dot.push_str(&format!("        \"{}{}\" [label=\"{}\", fillcolor=red];\n", ns, state, state));
+        }
+        dot.push_str("    }\n");
+    }
+
+    if fb.init_transition.is_some() {
+        dot.push_str(&format!("    \"{}{}\" [shape=point, label=\"\"];\n", ns, INIT_STATE));
+    }
+
+    dot.push('\n');
+
+    // Add edges
+    if let Some(init) = &fb.init_transition {
+        dot.push_str(&format!(
+            "    \"{}{}\" -> \"{}{}\" [label={}, style=dashed];\n",
+            ns,
+            INIT_STATE,
+            ns,
+            init.to_state,
+            edge_label(init, options)
+        ));
+    }
+
+    for (idx, transition) in fb.transitions.iter().enumerate() {
+        if let Some(target) = transition.computed_target_label() {
+            // The runtime target is no state, so the edge ends in a bare point
+            let end = format!("{}{}__computed_{}", ns, transition.from_state, idx);
+            dot.push_str(&format!("    \"{}\" [shape=point, label=\"\"];\n", end));
+            dot.push_str(&format!(
+                "    \"{}{}\" -> \"{}\" [label=\"{}\", style=dashed];\n",
+                ns,
+                transition.from_state,
+                end,
+                escape_label(&format!("{}\n{}", guard_label(transition, options), target))
+            ));
+            continue;
+        }
+
+        if let Some(via) = transition.via_label() {
             dot.push_str(&format!(
-                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                "    \"{}{}\" -> \"{}{}\" [label=\"{}\", style=dashed];\n",
+                ns,
                 transition.from_state,
+                ns,
                 transition.to_state,
-                label
+                via
             ));
+            continue;
         }
 
-        dot.push_str("}");
+        let label = edge_label(transition, options);
+        let style = if transition.is_external_force { ", color=red, style=dotted" } else { "" };
+
+        dot.push_str(&format!(
+            "    \"{}{}\" -> \"{}{}\" [label={}{}];\n",
+            ns,
+            transition.from_state,
+            ns,
+            transition.to_state,
+            label,
+            style
+        ));
     }
 
+    write_sub_machines(dot, fb, ns, options);
+}
+
+/// Disconnected cluster with a sample node per state color
+fn write_legend(dot: &mut String) {
+    dot.push_str("\n    subgraph cluster_legend {\n");
+    dot.push_str("        label=\"Legend\";\n");
+    dot.push_str("        style=dashed;\n");
+    dot.push_str("        node [shape=box];\n");
+    for (name, color) in [("Initial", "green"), ("Dead", "grey"), ("Unreachable", "red"), ("Cycle", "orange")] {
+        dot.push_str(&format!("        \"legend_{}\" [label=\"{}\", fillcolor={}];\n", name.to_lowercase(), name, color));
+    }
+    dot.push_str("    }\n");
+}
+
+/// One cluster per nested CASE, its states prefixed with the parent state
+/// (`20.10`) and hung off that state by a dashed edge
+fn write_sub_machines(dot: &mut String, fb: &FunctionBlock, prefix: &str, options: &OutputOptions) {
+    for sub in &fb.sub_machines {
+        let Some(parent) = &sub.parent_state else {
+            continue;
+        };
+        let parent_id = format!("{}{}", prefix, parent);
+
+        dot.push_str(&format!("\n    subgraph \"cluster_{}\" {{\n", parent_id));
+        dot.push_str(&format!("        label=\"{} ({})\";\n", parent_id, sub.case_variable));
+        dot.push_str("        style=rounded;\n");
+        for state in sub.states.keys() {
+            dot.push_str(&format!("        \"{}.{}\" [label=\"{}\"];\n", parent_id, state, state));
+        }
+        for transition in &sub.transitions {
+            dot.push_str(&format!(
+                "        \"{}.{}\" -> \"{}.{}\" [label={}];\n",
+                parent_id,
+                transition.from_state,
+                parent_id,
+                transition.to_state,
+                edge_label(transition, options)
+            ));
+        }
+        dot.push_str("    }\n");
+
+        let entry = sub.init_transition
+            .as_ref()
+            .map(|t| &t.to_state)
+            .or_else(|| sub.states.keys().next());
+        if let Some(entry) = entry {
+            dot.push_str(&format!("    \"{}\" -> \"{}.{}\" [style=dashed, arrowhead=none];\n", parent_id, parent_id, entry));
+        }
+
+        write_sub_machines(dot, sub, &format!("{}.", parent_id), options);
+    }
+}
+
+/// Quoted plain label, or an HTML-like table with one row per conjunct and
+/// OR-branches separated by rules when `rich_labels` is set
+fn edge_label(transition: &Transition, options: &OutputOptions) -> String {
+    let plain = guard_label(transition, options);
+    let Some(condition) = transition.condition.as_deref().filter(|_| options.rich_labels) else {
+        return format!("\"{}\"", escape_label(&plain));
+    };
+
+    let branches: Vec<String> = SignatureGenerator::parse_transition_condition(condition)
+        .iter()
+        .filter(|conjunction| !conjunction.is_empty())
+        .map(|conjunction| {
+            conjunction
+                .iter()
+                .map(|cond| format!("<TR><TD>{}</TD></TR>", escape_html(&condition_text(cond, options))))
+                .collect::<String>()
+        })
+        .collect();
+
+    if branches.is_empty() {
+        return format!("\"{}\"", escape_label(&plain));
+    }
+
+    format!(
+        "<<TABLE BORDER=\"0\" CELLBORDER=\"0\" CELLSPACING=\"0\">{}</TABLE>>",
+        branches.join("<HR/>")
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_label(label: &str) -> String {
+    label
+        .replace('\"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_output(dot: &str, output_path: Option<&Path>) -> Result<()> {
     if let Some(path) = output_path {
         let mut file = File::create(path)?;
         file.write_all(dot.as_bytes())?;
@@ -49,4 +391,234 @@ pub fn export_graphviz(fsm: &FiniteStateMachine, output_path: Option<&Path>) ->
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_orphan_state_in_isolated_cluster() {
+        let mut fb = FunctionBlock::new("OrphanFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("99".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "go = TRUE".to_string()));
+
+        let mut stats = HashMap::new();
+        stats.insert(fb.name.clone(), FsmStatistics::analyze(&fb));
+
+        let fsm = machine("orphan.xml", vec![fb]);
+
+        let dot = render_graphviz(&fsm, Some(&stats), &OutputOptions::default());
+        let cluster_start = dot.find("subgraph cluster_isolated {").unwrap();
+        let cluster = &dot[cluster_start..cluster_start + dot[cluster_start..].find('}').unwrap()];
+
+        assert!(cluster.contains("label=\"Isolated/Unreachable\""));
+        assert!(cluster.contains("rank=sink"));
+        assert!(cluster.contains("\"99\""));
+        assert!(!cluster.contains("\"10\""));
+        assert!(!cluster.contains("\"20\""));
+    }
+
+    #[test]
+    fn test_states_colored_by_role() {
+        let mut fb = FunctionBlock::new("RoleFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "50", "60"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "done = TRUE".to_string()));
+        fb.add_transition(Transition::new("50".to_string(), "60".to_string(), "a = TRUE".to_string()));
+        fb.add_transition(Transition::new("60".to_string(), "50".to_string(), "b = TRUE".to_string()));
+
+        let mut stats = HashMap::new();
+        stats.insert(fb.name.clone(), FsmStatistics::analyze(&fb));
+        let fsm = machine("role.xml", vec![fb]);
+
+        let dot = render_graphviz(&fsm, Some(&stats), &OutputOptions::default());
+        assert!(dot.contains("\"10\" [label=\"10\", fillcolor=green];"));
+        assert!(dot.contains("\"20\" [label=\"20\"];"));
+        assert!(dot.contains("\"30\" [label=\"30\", fillcolor=grey];"));
+        assert!(dot.contains("\"50\" [label=\"50\", fillcolor=red];"));
+        assert!(dot.contains("subgraph cluster_legend {"));
+        for entry in ["Initial", "Dead", "Unreachable", "Cycle"] {
+            assert!(dot.contains(&format!("[label=\"{}\", fillcolor=", entry)), "missing legend entry {}", entry);
+        }
+
+        let no_legend = OutputOptions { legend: false, ..OutputOptions::default() };
+        assert!(!render_graphviz(&fsm, Some(&stats), &no_legend).contains("cluster_legend"));
+
+        let plain = render_graphviz(&fsm, None, &OutputOptions::default());
+        assert!(plain.contains("fillcolor=lightblue"));
+        assert!(!plain.contains("fillcolor=green"));
+        assert!(!plain.contains("cluster_legend"));
+    }
+
+    #[test]
+    fn test_single_graph_namespaces_blocks() {
+        let mut function_blocks = Vec::new();
+        for name in ["PumpFB", "ValveFB"] {
+            let mut fb = FunctionBlock::new(name.to_string(), "state".to_string());
+            fb.add_state(State::new("10".to_string()));
+            fb.add_state(State::new("20".to_string()));
+            fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "go = TRUE".to_string()));
+            function_blocks.push(fb);
+        }
+        let fsm = machine("plant.xml", function_blocks);
+
+        let options = OutputOptions { single_graph: true, ..OutputOptions::default() };
+        let dot = render_graphviz(&fsm, None, &options);
+
+        assert_eq!(dot.matches("digraph").count(), 1);
+        assert!(dot.contains("subgraph cluster_0 {\n        label=\"PumpFB\";"));
+        assert!(dot.contains("subgraph cluster_1 {\n        label=\"ValveFB\";"));
+        assert!(dot.contains("\"PumpFB_10\" [label=\"10\"];"));
+        assert!(dot.contains("\"ValveFB_10\" [label=\"10\"];"));
+        assert!(dot.contains("\"PumpFB_10\" -> \"PumpFB_20\""));
+        assert!(!dot.contains("\"10\" ["));
+    }
+
+    #[test]
+    fn test_unconditional_transition_label() {
+        let mut fb = FunctionBlock::new("TimerFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::unconditional("10".to_string(), "20".to_string()));
+
+        let json = serde_json::to_value(&fb.transitions[0]).unwrap();
+        assert!(json["condition"].is_null());
+
+        let fsm = machine("timer.xml", vec![fb]);
+        let options = OutputOptions {
+            unconditional_label: "always".to_string(),
+            ..OutputOptions::default()
+        };
+
+        let dot = render_graphviz(&fsm, None, &options);
+        assert!(dot.contains("\"10\" -> \"20\" [label=\"always\"];"));
+    }
+
+    #[test]
+    fn test_rich_labels_table_rows() {
+        let mut fb = FunctionBlock::new("MixFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "a = 1 AND b = 2".to_string()));
+
+        let options = OutputOptions {
+            rich_labels: true,
+            ..OutputOptions::default()
+        };
+
+        let mut dot = String::new();
+        write_function_block(&mut dot, &fb, None, &options);
+
+        assert!(dot.contains("[label=<<TABLE"));
+        assert!(dot.contains("<TR><TD>a = 1</TD></TR><TR><TD>b = 2</TD></TR>"));
+        assert!(!dot.contains("<HR/>"));
+    }
+
+    #[test]
+    fn test_summary_graph_call_edge() {
+        let xml = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>A</derived-function-block-name>
+<fb-name-decl><fb-name>pump</fb-name><derived-function-block-name>B</derived-function-block-name></fb-name-decl>
+<function-block-invocation><fb-name>pump</fb-name></function-block-invocation>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>go</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+<function-block-declaration>
+<derived-function-block-name>B</derived-function-block-name>
+<case-statement>
+<expression><variable-name>step</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>0</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>run</variable-name></expression>
+<assignment-statement><variable-name>step</variable-name><expression><integer-literal>1</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+        let parser = crate::xml_parser::XmlParser::from_content(xml, &Default::default()).unwrap();
+        let calls = parser.find_block_calls();
+        assert_eq!(calls, vec![BlockCall { caller: "A".to_string(), callee: "B".to_string() }]);
+
+        let blocks = ["A", "B"].map(|name| FunctionBlock::new(name.to_string(), "state".to_string()));
+        let fsm = machine("calls.xml", blocks.into());
+
+        let dot = render_summary_graph(&fsm, &calls);
+        assert!(dot.contains("\"A\" -> \"B\";"));
+        assert!(!dot.contains("\"B\" -> \"A\""));
+    }
+
+    #[test]
+    fn test_neato_layout_attributes() {
+        let mut fb = FunctionBlock::new("BigFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "go = TRUE".to_string()));
+
+        let options = OutputOptions {
+            layout: DotLayout::Neato,
+            ..OutputOptions::default()
+        };
+
+        let mut dot = String::new();
+        write_function_block(&mut dot, &fb, None, &options);
+
+        assert!(!dot.contains("rankdir=LR"));
+        assert!(dot.contains("layout=neato;"));
+        assert!(dot.contains("overlap=false;"));
+        assert!(dot.contains("splines=true;"));
+    }
+
+    #[test]
+    fn test_ranks_follow_init_target() {
+        let mut fb = FunctionBlock::new("LoopFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "ready = TRUE".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "done = TRUE".to_string()));
+        fb.init_transition = Some(Transition::unconditional(INIT_STATE.to_string(), "20".to_string()));
+
+        let stat = FsmStatistics::analyze(&fb);
+        let mut dot = String::new();
+        write_function_block(&mut dot, &fb, Some(&stat), &OutputOptions::default());
+
+        assert!(!dot.contains("rank=same"));
+        assert!(dot.contains("\"20\" [label=\"20\", fillcolor=green"));
+        assert!(!dot.contains("\"10\" [label=\"10\", fillcolor=green"));
+    }
+
+    #[test]
+    fn test_computed_target_is_edge_annotation() {
+        let mut fb = FunctionBlock::new("SeqFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        let mut computed = Transition::new("10".to_string(), crate::fsm::COMPUTED_STATE.to_string(), "start = TRUE".to_string());
+        computed.computed_target = true;
+        computed.target_expression = Some("base + 10".to_string());
+        fb.add_transition(computed);
+
+        let dot = render_function_block(&fb, &OutputOptions::default());
+        assert!(dot.contains("\"10__computed_0\" [shape=point, label=\"\"];"));
+        assert!(dot.contains("\"10\" -> \"10__computed_0\" [label=\"start = TRUE\\n→ base + 10\", style=dashed];"));
+        assert!(!dot.contains("<computed>"));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::analysis::FsmValidator;
+use crate::fsm::{FiniteStateMachine, FunctionBlock, Transition, INIT_STATE};
+use crate::output::{escape_xml, guard_label, OutputOptions};
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+const LAYER_SPACING: usize = 180;
+const ROW_SPACING: usize = 120;
+const STATE_SIZE: usize = 60;
+const INIT_SIZE: usize = 10;
+
+/// diagrams.net (mxGraph) file with one page per function block
+pub fn export_drawio(fsm: &FiniteStateMachine, options: &OutputOptions, output_path: Option<&Path>) -> Result<()> {
+    let xml = render_drawio(fsm, options);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, xml)?;
+    } else {
+        println!("{}", xml);
+    }
+
+    Ok(())
+}
+
+fn render_drawio(fsm: &FiniteStateMachine, options: &OutputOptions) -> String {
+    let mut xml = String::from("<mxfile host=\"plc-fsm-analyzer\">\n");
+
+    for (idx, fb) in fsm.function_blocks.iter().enumerate() {
+        write_page(&mut xml, idx, fb, options);
+    }
+
+    xml.push_str("</mxfile>\n");
+    xml
+}
+
+fn write_page(xml: &mut String, page: usize, fb: &FunctionBlock, options: &OutputOptions) {
+    let compact;
+    let fb = if options.compact_states {
+        compact = fb.diagram_view(true);
+        &compact
+    } else {
+        fb
+    };
+
+    xml.push_str(&format!("  <diagram id=\"page-{}\" name=\"{}\">\n", page, escape_xml(&fb.name)));
+    xml.push_str("    <mxGraphModel>\n      <root>\n");
+    xml.push_str("        <mxCell id=\"0\"/>\n");
+    xml.push_str("        <mxCell id=\"1\" parent=\"0\"/>\n");
+
+    let ids: HashMap<&str, String> = fb.states
+        .keys()
+        .enumerate()
+        .map(|(idx, state)| (state.as_str(), format!("s{}", idx)))
+        .collect();
+
+    let positions = layered_positions(fb);
+    for &(state, (x, y)) in &positions {
+        xml.push_str(&format!(
+            "        <mxCell id=\"{}\" value=\"{}\" style=\"ellipse;whiteSpace=wrap;html=1;\" vertex=\"1\" parent=\"1\">\n",
+            ids[state],
+            escape_xml(state)
+        ));
+        xml.push_str(&format!(
+            "          <mxGeometry x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" as=\"geometry\"/>\n",
+            x, y, STATE_SIZE, STATE_SIZE
+        ));
+        xml.push_str("        </mxCell>\n");
+    }
+
+    if let Some(init) = &fb.init_transition
+        && let Some(target) = ids.get(init.to_state.as_str())
+        && let Some(&(_, (x, y))) = positions.iter().find(|(state, _)| *state == init.to_state) {
+        // Just left of the initial state, inside the margin before its column
+        xml.push_str(&format!(
+            "        <mxCell id=\"{}\" value=\"\" style=\"ellipse;fillColor=#000000;\" vertex=\"1\" parent=\"1\">\n",
+            INIT_STATE
+        ));
+        xml.push_str(&format!(
+            "          <mxGeometry x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" as=\"geometry\"/>\n",
+            x - 3 * INIT_SIZE,
+            y + (STATE_SIZE - INIT_SIZE) / 2,
+            INIT_SIZE,
+            INIT_SIZE
+        ));
+        xml.push_str("        </mxCell>\n");
+        write_edge(xml, "init", INIT_STATE, target, init, "dashed=1;", options);
+    }
+
+    for (idx, transition) in fb.transitions.iter().enumerate() {
+        if let Some(label) = transition.computed_target_label()
+            && let Some(&(_, (x, y))) = positions.iter().find(|(state, _)| *state == transition.from_state) {
+            // The runtime target is no state: the edge ends beside its source
+            xml.push_str(&format!(
+                "        <mxCell id=\"t{}\" value=\"{}\" style=\"rounded=1;html=1;dashed=1;\" edge=\"1\" parent=\"1\" source=\"{}\">\n",
+                idx,
+                escape_xml(&format!("{} {}", guard_label(transition, options), label)),
+                ids[transition.from_state.as_str()]
+            ));
+            xml.push_str("          <mxGeometry relative=\"1\" as=\"geometry\">\n");
+            xml.push_str(&format!(
+                "            <mxPoint x=\"{}\" y=\"{}\" as=\"targetPoint\"/>\n",
+                x + STATE_SIZE + LAYER_SPACING / 3,
+                y + STATE_SIZE / 2
+            ));
+            xml.push_str("          </mxGeometry>\n");
+            xml.push_str("        </mxCell>\n");
+            continue;
+        }
+
+        let (Some(source), Some(target)) = (
+            ids.get(transition.from_state.as_str()),
+            ids.get(transition.to_state.as_str()),
+        ) else {
+            continue;
+        };
+
+        let style = if transition.is_external_force {
+            "strokeColor=#FF0000;dashed=1;"
+        } else if !transition.via_states.is_empty() {
+            "dashed=1;"
+        } else {
+            ""
+        };
+        write_edge(xml, &format!("t{}", idx), source, target, transition, style, options);
+    }
+
+    xml.push_str("      </root>\n    </mxGraphModel>\n  </diagram>\n");
+}
+
+fn write_edge(
+    xml: &mut String,
+    id: &str,
+    source: &str,
+    target: &str,
+    transition: &Transition,
+    style: &str,
+    options: &OutputOptions,
+) {
+    xml.push_str(&format!(
+        "        <mxCell id=\"{}\" value=\"{}\" style=\"edgeStyle=orthogonalEdgeStyle;rounded=1;html=1;{}\" edge=\"1\" parent=\"1\" source=\"{}\" target=\"{}\">\n",
+        id,
+        escape_xml(&transition.via_label().unwrap_or_else(|| guard_label(transition, options))),
+        style,
+        source,
+        target
+    ));
+    xml.push_str("          <mxGeometry relative=\"1\" as=\"geometry\"/>\n");
+    xml.push_str("        </mxCell>\n");
+}
+
+/// Columns by BFS depth from the initial states, rows in state order;
+/// states the search never reaches go in a final column
+fn layered_positions(fb: &FunctionBlock) -> Vec<(&str, (usize, usize))> {
+    let mut layer: HashMap<&str, usize> = HashMap::new();
+    let initial_states = FsmValidator::initial_states(fb);
+    let mut queue: VecDeque<&str> = initial_states.iter().map(String::as_str).collect();
+    for &state in &queue {
+        layer.insert(state, 0);
+    }
+
+    while let Some(state) = queue.pop_front() {
+        let depth = layer[state];
+        for transition in fb.transitions.iter().filter(|t| t.from_state == state) {
+            if fb.states.contains_key(&transition.to_state) && !layer.contains_key(transition.to_state.as_str()) {
+                layer.insert(transition.to_state.as_str(), depth + 1);
+                queue.push_back(transition.to_state.as_str());
+            }
+        }
+    }
+
+    let last_layer = layer.values().max().map_or(0, |max| max + 1);
+    let mut rows: HashMap<usize, usize> = HashMap::new();
+
+    fb.states
+        .keys()
+        .map(|state| {
+            let column = layer.get(state.as_str()).copied().unwrap_or(last_layer);
+            let row = rows.entry(column).or_default();
+            let position = (40 + column * LAYER_SPACING, 40 + *row * ROW_SPACING);
+            *row += 1;
+            (state.as_str(), position)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::State;
+
+    #[test]
+    fn test_drawio_is_well_formed_with_vertex_per_state() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("30".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "level < 5 AND run = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 90".to_string()));
+
+        let fsm = machine("pump.xml", vec![fb]);
+
+        let xml = render_drawio(&fsm, &OutputOptions::default());
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+
+        let vertices: Vec<_> = doc.descendants()
+            .filter(|n| n.has_tag_name("mxCell") && n.attribute("vertex") == Some("1"))
+            .collect();
+        assert_eq!(vertices.len(), 3);
+
+        let edge = doc.descendants()
+            .find(|n| n.has_tag_name("mxCell") && n.attribute("edge") == Some("1"))
+            .unwrap();
+        assert_eq!(edge.attribute("value"), Some("level < 5 AND run = TRUE"));
+
+        let page = doc.descendants().find(|n| n.has_tag_name("diagram")).unwrap();
+        assert_eq!(page.attribute("name"), Some("PumpFB"));
+    }
+
+    #[test]
+    fn test_init_marker_is_a_placed_vertex() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.init_transition = Some(Transition::unconditional(INIT_STATE.to_string(), "10".to_string()));
+
+        let fsm = machine("pump.xml", vec![fb]);
+
+        let xml = render_drawio(&fsm, &OutputOptions::default());
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+
+        let marker = doc.descendants()
+            .find(|n| n.has_tag_name("mxCell") && n.attribute("id") == Some(INIT_STATE))
+            .unwrap();
+        assert_eq!(marker.attribute("vertex"), Some("1"));
+
+        let geometry = marker.first_element_child().unwrap();
+        assert_eq!(geometry.attribute("x"), Some("10"));
+        assert_eq!(geometry.attribute("y"), Some("65"));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use crate::output::{escape_xml, NodeIds};
+use anyhow::Result;
+use std::path::Path;
+
+const GRAPHML_NAMESPACE: &str = "http://graphml.graphdrawing.org/xmlns";
+
+/// GraphML document with one directed `<graph>` per function block, for
+/// yEd, Gephi and other graph tools
+pub fn export_graphml(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
+    let graphml = render_graphml(fsm);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, graphml)?;
+    } else {
+        print!("{}", graphml);
+    }
+
+    Ok(())
+}
+
+fn render_graphml(fsm: &FiniteStateMachine) -> String {
+    let mut graphml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    graphml.push_str(&format!("<graphml xmlns=\"{}\">\n", GRAPHML_NAMESPACE));
+    graphml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"guard\" for=\"edge\" attr.name=\"guard\" attr.type=\"string\"/>\n");
+    graphml.push_str("  <key id=\"computed\" for=\"node\" attr.name=\"computed_targets\" attr.type=\"string\"/>\n");
+
+    let ids = NodeIds::new(fsm);
+    for fb in &fsm.function_blocks {
+        write_graph(&mut graphml, fb, &ids);
+    }
+
+    graphml.push_str("</graphml>\n");
+    graphml
+}
+
+fn write_graph(graphml: &mut String, fb: &FunctionBlock, ids: &NodeIds) {
+    graphml.push_str(&format!("  <graph id=\"{}\" edgedefault=\"directed\">\n", ids.block(&fb.name)));
+
+    // Ids must be unique across the document, so they are prefixed with the block
+    for state_id in fb.states.keys() {
+        graphml.push_str(&format!("    <node id=\"{}\">\n", ids.state(&fb.name, state_id)));
+        graphml.push_str(&format!("      <data key=\"label\">{}</data>\n", escape_xml(state_id)));
+        // Runtime targets have no node to point an edge at
+        let computed: Vec<String> = fb.transitions
+            .iter()
+            .filter(|t| &t.from_state == state_id)
+            .filter_map(|t| Some(format!("{} {}", t.condition_label("TRUE"), t.computed_target_label()?)))
+            .collect();
+        if !computed.is_empty() {
+            graphml.push_str(&format!("      <data key=\"computed\">{}</data>\n", escape_xml(&computed.join("; "))));
+        }
+        graphml.push_str("    </node>\n");
+    }
+
+    for (idx, transition) in fb.transitions.iter().enumerate().filter(|(_, t)| !t.computed_target) {
+        graphml.push_str(&format!(
+            "    <edge id=\"{}::e{}\" source=\"{}\" target=\"{}\">\n",
+            ids.block(&fb.name),
+            idx,
+            ids.state(&fb.name, &transition.from_state),
+            ids.state(&fb.name, &transition.to_state)
+        ));
+        if let Some(guard) = transition.condition.as_deref().filter(|g| !g.trim().is_empty()) {
+            graphml.push_str(&format!("      <data key=\"guard\">{}</data>\n", escape_xml(guard)));
+        }
+        graphml.push_str("    </edge>\n");
+    }
+
+    graphml.push_str("  </graph>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_graphml_graph_per_block_and_edge_per_transition() {
+        let mut pump = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            pump.add_state(State::new(id.to_string()));
+        }
+        pump.add_transition(Transition::new("10".to_string(), "20".to_string(), "level < 5".to_string()));
+        pump.add_transition(Transition::new("20".to_string(), "30".to_string(), "".to_string()));
+
+        let mut valve = FunctionBlock::new("ValveFB".to_string(), "step".to_string());
+        for id in ["10", "20"] {
+            valve.add_state(State::new(id.to_string()));
+        }
+        valve.add_transition(Transition::new("10".to_string(), "20".to_string(), "open = TRUE".to_string()));
+
+        let fsm = machine("plant.xml", vec![pump, valve]);
+
+        let graphml = render_graphml(&fsm);
+        let doc = roxmltree::Document::parse(&graphml).unwrap();
+
+        let graphs: Vec<_> = doc.descendants().filter(|n| n.has_tag_name("graph")).collect();
+        assert_eq!(graphs.iter().map(|g| g.attribute("id").unwrap()).collect::<Vec<_>>(), ["PumpFB", "ValveFB"]);
+
+        let edges: Vec<_> = doc.descendants().filter(|n| n.has_tag_name("edge")).collect();
+        assert_eq!(edges.len(), 3);
+        assert_eq!(edges[0].first_element_child().and_then(|d| d.text()), Some("level < 5"));
+        assert!(edges[1].first_element_child().is_none());
+
+        let mut node_ids: Vec<_> = doc.descendants().filter(|n| n.has_tag_name("node")).map(|n| n.attribute("id").unwrap()).collect();
+        node_ids.sort();
+        node_ids.dedup();
+        assert_eq!(node_ids.len(), 5);
+    }
+}
@@ -7,8 +7,32 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+/// The FSM as JSON with each transition carrying its `GuardMetrics`. The
+/// extra keys are ignored when the JSON is read back into a `FiniteStateMachine`.
+fn enriched_fsm(fsm: &FiniteStateMachine) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(fsm)?;
+
+    let blocks = value["function_blocks"].as_array_mut().into_iter().flatten();
+    for (fb, fb_value) in fsm.function_blocks.iter().zip(blocks) {
+        let transitions = fb_value["transitions"].as_array_mut().into_iter().flatten();
+        for (transition, transition_value) in fb.transitions.iter().zip(transitions) {
+            if let (Some(object), serde_json::Value::Object(metrics)) =
+                (transition_value.as_object_mut(), serde_json::to_value(transition.guard_metrics())?) {
+                object.extend(metrics);
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+/// Read back a machine written by `export_json`
+pub fn load_json(path: &Path) -> Result<FiniteStateMachine> {
+    FiniteStateMachine::from_json_file(path)
+}
+
 pub fn export_json(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
-    let json = serde_json::to_string_pretty(fsm)?;
+    let json = serde_json::to_string_pretty(&enriched_fsm(fsm)?)?;
 
     if let Some(path) = output_path {
         let mut file = File::create(path)?;
@@ -27,11 +51,11 @@ pub fn export_with_analysis(
 ) -> Result<()> {
     #[derive(serde::Serialize)]
     struct FsmWithAnalysis<'a> {
-        fsm: &'a FiniteStateMachine,
+        fsm: serde_json::Value,
         analysis: &'a HashMap<String, FsmStatistics>,
     }
 
-    let data = FsmWithAnalysis { fsm, analysis: stats };
+    let data = FsmWithAnalysis { fsm: enriched_fsm(fsm)?, analysis: stats };
     let json = serde_json::to_string_pretty(&data)?;
 
     if let Some(path) = output_path {
@@ -52,11 +76,11 @@ pub fn export_with_signatures(
 ) -> Result<()> {
     #[derive(serde::Serialize)]
     struct FsmWithSignatures<'a> {
-        fsm: &'a FiniteStateMachine,
+        fsm: serde_json::Value,
         signatures: &'a HashMap<String, StateSignatureTable>,
     }
 
-    let data = FsmWithSignatures { fsm, signatures };
+    let data = FsmWithSignatures { fsm: enriched_fsm(fsm)?, signatures };
     let json = serde_json::to_string_pretty(&data)?;
 
     if let Some(path) = output_path {
@@ -78,12 +102,12 @@ pub fn export_with_full_analysis(
 ) -> Result<()> {
     #[derive(serde::Serialize)]
     struct FsmWithFullAnalysis<'a> {
-        fsm: &'a FiniteStateMachine,
+        fsm: serde_json::Value,
         analysis: &'a HashMap<String, FsmStatistics>,
         signatures: &'a HashMap<String, StateSignatureTable>,
     }
 
-    let data = FsmWithFullAnalysis { fsm, analysis: stats, signatures };
+    let data = FsmWithFullAnalysis { fsm: enriched_fsm(fsm)?, analysis: stats, signatures };
     let json = serde_json::to_string_pretty(&data)?;
 
     if let Some(path) = output_path {
@@ -94,4 +118,64 @@ pub fn export_with_full_analysis(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    #[test]
+    fn test_transitions_carry_guard_metrics() {
+        let mut fb = FunctionBlock::new("MixFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new(
+            "10".to_string(),
+            "20".to_string(),
+            "(a = 1 OR b = 2) AND a <> 3".to_string(),
+        ));
+        fb.add_transition(Transition::unconditional("20".to_string(), "10".to_string()));
+
+        let fsm = machine("mix.xml", vec![fb]);
+
+        let value = enriched_fsm(&fsm).unwrap();
+        let transitions = &value["function_blocks"][0]["transitions"];
+        assert_eq!(transitions[0]["guard_is_compound"], true);
+        assert_eq!(transitions[0]["guard_variable_count"], 2);
+        assert_eq!(transitions[0]["dnf_term_count"], 2);
+        assert_eq!(transitions[1]["guard_is_compound"], false);
+        assert_eq!(transitions[1]["dnf_term_count"], 0);
+
+        let round_trip: FiniteStateMachine = serde_json::from_value(value).unwrap();
+        assert_eq!(round_trip.function_blocks[0].transitions.len(), 2);
+    }
+
+    #[test]
+    fn test_split_block_loads_standalone() {
+        let mut blocks = Vec::new();
+        for (name, states) in [("PumpFB", ["10", "20", "30"].as_slice()), ("ValveFB", ["0", "1"].as_slice())] {
+            let mut fb = FunctionBlock::new(name.to_string(), "state".to_string());
+            for pair in states.windows(2) {
+                fb.add_state(State::new(pair[0].to_string()));
+                fb.add_state(State::new(pair[1].to_string()));
+                fb.add_transition(Transition::new(pair[0].to_string(), pair[1].to_string(), "go".to_string()));
+            }
+            blocks.push(fb);
+        }
+        let fsm = machine("line.xml", blocks);
+
+        let dir = tempfile::tempdir().unwrap();
+        for single in fsm.split_by_block() {
+            let path = dir.path().join(format!("{}.json", single.function_blocks[0].name));
+            export_json(&single, Some(&path)).unwrap();
+        }
+
+        let valve = load_json(&dir.path().join("ValveFB.json")).unwrap();
+        assert_eq!(valve.function_blocks.len(), 1);
+        assert_eq!(valve.function_blocks[0].name, "ValveFB");
+        assert_eq!((valve.metadata.total_states, valve.metadata.total_transitions), (2, 1));
+        assert_eq!(valve.metadata.source_file, std::path::PathBuf::from("line.xml"));
+    }
+}
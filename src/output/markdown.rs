@@ -1,12 +1,17 @@
 use crate::fsm::FiniteStateMachine;
-use crate::analysis::{FsmStatistics, StateSignatureTable}; // ✅ NEW IMPORT
+use crate::analysis::{DecisionTable, FsmStatistics, StateSignatureTable}; // ✅ NEW IMPORT
+use crate::output::{block_summary, compare_states, dot, guard_text, signature_text, transition_lines, OutputOptions, TransitionLine};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
-pub fn export_markdown(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
+pub fn export_markdown(
+    fsm: &FiniteStateMachine,
+    options: &OutputOptions,
+    output_path: Option<&Path>
+) -> Result<()> {
     let mut md = String::new();
 
     md.push_str("# FSM Extraction Report\n\n");
@@ -16,7 +21,7 @@ pub fn export_markdown(fsm: &FiniteStateMachine, output_path: Option<&Path>) ->
     md.push_str(&format!("**Total Transitions:** {}\n\n", fsm.metadata.total_transitions));
 
     for fb in &fsm.function_blocks {
-        write_function_block(&mut md, fb);
+        write_function_block(&mut md, fb, None, options);
     }
 
     write_output(&md, output_path)?;
@@ -26,6 +31,7 @@ pub fn export_markdown(fsm: &FiniteStateMachine, output_path: Option<&Path>) ->
 pub fn export_with_analysis(
     fsm: &FiniteStateMachine,
     stats: &HashMap<String, FsmStatistics>,
+    options: &OutputOptions,
     output_path: Option<&Path>
 ) -> Result<()> {
     let mut md = String::new();
@@ -35,12 +41,15 @@ pub fn export_with_analysis(
     md.push_str(&format!("**Extraction Date:** {}\n", fsm.metadata.extraction_date));
     md.push_str(&format!("**Total States:** {}\n", fsm.metadata.total_states));
     md.push_str(&format!("**Total Transitions:** {}\n\n", fsm.metadata.total_transitions));
+    if options.legend {
+        write_legend(&mut md);
+    }
 
     for fb in &fsm.function_blocks {
-        write_function_block(&mut md, fb);
+        write_function_block(&mut md, fb, stats.get(&fb.name), options);
 
         if let Some(stat) = stats.get(&fb.name) {
-            write_analysis_section(&mut md, stat);
+            write_analysis_section(&mut md, fb, stat);
         }
     }
 
@@ -52,6 +61,7 @@ pub fn export_with_analysis(
 pub fn export_with_signatures(
     fsm: &FiniteStateMachine,
     signatures: &HashMap<String, StateSignatureTable>,
+    options: &OutputOptions,
     output_path: Option<&Path>
 ) -> Result<()> {
     let mut md = String::new();
@@ -63,10 +73,10 @@ pub fn export_with_signatures(
     md.push_str(&format!("**Total Transitions:** {}\n\n", fsm.metadata.total_transitions));
 
     for fb in &fsm.function_blocks {
-        write_function_block(&mut md, fb);
+        write_function_block(&mut md, fb, None, options);
 
         if let Some(sig_table) = signatures.get(&fb.name) {
-            write_signature_section(&mut md, sig_table);
+            write_signature_section(&mut md, sig_table, options);
         }
     }
 
@@ -79,6 +89,7 @@ pub fn export_with_full_analysis(
     fsm: &FiniteStateMachine,
     stats: &HashMap<String, FsmStatistics>,
     signatures: &HashMap<String, StateSignatureTable>,
+    options: &OutputOptions,
     output_path: Option<&Path>
 ) -> Result<()> {
     let mut md = String::new();
@@ -88,16 +99,19 @@ pub fn export_with_full_analysis(
     md.push_str(&format!("**Extraction Date:** {}\n", fsm.metadata.extraction_date));
     md.push_str(&format!("**Total States:** {}\n", fsm.metadata.total_states));
     md.push_str(&format!("**Total Transitions:** {}\n\n", fsm.metadata.total_transitions));
+    if options.legend {
+        write_legend(&mut md);
+    }
 
     for fb in &fsm.function_blocks {
-        write_function_block(&mut md, fb);
+        write_function_block(&mut md, fb, stats.get(&fb.name), options);
 
         if let Some(stat) = stats.get(&fb.name) {
-            write_analysis_section(&mut md, stat);
+            write_analysis_section(&mut md, fb, stat);
         }
 
         if let Some(sig_table) = signatures.get(&fb.name) {
-            write_signature_section(&mut md, sig_table);
+            write_signature_section(&mut md, sig_table, options);
         }
     }
 
@@ -105,38 +119,141 @@ pub fn export_with_full_analysis(
     Ok(())
 }
 
-fn write_function_block(md: &mut String, fb: &crate::fsm::FunctionBlock) {
+fn write_function_block(
+    md: &mut String,
+    fb: &crate::fsm::FunctionBlock,
+    stat: Option<&FsmStatistics>,
+    options: &OutputOptions,
+) {
     md.push_str(&format!("## Function Block: {}\n\n", fb.name));
+    md.push_str(&format!("**{}**\n\n", block_summary(fb, stat).0));
     md.push_str(&format!("**Case Variable:** `{}`\n\n", fb.case_variable));
+    if let Some(parent) = &fb.parent_state {
+        md.push_str(&format!("**Runs In State:** {}\n\n", parent));
+    }
     md.push_str(&format!("**States:** {} | **Transitions:** {}\n\n",
                          fb.state_count(),
                          fb.transition_count()
     ));
 
+    if let Some(init) = &fb.init_transition {
+        md.push_str(&format!(
+            "**Initialization:** `{}` → {}",
+            guard_text(init, options),
+            init.to_state
+        ));
+        if !init.actions.is_empty() {
+            md.push_str(&format!(" (actions: `{}`)", init.actions.join("; ")));
+        }
+        md.push_str("\n\n");
+    }
+
     if !fb.transitions.is_empty() {
         md.push_str("### State Transitions\n\n");
-        md.push_str("| Current State | Next State | Transition Condition |\n");
-        md.push_str("|---------------|------------|---------------------|\n");
-
-        for transition in &fb.transitions {
-            md.push_str(&format!(
-                "| {} | {} | {} |\n",
-                transition.from_state,
-                transition.to_state,
-                transition.condition.replace('|', "\\|")
-            ));
+        md.push_str("| Current State | Next State | Transition Condition | Priority |\n");
+        md.push_str("|---------------|------------|---------------------|----------|\n");
+
+        for line in transition_lines(&fb.transitions, options) {
+            match line {
+                TransitionLine::Shown(transition) => md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    transition.from_state,
+                    transition.to_state,
+                    guard_text(transition, options).replace('|', "\\|"),
+                    transition.priority
+                )),
+                TransitionLine::Hidden { from_state, count } => {
+                    md.push_str(&format!("| {} | … | … and {} more | … |\n", from_state, count));
+                }
+            }
+        }
+
+        md.push('\n');
+    }
+
+    write_state_outputs(md, fb);
+
+    if options.with_diagram {
+        md.push_str("```dot\n");
+        md.push_str(&dot::render_function_block(fb, options));
+        md.push_str("\n```\n\n");
+    }
+
+    if options.decision_tables {
+        write_decision_tables(md, fb);
+    }
+
+    for sub in &fb.sub_machines {
+        write_function_block(md, sub, None, options);
+    }
+}
+
+fn write_state_outputs(md: &mut String, fb: &crate::fsm::FunctionBlock) {
+    let outputs = fb.state_outputs();
+    if outputs.values().all(Vec::is_empty) {
+        return;
+    }
+
+    md.push_str("### State Outputs\n\n");
+    md.push_str("| State | Outputs |\n");
+    md.push_str("|-------|---------|\n");
+
+    for state_id in fb.states.keys() {
+        let assignments: Vec<String> = outputs[state_id]
+            .iter()
+            .map(|a| format!("`{} := {}`", a.variable, a.value))
+            .collect();
+        md.push_str(&format!("| {} | {} |\n", state_id, assignments.join(", ").replace('|', "\\|")));
+    }
+
+    md.push('\n');
+}
+
+fn write_decision_tables(md: &mut String, fb: &crate::fsm::FunctionBlock) {
+    let tables: Vec<DecisionTable> = fb.states
+        .keys()
+        .map(|state_id| DecisionTable::build(fb, state_id))
+        .filter(|table| !table.is_empty())
+        .collect();
+
+    if tables.is_empty() {
+        return;
+    }
+
+    md.push_str("### Decision Tables\n\n");
+
+    for table in &tables {
+        md.push_str(&format!("#### State {}\n\n", table.state_id));
+
+        let header: Vec<String> = table.variables.iter().map(|v| format!("`{}`", v)).collect();
+        md.push_str(&format!("| {} | Next State |\n", header.join(" | ")));
+        md.push_str(&format!("|{}------------|\n", "---|".repeat(table.variables.len())));
+
+        for row in &table.rows {
+            let cells: Vec<String> = row.cells.iter().map(|c| c.replace('|', "\\|")).collect();
+            md.push_str(&format!("| {} | {} |\n", cells.join(" | "), row.target_state));
         }
 
-        md.push_str("\n");
+        md.push('\n');
     }
 }
 
-fn write_analysis_section(md: &mut String, stat: &FsmStatistics) {
+/// What the analysis lists and diagram colors mean
+fn write_legend(md: &mut String) {
+    md.push_str("**Legend:**\n\n");
+    md.push_str("- **Initial** (green): no incoming transitions, where the block starts\n");
+    md.push_str("- **Dead** (grey): no outgoing transitions, the block stays there\n");
+    md.push_str("- **Unreachable** (red): can't be reached from an initial state\n");
+    md.push_str("- **Cycle** (orange): part of a loop of transitions\n\n");
+}
+
+fn write_analysis_section(md: &mut String, fb: &crate::fsm::FunctionBlock, stat: &FsmStatistics) {
     md.push_str("### Analysis Results\n\n");
     md.push_str(&format!("- **Total States:** {}\n", stat.total_states));
     md.push_str(&format!("- **Total Transitions:** {}\n", stat.total_transitions));
     md.push_str(&format!("- **Avg Transitions/State:** {:.2}\n", stat.avg_transitions_per_state));
     md.push_str(&format!("- **Max Transitions from State:** {}\n", stat.max_transitions_from_state));
+    md.push_str(&format!("- **Deterministic:** {}\n", if stat.is_deterministic { "Yes" } else { "No" }));
 
     if !stat.unreachable_states.is_empty() {
         md.push_str(&format!("- **Unreachable States:** {:?}\n", stat.unreachable_states));
@@ -144,15 +261,31 @@ fn write_analysis_section(md: &mut String, stat: &FsmStatistics) {
     if !stat.dead_states.is_empty() {
         md.push_str(&format!("- **Dead-end States:** {:?}\n", stat.dead_states));
     }
+    if !stat.terminal_states.is_empty() {
+        md.push_str(&format!("- **Terminal States:** {:?}\n", stat.terminal_states));
+    }
     if !stat.cycles.is_empty() {
         md.push_str(&format!("- **Cycles Found:** {}\n", stat.cycles.len()));
     }
 
-    md.push_str("\n");
+    md.push('\n');
+
+    if !stat.cycle_membership.is_empty() {
+        md.push_str("### Cycle Participation\n\n");
+        md.push_str("| State | cycle_member | Cycles |\n");
+        md.push_str("|-------|--------------|--------|\n");
+
+        for state_id in fb.states.keys() {
+            let count = stat.cycle_membership.get(state_id).copied().unwrap_or(0);
+            md.push_str(&format!("| {} | {} | {} |\n", state_id, count > 0, count));
+        }
+
+        md.push('\n');
+    }
 }
 
 // Write signature section
-fn write_signature_section(md: &mut String, sig_table: &StateSignatureTable) {
+fn write_signature_section(md: &mut String, sig_table: &StateSignatureTable, options: &OutputOptions) {
     md.push_str("### State Signatures\n\n");
     md.push_str(&format!("**Case Variable:** `{}`\n\n", sig_table.case_variable));
 
@@ -163,12 +296,58 @@ fn write_signature_section(md: &mut String, sig_table: &StateSignatureTable) {
         md.push_str(&format!(
             "| {} | {} | {} |\n",
             state_id,
-            sig.format_conditions().replace('|', "\\|"),
+            signature_text(sig, options).replace('|', "\\|"),
             sig.paths_count
         ));
     }
 
-    md.push_str("\n");
+    md.push('\n');
+    write_condition_sources(md, sig_table);
+
+    let partial = sig_table.partial_states();
+    if !partial.is_empty() {
+        md.push_str(&format!(
+            "> ⚠ Signatures for cyclic states are partial: {}\n\n",
+            partial.join(", ")
+        ));
+    }
+
+    let limited = sig_table.limited_states();
+    if !limited.is_empty() {
+        md.push_str(&format!(
+            "> ⚠ Path limits truncated signatures of: {}\n\n",
+            limited.join(", ")
+        ));
+    }
+}
+
+/// Footnotes tying each signature condition to the transition it came from.
+/// There is no HTML format; these render as a list wherever the markdown is
+/// turned into HTML.
+fn write_condition_sources(md: &mut String, sig_table: &StateSignatureTable) {
+    let mut states: Vec<_> = sig_table.signatures.iter().collect();
+    states.sort_by(|a, b| compare_states(a.0, b.0));
+
+    let mut lines = Vec::new();
+    for (state_id, sig) in states {
+        let mut seen = Vec::new();
+        for condition in sig.path_signatures.iter().flat_map(|ps| &ps.conditions) {
+            let Some(origin) = &condition.origin_transition_id else {
+                continue;
+            };
+            let line = format!("- State {}: `{}` from transition `{}`\n", state_id, condition, origin);
+            if !seen.contains(&line) {
+                seen.push(line);
+            }
+        }
+        lines.extend(seen);
+    }
+
+    if !lines.is_empty() {
+        md.push_str("**Condition Sources:**\n\n");
+        md.push_str(&lines.concat());
+        md.push('\n');
+    }
 }
 
 fn write_output(content: &str, output_path: Option<&Path>) -> Result<()> {
@@ -179,4 +358,72 @@ fn write_output(content: &str, output_path: Option<&Path>) -> Result<()> {
         println!("{}", content);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    #[test]
+    fn test_diagram_fenced_per_block() {
+        let mut md = String::new();
+        let options = OutputOptions { with_diagram: true, ..OutputOptions::default() };
+
+        for (name, from, to) in [("FB_A", "10", "20"), ("FB_B", "30", "40")] {
+            let mut fb = FunctionBlock::new(name.to_string(), "state".to_string());
+            fb.add_state(State::new(from.to_string()));
+            fb.add_state(State::new(to.to_string()));
+            fb.add_transition(Transition::new(from.to_string(), to.to_string(), "go".to_string()));
+            write_function_block(&mut md, &fb, None, &options);
+        }
+
+        let blocks: Vec<&str> = md.split("```dot\n").skip(1).map(|rest| rest.split("```").next().unwrap()).collect();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].starts_with("digraph \"FB_A\""));
+        assert!(blocks[0].contains("\"10\" -> \"20\""));
+        assert!(blocks[1].starts_with("digraph \"FB_B\""));
+        assert!(blocks[1].contains("\"30\" -> \"40\""));
+        assert!(!blocks[0].contains("FB_B"));
+    }
+
+    #[test]
+    fn test_transitions_limited_per_state() {
+        let mut fb = FunctionBlock::new("SorterFB".to_string(), "state".to_string());
+        for target in ["50", "20", "40", "30", "60"] {
+            fb.add_transition(Transition::new("10".to_string(), target.to_string(), format!("lane = {}", target)));
+        }
+        fb.add_transition(Transition::new("20".to_string(), "10".to_string(), "done".to_string()));
+
+        let mut md = String::new();
+        let options = OutputOptions { max_transitions_per_state: Some(2), ..OutputOptions::default() };
+        write_function_block(&mut md, &fb, None, &options);
+
+        let rows: Vec<&str> = md.lines().filter(|l| l.starts_with("| 10 ")).collect();
+        assert_eq!(rows, ["| 10 | 20 | lane = 20 | 0 |", "| 10 | 30 | lane = 30 | 0 |", "| 10 | … | … and 3 more | … |"]);
+        assert!(md.contains("| 20 | 10 | done | 0 |"));
+        assert_eq!(fb.transition_count(), 6);
+    }
+
+    #[test]
+    fn test_condition_sources_footnotes() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 5".to_string()));
+        let table = crate::analysis::SignatureGenerator::generate(&fb).unwrap();
+
+        let mut md = String::new();
+        write_condition_sources(&mut md, &table);
+
+        assert_eq!(
+            md,
+            "**Condition Sources:**\n\n\
+             - State 20: `start = TRUE` from transition `10_to_20`\n\
+             - State 30: `level > 5` from transition `20_to_30`\n\
+             - State 30: `start = TRUE` from transition `10_to_20`\n\n"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::analysis::FsmValidator;
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use crate::output::{guard_label, NodeIds, OutputOptions};
+use anyhow::Result;
+use std::path::Path;
+
+/// Mermaid `stateDiagram-v2` with one composite state per function block,
+/// for embedding in GitHub markdown
+pub fn export_mermaid(fsm: &FiniteStateMachine, options: &OutputOptions, output_path: Option<&Path>) -> Result<()> {
+    let mermaid = render_mermaid(fsm, options);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, mermaid)?;
+    } else {
+        print!("{}", mermaid);
+    }
+
+    Ok(())
+}
+
+fn render_mermaid(fsm: &FiniteStateMachine, options: &OutputOptions) -> String {
+    let mut mermaid = String::from("stateDiagram-v2\n");
+
+    let ids = NodeIds::new(fsm);
+    for fb in &fsm.function_blocks {
+        if options.compact_states {
+            write_function_block(&mut mermaid, &fb.diagram_view(true), &ids, options);
+        } else {
+            write_function_block(&mut mermaid, fb, &ids, options);
+        }
+    }
+
+    mermaid
+}
+
+fn write_function_block(mermaid: &mut String, fb: &FunctionBlock, ids: &NodeIds, options: &OutputOptions) {
+    let block_id = ids.block(&fb.name);
+    mermaid.push_str(&format!("    state \"{}\" as {} {{\n", escape_label(&fb.name), block_id));
+
+    // State ids are only unique within a block, so nodes are prefixed with it
+    for state_id in fb.states.keys() {
+        mermaid.push_str(&format!(
+            "        state \"{}\" as {}\n",
+            escape_label(state_id),
+            ids.state(&fb.name, state_id)
+        ));
+    }
+
+    if let Some(initial) = FsmValidator::initial_state(fb) {
+        let guard = fb.init_transition
+            .as_ref()
+            .filter(|init| init.to_state == initial && init.condition.is_some())
+            .map(|init| guard_label(init, options));
+        write_edge(mermaid, "[*]", &ids.state(&fb.name, &initial), guard.as_deref());
+    }
+
+    for transition in &fb.transitions {
+        if let Some(target) = transition.computed_target_label() {
+            // No state to point at: the runtime target is noted on the source
+            mermaid.push_str(&format!(
+                "        note right of {} : {} {}\n",
+                ids.state(&fb.name, &transition.from_state),
+                escape_label(&transition.condition.as_ref().map_or("TRUE".to_string(), |_| guard_label(transition, options))),
+                escape_label(&target)
+            ));
+            continue;
+        }
+        let label = transition.via_label()
+            .or_else(|| transition.condition.as_ref().map(|_| guard_label(transition, options)));
+        write_edge(
+            mermaid,
+            &ids.state(&fb.name, &transition.from_state),
+            &ids.state(&fb.name, &transition.to_state),
+            label.as_deref(),
+        );
+    }
+
+    mermaid.push_str("    }\n");
+}
+
+fn write_edge(mermaid: &mut String, from: &str, to: &str, guard: Option<&str>) {
+    match guard.filter(|g| !g.trim().is_empty()) {
+        Some(guard) => mermaid.push_str(&format!("        {} --> {} : {}\n", from, to, escape_label(guard))),
+        None => mermaid.push_str(&format!("        {} --> {}\n", from, to)),
+    }
+}
+
+/// Mermaid entity codes for characters that end a label or a statement
+fn escape_label(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            ':' => escaped.push_str("#58;"),
+            ';' => escaped.push_str("#59;"),
+            '"' => escaped.push_str("#quot;"),
+            '>' if escaped.ends_with("--") => escaped.push_str("#62;"),
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_mermaid_edge_per_transition() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "t > T#5s".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "msg = 'a:b-->c'".to_string()));
+
+        let fsm = machine("pump.xml", vec![fb]);
+
+        let mermaid = render_mermaid(&fsm, &OutputOptions::default());
+        assert!(mermaid.starts_with("stateDiagram-v2\n"));
+        assert!(mermaid.contains("        PumpFB_10 --> PumpFB_20 : start = TRUE\n"));
+        assert!(mermaid.contains("        PumpFB_20 --> PumpFB_30 : t > T#35;5s\n"));
+        assert!(mermaid.contains("        PumpFB_30 --> PumpFB_10 : msg = 'a#58;b--#62;c'\n"));
+        assert!(mermaid.contains("        [*] --> PumpFB_10\n"));
+
+        let shorthand = render_mermaid(&fsm, &OutputOptions { boolean_shorthand: true, ..OutputOptions::default() });
+        assert!(shorthand.contains("        PumpFB_10 --> PumpFB_20 : start\n"));
+    }
+
+    #[test]
+    fn test_compact_states_hide_passthrough() {
+        let mut fb = FunctionBlock::new("LineFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "done".to_string()));
+
+        let fsm = machine("line.xml", vec![fb]);
+
+        let mermaid = render_mermaid(&fsm, &OutputOptions { compact_states: true, ..OutputOptions::default() });
+        assert!(!mermaid.contains("LineFB_20"));
+        assert!(mermaid.contains("        LineFB_10 --> LineFB_30 : (via 1 state)\n"));
+    }
+}
@@ -2,37 +2,513 @@ pub mod text;
 pub mod json;
 pub mod dot;
 pub mod markdown;
+pub mod stats_csv;
+pub mod plain;
+pub mod drawio;
+pub mod sql;
+pub mod mermaid;
+pub mod plantuml;
+pub mod scxml;
+pub mod graphml;
+pub mod smv;
 
-use crate::fsm::FiniteStateMachine;
-use crate::analysis::{FsmStatistics, StateSignatureTable};
+use crate::fsm::{FiniteStateMachine, FunctionBlock, Transition};
+use crate::analysis::signatures::{Condition, ConditionExpr, PathSignature, StateSignature};
+use crate::analysis::{BlockStatus, FsmAnalyzer, FsmStatistics, SignatureGenerator, StateSignatureTable};
 use anyhow::Result;
 use clap::ValueEnum;
-use std::collections::HashMap;
+use serde::Deserialize;
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Text,
     Json,
     Dot,
     Markdown,
+    /// diagrams.net (mxGraph XML)
+    #[value(name = "drawio")]
+    DrawIo,
+    /// SQL `WHERE` clause per state, from its signature
+    Sql,
+    /// Mermaid `stateDiagram-v2`, rendered natively by GitHub markdown
+    Mermaid,
+    /// PlantUML state diagram
+    #[value(name = "plantuml")]
+    PlantUml,
+    /// W3C SCXML state chart
+    Scxml,
+    /// GraphML, for yEd and Gephi
+    #[value(name = "graphml")]
+    GraphMl,
+    /// NuSMV model for model checking
+    Smv,
+}
+
+/// Output formats for the `analyze` subcommand
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum AnalyzeFormat {
+    /// Human-readable report
+    #[default]
+    Text,
+    /// One CSV row of statistics per function block
+    StatsCsv,
+}
+
+/// Output formats for the `list` subcommand
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ListFormat {
+    /// One line per function block
+    #[default]
+    Text,
+    /// Array of `{name, case_variable, state_count}`
+    Json,
+}
+
+impl OutputFormat {
+    /// File extension used when writing one output file per input
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Dot => "dot",
+            OutputFormat::Markdown => "md",
+            OutputFormat::DrawIo => "drawio",
+            OutputFormat::Sql => "sql",
+            OutputFormat::Mermaid => "mmd",
+            OutputFormat::PlantUml => "puml",
+            OutputFormat::Scxml => "scxml",
+            OutputFormat::GraphMl => "graphml",
+            OutputFormat::Smv => "smv",
+        }
+    }
+}
+
+/// Graphviz engine the DOT output is laid out for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DotLayout {
+    /// Hierarchical, left to right
+    #[default]
+    Dot,
+    /// Spring model, for large machines
+    Neato,
+    /// Force-directed placement, for large machines
+    Fdp,
+    /// Circular, for highly cyclic machines
+    Circo,
+}
+
+impl DotLayout {
+    /// Graphviz command that renders this layout
+    pub fn engine(&self) -> &'static str {
+        match self {
+            DotLayout::Dot => "dot",
+            DotLayout::Neato => "neato",
+            DotLayout::Fdp => "fdp",
+            DotLayout::Circo => "circo",
+        }
+    }
+}
+
+/// How displayed guards and signatures spell their operators
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardStyle {
+    /// Structured Text: `AND`, `OR`, `XOR`, `NOT`, `=`, `<>`
+    #[default]
+    St,
+    /// C: `&&`, `||`, `^`, `!`, `==`, `!=`
+    C,
+}
+
+/// Display text for transitions without a guard unless overridden
+pub const DEFAULT_UNCONDITIONAL_LABEL: &str = "No Check";
+
+/// Rendering switches shared by the output backends
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    /// Render a per-state decision table of outgoing guards (markdown)
+    pub decision_tables: bool,
+    /// Shown in place of the guard for unconditional transitions
+    pub unconditional_label: String,
+    /// Render DOT guards as HTML-like tables, one row per conjunct
+    pub rich_labels: bool,
+    /// Text format emits sorted, uncolored, table-free lines for version control
+    pub plain: bool,
+    /// Graph attributes of the DOT output
+    pub layout: DotLayout,
+    /// Phrase guards and signatures in English (text and markdown)
+    pub condition_language: bool,
+    /// Embed each block's DOT graph in a fenced code block (markdown)
+    pub with_diagram: bool,
+    /// Shorten displayed signatures to this many characters (text and markdown)
+    pub max_signature_width: Option<usize>,
+    /// Hide pass-through states in diagrams, leaving analysis on the full FSM (DOT and draw.io)
+    pub compact_states: bool,
+    /// Show `var = TRUE` as `var` and `var = FALSE` as `NOT var` (`!var` in
+    /// the C style)
+    pub boolean_shorthand: bool,
+    /// Operator spelling of displayed guards and signatures
+    pub guard_style: GuardStyle,
+    /// List at most this many transitions per source state (text and markdown)
+    pub max_transitions_per_state: Option<usize>,
+    /// Explain the analysis coloring and markers (DOT and markdown with analysis)
+    pub legend: bool,
+    /// Draw every block as a cluster of one DOT graph instead of one graph each
+    pub single_graph: bool,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self {
+            decision_tables: false,
+            unconditional_label: DEFAULT_UNCONDITIONAL_LABEL.to_string(),
+            rich_labels: false,
+            plain: false,
+            layout: DotLayout::default(),
+            condition_language: false,
+            with_diagram: false,
+            max_signature_width: None,
+            compact_states: false,
+            boolean_shorthand: false,
+            guard_style: GuardStyle::St,
+            max_transitions_per_state: None,
+            legend: true,
+            single_graph: false,
+        }
+    }
+}
+
+/// One-line verdict shown under a block's heading, from `stat` or from
+/// statistics computed here when the caller has none for the block
+pub(crate) fn block_summary(fb: &FunctionBlock, stat: Option<&FsmStatistics>) -> (String, BlockStatus) {
+    match stat {
+        Some(stat) => (stat.summary_line(&fb.name), stat.status()),
+        None => {
+            let stat = FsmStatistics::analyze(fb);
+            (stat.summary_line(&fb.name), stat.status())
+        }
+    }
+}
+
+/// Row of a transition listing: a transition, or how many of a source state's
+/// transitions `max_transitions_per_state` left out
+pub(crate) enum TransitionLine<'a> {
+    Shown(&'a Transition),
+    Hidden { from_state: &'a str, count: usize },
+}
+
+/// Transitions as listed by the text and markdown backends. Under a per-state
+/// limit each source state shows its first transitions by target and guard,
+/// then a `Hidden` row for the rest; without one, all in extraction order.
+pub(crate) fn transition_lines<'a>(transitions: &'a [Transition], options: &OutputOptions) -> Vec<TransitionLine<'a>> {
+    let Some(max) = options.max_transitions_per_state else {
+        return transitions.iter().map(TransitionLine::Shown).collect();
+    };
+
+    let mut by_state: IndexMap<&str, Vec<&Transition>> = IndexMap::new();
+    for transition in transitions {
+        by_state.entry(&transition.from_state).or_default().push(transition);
+    }
+
+    let mut lines = Vec::new();
+    for (from_state, mut outgoing) in by_state {
+        outgoing.sort_by(|a, b| {
+            compare_states(&a.to_state, &b.to_state).then_with(|| a.condition.cmp(&b.condition))
+        });
+        let hidden = outgoing.len().saturating_sub(max);
+        lines.extend(outgoing.into_iter().take(max).map(TransitionLine::Shown));
+        if hidden > 0 {
+            lines.push(TransitionLine::Hidden { from_state, count: hidden });
+        }
+    }
+    lines
+}
+
+/// Numeric state ids in numeric order, ahead of named ones
+pub(crate) fn compare_states(a: &str, b: &str) -> Ordering {
+    match (a.parse::<i64>(), b.parse::<i64>()) {
+        (Ok(x), Ok(y)) => x.cmp(&y),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+/// Guard of `transition` as labelled on diagrams and in tables: the source
+/// text, re-rendered from its parse tree when `boolean_shorthand` or the C
+/// style asks for it
+pub(crate) fn guard_label(transition: &Transition, options: &OutputOptions) -> String {
+    if (options.boolean_shorthand || options.guard_style == GuardStyle::C)
+        && let Some(guard) = transition.guard_ast() {
+        return expr_text(guard, options);
+    }
+    transition.condition_label(&options.unconditional_label).to_string()
+}
+
+/// Guard of `transition` as shown by the text and markdown backends
+pub(crate) fn guard_text(transition: &Transition, options: &OutputOptions) -> String {
+    let Some(condition) = transition.condition.as_deref().filter(|_| options.condition_language) else {
+        return guard_label(transition, options);
+    };
+
+    SignatureGenerator::parse_transition_condition(condition)
+        .into_iter()
+        .map(|conjunction| PathSignature::new(conjunction, 0).to_natural_language())
+        .collect::<Vec<_>>()
+        .join(", or ")
+}
+
+/// `expr` spelled per `options`, with parentheses only where precedence
+/// (NOT, AND, XOR, OR from tightest) needs them
+pub(crate) fn expr_text(expr: &ConditionExpr, options: &OutputOptions) -> String {
+    fn precedence(expr: &ConditionExpr) -> u8 {
+        match expr {
+            ConditionExpr::Or(..) => 1,
+            ConditionExpr::Xor(..) => 2,
+            ConditionExpr::And(..) => 3,
+            ConditionExpr::Not(_) | ConditionExpr::Atomic(_) => 4,
+        }
+    }
+
+    let c_style = options.guard_style == GuardStyle::C;
+    let operand = |child: &ConditionExpr, level: u8| {
+        let text = expr_text(child, options);
+        if precedence(child) < level { format!("({})", text) } else { text }
+    };
+    let binary = |a: &ConditionExpr, b: &ConditionExpr, st: &str, c: &str| {
+        let level = precedence(expr);
+        format!("{} {} {}", operand(a, level), if c_style { c } else { st }, operand(b, level))
+    };
+
+    match expr {
+        ConditionExpr::Atomic(condition) => condition_text(condition, options),
+        ConditionExpr::And(a, b) => binary(a, b, "AND", "&&"),
+        ConditionExpr::Xor(a, b) => binary(a, b, "XOR", "^"),
+        ConditionExpr::Or(a, b) => binary(a, b, "OR", "||"),
+        ConditionExpr::Not(inner) => {
+            let text = expr_text(inner, options);
+            let text = if text.contains(' ') { format!("({})", text) } else { text };
+            if c_style { format!("!{}", text) } else { format!("NOT {}", text) }
+        }
+    }
+}
+
+/// `condition` spelled per `options`; display only, the condition is unchanged
+pub(crate) fn condition_text(condition: &Condition, options: &OutputOptions) -> String {
+    let c_style = options.guard_style == GuardStyle::C;
+    match condition.boolean_test().filter(|_| options.boolean_shorthand) {
+        Some(true) => condition.variable.clone(),
+        Some(false) if c_style => format!("!{}", condition.variable),
+        Some(false) => format!("NOT {}", condition.variable),
+        None if c_style => {
+            let operator = match condition.operator.as_str() {
+                "=" => "==",
+                "<>" => "!=",
+                other => other,
+            };
+            format!("{} {} {}", condition.variable, operator, condition.shown_value())
+        }
+        None => condition.to_string(),
+    }
+}
+
+/// Conditions of one path joined by AND, `[initial]` for none
+fn conjunction_text(conditions: &[Condition], options: &OutputOptions) -> String {
+    if conditions.is_empty() {
+        return "[initial]".to_string();
+    }
+    let separator = if options.guard_style == GuardStyle::C { " && " } else { " AND " };
+    conditions.iter().map(|c| condition_text(c, options)).collect::<Vec<_>>().join(separator)
+}
+
+/// `StateSignature::format_conditions` spelled per `options`
+pub(crate) fn signature_conditions(signature: &StateSignature, options: &OutputOptions) -> String {
+    match signature.path_signatures.as_slice() {
+        _ if signature.is_unsatisfiable() => "[unsatisfiable]".to_string(),
+        [] => "[initial]".to_string(),
+        [single] => conjunction_text(&single.conditions, options),
+        paths => {
+            let separator = if options.guard_style == GuardStyle::C { " || " } else { " OR " };
+            paths
+                .iter()
+                .map(|ps| format!("({})", conjunction_text(&ps.conditions, options)))
+                .collect::<Vec<_>>()
+                .join(separator)
+        }
+    }
+}
+
+/// Signature conditions as shown by the text and markdown backends
+pub(crate) fn signature_text(signature: &StateSignature, options: &OutputOptions) -> String {
+    let Some(max_width) = options.max_signature_width else {
+        return if options.condition_language {
+            signature.to_natural_language()
+        } else {
+            signature_conditions(signature, options)
+        };
+    };
+
+    let c_style = options.guard_style == GuardStyle::C;
+    // Truncate at whole disjuncts, or whole conditions of a single path
+    let (terms, separator): (Vec<String>, &str) = match signature.path_signatures.as_slice() {
+        _ if options.condition_language => (
+            signature.path_signatures.iter().map(PathSignature::to_natural_language).collect(),
+            ", or ",
+        ),
+        [] => (vec![signature.format_conditions()], ""),
+        [single] if single.conditions.is_empty() => (vec!["[initial]".to_string()], ""),
+        [single] => (
+            single.conditions.iter().map(|c| condition_text(c, options)).collect(),
+            if c_style { " && " } else { " AND " },
+        ),
+        paths => (
+            paths.iter().map(|ps| format!("({})", conjunction_text(&ps.conditions, options))).collect(),
+            if c_style { " || " } else { " OR " },
+        ),
+    };
+
+    truncate_terms(&terms, separator, max_width)
+}
+
+/// Join `terms`, keeping only as many as fit in `max_width` characters followed
+/// by a `…(+k more)` marker for the hidden rest
+fn truncate_terms(terms: &[String], separator: &str, max_width: usize) -> String {
+    let full = terms.join(separator);
+    if full.chars().count() <= max_width {
+        return full;
+    }
+
+    let mut shown = String::new();
+    for (kept, term) in terms.iter().enumerate() {
+        let candidate = if kept == 0 { term.clone() } else { format!("{}{}{}", shown, separator, term) };
+        let marker = format!("…(+{} more)", terms.len() - kept - 1);
+        if candidate.chars().count() + 1 + marker.chars().count() > max_width {
+            let marker = format!("…(+{} more)", terms.len() - kept);
+            return if shown.is_empty() { marker } else { format!("{} {}", shown, marker) };
+        }
+        shown = candidate;
+    }
+
+    shown
+}
+
+/// Text for an XML attribute or element; line breaks become character
+/// references so attribute values keep them
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
+
+/// Diagram identifiers for every block and state of a machine.
+///
+/// `<block>_<state>` keeps ASCII alphanumerics, turns everything else into
+/// `_` and gets a leading `_` before a digit, so it's a valid name in
+/// Mermaid, PlantUML and XML. Names that reduce to the same id (`A-1` and
+/// `A_1`) get a numeric suffix in document order.
+pub(crate) struct NodeIds {
+    ids: HashMap<(String, Option<String>), String>,
+}
+
+impl NodeIds {
+    pub(crate) fn new(fsm: &FiniteStateMachine) -> Self {
+        let mut ids = HashMap::new();
+        let mut taken = HashSet::new();
+
+        for fb in &fsm.function_blocks {
+            let referenced = fb.transitions
+                .iter()
+                .chain(&fb.init_transition)
+                .filter(|t| !t.computed_target)
+                .flat_map(|t| [&t.from_state, &t.to_state]);
+            let states = std::iter::once(None).chain(fb.states.keys().chain(referenced).map(Some));
+
+            for state in states {
+                let key = (fb.name.clone(), state.cloned());
+                if ids.contains_key(&key) {
+                    continue;
+                }
+                let base = Self::sanitize(&fb.name, state.map(String::as_str));
+                let mut id = base.clone();
+                let mut suffix = 2;
+                while !taken.insert(id.clone()) {
+                    id = format!("{}_{}", base, suffix);
+                    suffix += 1;
+                }
+                ids.insert(key, id);
+            }
+        }
+
+        Self { ids }
+    }
+
+    pub(crate) fn block(&self, block: &str) -> String {
+        self.lookup(block, None)
+    }
+
+    pub(crate) fn state(&self, block: &str, state: &str) -> String {
+        self.lookup(block, Some(state))
+    }
+
+    fn lookup(&self, block: &str, state: Option<&str>) -> String {
+        self.ids
+            .get(&(block.to_string(), state.map(str::to_string)))
+            .cloned()
+            .unwrap_or_else(|| Self::sanitize(block, state))
+    }
+
+    fn sanitize(block: &str, state: Option<&str>) -> String {
+        let raw = match state {
+            Some(state) => format!("{}_{}", block, state),
+            None => block.to_string(),
+        };
+        let id: String = raw.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+        if id.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("_{}", id)
+        } else {
+            id
+        }
+    }
 }
 
 pub struct OutputWriter {
     format: OutputFormat,
+    options: OutputOptions,
 }
 
 impl OutputWriter {
     pub fn new(format: OutputFormat) -> Self {
-        Self { format }
+        Self { format, options: OutputOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: OutputOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn write(&self, fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
         match self.format {
-            OutputFormat::Text => text::print_text_table(fsm),
+            OutputFormat::Text if self.options.plain => {
+                write_text(&plain::render_plain(fsm, None, None, &self.options), output_path)?
+            },
+            OutputFormat::Text => text::print_text_table(fsm, &self.options),
             OutputFormat::Json => json::export_json(fsm, output_path)?,
-            OutputFormat::Dot => dot::export_graphviz(fsm, output_path)?,
-            OutputFormat::Markdown => markdown::export_markdown(fsm, output_path)?,
+            OutputFormat::Dot => dot::export_graphviz(fsm, &self.options, output_path)?,
+            OutputFormat::Markdown => markdown::export_markdown(fsm, &self.options, output_path)?,
+            OutputFormat::DrawIo => drawio::export_drawio(fsm, &self.options, output_path)?,
+            OutputFormat::Sql => sql::export_sql(fsm, &FsmAnalyzer::new().generate_signatures(fsm)?, output_path)?,
+            OutputFormat::Mermaid => mermaid::export_mermaid(fsm, &self.options, output_path)?,
+            OutputFormat::PlantUml => plantuml::export_plantuml(fsm, &self.options, output_path)?,
+            OutputFormat::Scxml => scxml::export_scxml(fsm, output_path)?,
+            OutputFormat::GraphMl => graphml::export_graphml(fsm, output_path)?,
+            OutputFormat::Smv => smv::export_smv(fsm, output_path)?,
         }
         Ok(())
     }
@@ -44,10 +520,20 @@ impl OutputWriter {
         output_path: Option<&Path>
     ) -> Result<()> {
         match self.format {
-            OutputFormat::Text => text::print_with_analysis(fsm, stats),
+            OutputFormat::Text if self.options.plain => {
+                write_text(&plain::render_plain(fsm, Some(stats), None, &self.options), output_path)?
+            },
+            OutputFormat::Text => text::print_with_analysis(fsm, stats, &self.options),
             OutputFormat::Json => json::export_with_analysis(fsm, stats, output_path)?,
-            OutputFormat::Dot => dot::export_graphviz(fsm, output_path)?,
-            OutputFormat::Markdown => markdown::export_with_analysis(fsm, stats, output_path)?,
+            OutputFormat::Dot => dot::export_with_analysis(fsm, stats, &self.options, output_path)?,
+            OutputFormat::Markdown => markdown::export_with_analysis(fsm, stats, &self.options, output_path)?,
+            OutputFormat::DrawIo => drawio::export_drawio(fsm, &self.options, output_path)?,
+            OutputFormat::Sql => sql::export_sql(fsm, &FsmAnalyzer::new().generate_signatures(fsm)?, output_path)?,
+            OutputFormat::Mermaid => mermaid::export_mermaid(fsm, &self.options, output_path)?,
+            OutputFormat::PlantUml => plantuml::export_plantuml(fsm, &self.options, output_path)?,
+            OutputFormat::Scxml => scxml::export_scxml(fsm, output_path)?,
+            OutputFormat::GraphMl => graphml::export_graphml(fsm, output_path)?,
+            OutputFormat::Smv => smv::export_smv(fsm, output_path)?,
         }
         Ok(())
     }
@@ -60,10 +546,20 @@ impl OutputWriter {
         output_path: Option<&Path>
     ) -> Result<()> {
         match self.format {
-            OutputFormat::Text => text::print_with_signatures(fsm, signatures),
+            OutputFormat::Text if self.options.plain => {
+                write_text(&plain::render_plain(fsm, None, Some(signatures), &self.options), output_path)?
+            },
+            OutputFormat::Text => text::print_with_signatures(fsm, signatures, &self.options),
             OutputFormat::Json => json::export_with_signatures(fsm, signatures, output_path)?,
-            OutputFormat::Dot => dot::export_graphviz(fsm, output_path)?,
-            OutputFormat::Markdown => markdown::export_with_signatures(fsm, signatures, output_path)?,
+            OutputFormat::Dot => dot::export_graphviz(fsm, &self.options, output_path)?,
+            OutputFormat::Markdown => markdown::export_with_signatures(fsm, signatures, &self.options, output_path)?,
+            OutputFormat::DrawIo => drawio::export_drawio(fsm, &self.options, output_path)?,
+            OutputFormat::Sql => sql::export_sql(fsm, signatures, output_path)?,
+            OutputFormat::Mermaid => mermaid::export_mermaid(fsm, &self.options, output_path)?,
+            OutputFormat::PlantUml => plantuml::export_plantuml(fsm, &self.options, output_path)?,
+            OutputFormat::Scxml => scxml::export_scxml(fsm, output_path)?,
+            OutputFormat::GraphMl => graphml::export_graphml(fsm, output_path)?,
+            OutputFormat::Smv => smv::export_smv(fsm, output_path)?,
         }
         Ok(())
     }
@@ -77,11 +573,114 @@ impl OutputWriter {
         output_path: Option<&Path>
     ) -> Result<()> {
         match self.format {
-            OutputFormat::Text => text::print_with_full_analysis(fsm, stats, signatures),
+            OutputFormat::Text if self.options.plain => {
+                write_text(&plain::render_plain(fsm, Some(stats), Some(signatures), &self.options), output_path)?
+            },
+            OutputFormat::Text => text::print_with_full_analysis(fsm, stats, signatures, &self.options),
             OutputFormat::Json => json::export_with_full_analysis(fsm, stats, signatures, output_path)?,
-            OutputFormat::Dot => dot::export_graphviz(fsm, output_path)?,
-            OutputFormat::Markdown => markdown::export_with_full_analysis(fsm, stats, signatures, output_path)?,
+            OutputFormat::Dot => dot::export_with_analysis(fsm, stats, &self.options, output_path)?,
+            OutputFormat::Markdown => markdown::export_with_full_analysis(fsm, stats, signatures, &self.options, output_path)?,
+            OutputFormat::DrawIo => drawio::export_drawio(fsm, &self.options, output_path)?,
+            OutputFormat::Sql => sql::export_sql(fsm, signatures, output_path)?,
+            OutputFormat::Mermaid => mermaid::export_mermaid(fsm, &self.options, output_path)?,
+            OutputFormat::PlantUml => plantuml::export_plantuml(fsm, &self.options, output_path)?,
+            OutputFormat::Scxml => scxml::export_scxml(fsm, output_path)?,
+            OutputFormat::GraphMl => graphml::export_graphml(fsm, output_path)?,
+            OutputFormat::Smv => smv::export_smv(fsm, output_path)?,
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+fn write_text(content: &str, output_path: Option<&Path>) -> Result<()> {
+    if let Some(path) = output_path {
+        std::fs::write(path, content)?;
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{FunctionBlock, State};
+
+    #[test]
+    fn test_signature_width_truncation() {
+        let mut fb = FunctionBlock::new("WideFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        let guard = (1..=6).map(|i| format!("sensor_{} = TRUE", i)).collect::<Vec<_>>().join(" AND ");
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), guard));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig = table.get_signature("20").unwrap();
+
+        let options = OutputOptions { max_signature_width: Some(50), ..OutputOptions::default() };
+        let shown = signature_text(sig, &options);
+        assert_eq!(shown, "sensor_1 = TRUE AND sensor_2 = TRUE …(+4 more)");
+        assert!(shown.chars().count() <= 50);
+
+        assert_eq!(signature_text(sig, &OutputOptions::default()), sig.format_conditions());
+        let json = serde_json::to_string(&table).unwrap();
+        assert!(json.contains("sensor_6"));
+    }
+
+    #[test]
+    fn test_boolean_shorthand_display_only() {
+        let mut fb = FunctionBlock::new("PressFB".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "enabled = TRUE AND fault = FALSE".to_string()));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig = table.get_signature("20").unwrap();
+
+        let options = OutputOptions { boolean_shorthand: true, ..OutputOptions::default() };
+        assert_eq!(signature_text(sig, &options), "enabled AND NOT fault");
+        assert_eq!(guard_text(&fb.transitions[0], &options), "enabled AND NOT fault");
+        assert_eq!(signature_text(sig, &OutputOptions::default()), "enabled = TRUE AND fault = FALSE");
+        assert_eq!(sig.path_signatures[0].conditions[1].value, "FALSE");
+
+        let c_style = OutputOptions { guard_style: GuardStyle::C, ..options };
+        assert_eq!(signature_text(sig, &c_style), "enabled && !fault");
+        assert_eq!(guard_label(&fb.transitions[0], &c_style), "enabled && !fault");
+    }
+
+    #[test]
+    fn test_shorthand_keeps_guard_structure() {
+        let transition = Transition::new(
+            "10".to_string(),
+            "20".to_string(),
+            "(a = TRUE OR b = FALSE) AND NOT (c = TRUE AND level > 5)".to_string(),
+        );
+
+        let options = OutputOptions { boolean_shorthand: true, ..OutputOptions::default() };
+        assert_eq!(guard_label(&transition, &options), "(a OR NOT b) AND NOT (c AND level > 5)");
+
+        let c_style = OutputOptions { guard_style: GuardStyle::C, ..OutputOptions::default() };
+        assert_eq!(guard_label(&transition, &c_style), "(a == TRUE || b == FALSE) && !(c == TRUE && level > 5)");
+
+        assert_eq!(guard_label(&transition, &OutputOptions::default()), transition.condition.as_deref().unwrap());
+    }
+
+    #[test]
+    fn test_node_ids_disambiguate_collisions() {
+        let mut fb = FunctionBlock::new("Pump.FB".to_string(), "state".to_string());
+        for id in ["A-1", "A_1", "1"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        let fsm = machine("pump.xml", vec![fb, FunctionBlock::new("Pump_FB".to_string(), "state".to_string())]);
+
+        let ids = NodeIds::new(&fsm);
+        assert_eq!(ids.block("Pump.FB"), "Pump_FB");
+        assert_eq!(ids.state("Pump.FB", "A-1"), "Pump_FB_A_1");
+        assert_eq!(ids.state("Pump.FB", "A_1"), "Pump_FB_A_1_2");
+        assert_eq!(ids.state("Pump.FB", "1"), "Pump_FB_1");
+        assert_eq!(ids.block("Pump_FB"), "Pump_FB_2");
+
+        assert_eq!(escape_xml("a < b\n\"c\""), "a &lt; b&#10;&quot;c&quot;");
+    }
+}
@@ -0,0 +1,120 @@
+use crate::fsm::{FiniteStateMachine, Transition};
+use crate::analysis::{FsmStatistics, StateSignatureTable};
+use crate::output::{compare_states, signature_conditions, OutputOptions};
+use std::collections::HashMap;
+
+/// Uncolored, table-free text with blocks, transitions and states in a fixed
+/// order, so committed reports diff line by line
+pub fn render_plain(
+    fsm: &FiniteStateMachine,
+    stats: Option<&HashMap<String, FsmStatistics>>,
+    signatures: Option<&HashMap<String, StateSignatureTable>>,
+    options: &OutputOptions,
+) -> String {
+    let mut out = String::new();
+
+    let mut blocks: Vec<_> = fsm.function_blocks.iter().collect();
+    blocks.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for fb in blocks {
+        out.push_str(&format!("FB {}\n", fb.name));
+        out.push_str(&format!("  case {}\n", fb.case_variable));
+
+        if let Some(init) = &fb.init_transition {
+            out.push_str(&format!(
+                "  init -> {} [{}]\n",
+                init.to_state,
+                init.condition_label(&options.unconditional_label)
+            ));
+        }
+
+        let mut transitions: Vec<&Transition> = fb.transitions.iter().collect();
+        transitions.sort_by(|a, b| {
+            compare_states(&a.from_state, &b.from_state)
+                .then_with(|| compare_states(&a.to_state, &b.to_state))
+                .then_with(|| a.condition.cmp(&b.condition))
+        });
+
+        for t in transitions {
+            out.push_str(&format!(
+                "  {} -> {} [{}]\n",
+                t.from_state,
+                t.to_state,
+                t.condition_label(&options.unconditional_label)
+            ));
+        }
+
+        if let Some(stat) = stats.and_then(|s| s.get(&fb.name)) {
+            out.push_str(&format!("  unreachable {}\n", sorted_states(&stat.unreachable_states)));
+            out.push_str(&format!("  dead {}\n", sorted_states(&stat.dead_states)));
+            out.push_str(&format!("  cycles {}\n", stat.cycles.len()));
+        }
+
+        if let Some(sig_table) = signatures.and_then(|s| s.get(&fb.name)) {
+            let mut states: Vec<&String> = sig_table.signatures.keys().collect();
+            states.sort_by(|a, b| compare_states(a, b));
+
+            for state_id in states {
+                out.push_str(&format!(
+                    "  signature {}: {}\n",
+                    state_id,
+                    signature_conditions(&sig_table.signatures[state_id], options)
+                ));
+            }
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+/// `states` as a comma-separated list in `compare_states` order, or `none`
+fn sorted_states(states: &[String]) -> String {
+    if states.is_empty() {
+        return "none".to_string();
+    }
+
+    let mut sorted: Vec<&str> = states.iter().map(String::as_str).collect();
+    sorted.sort_by(|a, b| compare_states(a, b));
+    sorted.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::analysis::FsmAnalyzer;
+    use crate::fsm::{FunctionBlock, State};
+
+    fn create_chain_fsm() -> FiniteStateMachine {
+        let mut fb = FunctionBlock::new("Pump".to_string(), "state".to_string());
+        fb.add_state(State::new("10".to_string()));
+        fb.add_state(State::new("20".to_string()));
+        fb.add_state(State::new("100".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "100".to_string(), "sensor = high".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+
+        machine("chain.xml", vec![fb])
+    }
+
+    #[test]
+    fn test_plain_output_is_stable_and_unstyled() {
+        let analyzer = FsmAnalyzer::new();
+        let options = OutputOptions::default();
+
+        let render = || {
+            let fsm = create_chain_fsm();
+            let stats = analyzer.analyze_all(&fsm);
+            let signatures = analyzer.generate_signatures(&fsm).unwrap();
+            render_plain(&fsm, Some(&stats), Some(&signatures), &options)
+        };
+
+        let first = render();
+        assert_eq!(first, render());
+        assert!(first.starts_with("FB Pump\n  case state\n  10 -> 20 [sensor = low]\n  20 -> 100 [sensor = high]\n"));
+        assert!(!first.contains('\u{1b}'));
+        assert!(!first.contains('|'));
+        assert!(!first.chars().any(|c| ('\u{2500}'..='\u{257f}').contains(&c)));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::analysis::FsmValidator;
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use crate::output::{guard_label, NodeIds, OutputOptions};
+use anyhow::Result;
+use std::path::Path;
+
+/// PlantUML state diagram with one composite state per function block
+pub fn export_plantuml(fsm: &FiniteStateMachine, options: &OutputOptions, output_path: Option<&Path>) -> Result<()> {
+    let uml = render_plantuml(fsm, options);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, uml)?;
+    } else {
+        print!("{}", uml);
+    }
+
+    Ok(())
+}
+
+fn render_plantuml(fsm: &FiniteStateMachine, options: &OutputOptions) -> String {
+    let mut uml = String::from("@startuml\n");
+
+    let ids = NodeIds::new(fsm);
+    for fb in &fsm.function_blocks {
+        write_function_block(&mut uml, fb, &ids, options);
+    }
+
+    uml.push_str("@enduml\n");
+    uml
+}
+
+fn write_function_block(uml: &mut String, fb: &FunctionBlock, ids: &NodeIds, options: &OutputOptions) {
+    uml.push_str(&format!("state \"{}\" as {} {{\n", escape_label(&fb.name), ids.block(&fb.name)));
+
+    // State ids are only unique within a block, so nodes are prefixed with it
+    for state_id in fb.states.keys() {
+        uml.push_str(&format!("  state \"{}\" as {}\n", escape_label(state_id), ids.state(&fb.name, state_id)));
+    }
+
+    for initial in FsmValidator::initial_states(fb) {
+        uml.push_str(&format!("  [*] --> {}\n", ids.state(&fb.name, &initial)));
+    }
+
+    for transition in &fb.transitions {
+        let from = ids.state(&fb.name, &transition.from_state);
+        let guard = transition.condition
+            .as_deref()
+            .filter(|g| !g.trim().is_empty())
+            .map(|_| guard_label(transition, options));
+        if let Some(target) = transition.computed_target_label() {
+            // No state to point at: the runtime target is noted on the source
+            let guard = guard.unwrap_or_else(|| "TRUE".to_string());
+            uml.push_str(&format!("  note right of {} : {} {}\n", from, escape_label(&guard), escape_label(&target)));
+            continue;
+        }
+        let to = ids.state(&fb.name, &transition.to_state);
+        match guard {
+            Some(guard) => uml.push_str(&format!("  {} --> {} : {}\n", from, to, escape_label(&guard))),
+            None => uml.push_str(&format!("  {} --> {}\n", from, to)),
+        }
+    }
+
+    for dead in FsmValidator::find_dead_states(fb) {
+        uml.push_str(&format!("  {} --> [*]\n", ids.state(&fb.name, &dead)));
+    }
+
+    uml.push_str("}\n");
+}
+
+/// Labels end at the line, and `"` would close a display name
+fn escape_label(text: &str) -> String {
+    text.replace('"', "'").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_plantuml_linear_fsm() {
+        let mut fb = FunctionBlock::new("TestFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+
+        let fsm = machine("test.xml", vec![fb]);
+
+        let uml = render_plantuml(&fsm, &OutputOptions::default());
+        assert!(uml.starts_with("@startuml\n"));
+        assert!(uml.ends_with("@enduml\n"));
+        assert!(uml.contains("  [*] --> TestFB_10\n"));
+        assert!(uml.contains("  TestFB_10 --> TestFB_20 : sensor = low\n"));
+        assert!(uml.contains("  TestFB_20 --> TestFB_30 : sensor = high\n"));
+        assert!(uml.contains("  TestFB_30 --> [*]\n"));
+        assert_eq!(uml.matches("[*]").count(), 2);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::analysis::FsmValidator;
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use crate::output::{escape_xml, NodeIds};
+use anyhow::Result;
+use std::path::Path;
+
+const SCXML_NAMESPACE: &str = "http://www.w3.org/2005/07/scxml";
+
+/// W3C SCXML document with the function blocks as regions of one `<parallel>`,
+/// since a PLC scans them all every cycle
+pub fn export_scxml(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
+    let scxml = render_scxml(fsm);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, scxml)?;
+    } else {
+        print!("{}", scxml);
+    }
+
+    Ok(())
+}
+
+fn render_scxml(fsm: &FiniteStateMachine) -> String {
+    let mut scxml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    scxml.push_str(&format!("<scxml xmlns=\"{}\" version=\"1.0\" initial=\"plc\">\n", SCXML_NAMESPACE));
+    scxml.push_str("  <parallel id=\"plc\">\n");
+
+    let ids = NodeIds::new(fsm);
+    for fb in &fsm.function_blocks {
+        write_function_block(&mut scxml, fb, &ids);
+    }
+
+    scxml.push_str("  </parallel>\n");
+    scxml.push_str("</scxml>\n");
+    scxml
+}
+
+fn write_function_block(scxml: &mut String, fb: &FunctionBlock, ids: &NodeIds) {
+    scxml.push_str(&format!("    <state id=\"{}\"", ids.block(&fb.name)));
+    if let Some(initial) = FsmValidator::initial_state(fb) {
+        scxml.push_str(&format!(" initial=\"{}\"", ids.state(&fb.name, &initial)));
+    }
+    scxml.push_str(">\n");
+
+    // State ids are only unique within a block, so they are prefixed with it
+    for id in fb.states.keys() {
+        let outgoing: Vec<_> = fb.transitions.iter().filter(|t| &t.from_state == id).collect();
+        if outgoing.is_empty() {
+            scxml.push_str(&format!("      <state id=\"{}\"/>\n", ids.state(&fb.name, id)));
+            continue;
+        }
+
+        scxml.push_str(&format!("      <state id=\"{}\">\n", ids.state(&fb.name, id)));
+        // Eventless: PLC transitions fire on the scan where their guard holds
+        for transition in outgoing {
+            if let Some(target) = transition.computed_target_label() {
+                // SCXML targets must be states; keep the runtime target as a note
+                scxml.push_str(&format!(
+                    "        <!-- {} {} -->\n",
+                    escape_xml(transition.condition_label("TRUE")).replace("--", "- -"),
+                    escape_xml(&target).replace("--", "- -")
+                ));
+                continue;
+            }
+            let target = ids.state(&fb.name, &transition.to_state);
+            match transition.condition.as_deref().filter(|g| !g.trim().is_empty()) {
+                Some(guard) => scxml.push_str(&format!(
+                    "        <transition cond=\"{}\" target=\"{}\"/>\n",
+                    escape_xml(guard),
+                    target
+                )),
+                None => scxml.push_str(&format!("        <transition target=\"{}\"/>\n", target)),
+            }
+        }
+        scxml.push_str("      </state>\n");
+    }
+
+    scxml.push_str("    </state>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_scxml_is_well_formed_with_transition_per_fsm_transition() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "level < 5 AND run = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "msg = \"a&b\"".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "".to_string()));
+
+        let fsm = machine("pump.xml", vec![fb]);
+
+        let scxml = render_scxml(&fsm);
+        let doc = roxmltree::Document::parse(&scxml).unwrap();
+        assert_eq!(doc.root_element().tag_name().namespace(), Some(SCXML_NAMESPACE));
+
+        let transitions: Vec<_> = doc.descendants().filter(|n| n.has_tag_name("transition")).collect();
+        assert_eq!(transitions.len(), 3);
+        assert_eq!(transitions[0].attribute("cond"), Some("level < 5 AND run = TRUE"));
+        assert_eq!(transitions[1].attribute("cond"), Some("msg = \"a&b\""));
+        assert_eq!(transitions[2].attribute("cond"), None);
+        assert_eq!(transitions[2].attribute("target"), Some("PumpFB_10"));
+
+        let block = doc.descendants().find(|n| n.attribute("id") == Some("PumpFB")).unwrap();
+        assert_eq!(block.attribute("initial"), Some("PumpFB_10"));
+    }
+}
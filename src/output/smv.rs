@@ -0,0 +1,353 @@
+use crate::analysis::signatures::{parse_duration_ms, Condition};
+use crate::analysis::{FsmValidator, SignatureGenerator};
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use anyhow::Result;
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// NuSMV model with one module per function block, instantiated from `main`.
+/// Guard variables become unconstrained inputs typed from the values they're
+/// compared against; comparisons SMV can't express are widened to `TRUE`, so
+/// the model over-approximates the PLC.
+pub fn export_smv(fsm: &FiniteStateMachine, output_path: Option<&Path>) -> Result<()> {
+    let smv = render_smv(fsm);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, smv)?;
+    } else {
+        print!("{}", smv);
+    }
+
+    Ok(())
+}
+
+/// What an input variable is compared against across a block's guards
+enum InputType {
+    Boolean,
+    /// Integer range padded by one on either side of the compared values
+    Range(i64, i64),
+    /// Symbolic values, plus `OTHER` for anything not mentioned
+    Symbolic(Vec<String>),
+}
+
+/// Guard variable as declared in the module's `IVAR` section
+struct Input {
+    /// SMV identifier, unique within the module
+    name: String,
+    input_type: InputType,
+}
+
+/// Identifiers SMV reserves, plus the module's own `state` variable
+const RESERVED: [&str; 24] = [
+    "state", "MODULE", "VAR", "IVAR", "DEFINE", "ASSIGN", "INIT", "INVAR", "TRANS", "SPEC", "LTLSPEC",
+    "FAIRNESS", "init", "next", "case", "esac", "TRUE", "FALSE", "boolean", "mod", "in", "union", "self", "process",
+];
+
+fn render_smv(fsm: &FiniteStateMachine) -> String {
+    let blocks: Vec<&FunctionBlock> = fsm.function_blocks.iter().filter(|fb| !fb.states.is_empty()).collect();
+
+    let mut smv = format!("-- Extracted from {}\n", fsm.metadata.source_file.display());
+    smv.push_str("MODULE main\n");
+    if !blocks.is_empty() {
+        smv.push_str("VAR\n");
+        for fb in &blocks {
+            let module = identifier(&fb.name);
+            smv.push_str(&format!("  {}_fsm : {};\n", module, module));
+        }
+    }
+
+    for fb in blocks {
+        smv.push('\n');
+        write_module(&mut smv, fb);
+    }
+
+    smv
+}
+
+fn write_module(smv: &mut String, fb: &FunctionBlock) {
+    let guards: Vec<Vec<Vec<Condition>>> = fb.transitions
+        .iter()
+        .map(|t| SignatureGenerator::parse_transition_condition(t.condition.as_deref().unwrap_or_default()))
+        .collect();
+    let inputs = inputs(fb, &guards);
+
+    smv.push_str(&format!("MODULE {}\n", identifier(&fb.name)));
+    if !inputs.is_empty() {
+        smv.push_str("IVAR\n");
+        for input in inputs.values() {
+            let declared = match &input.input_type {
+                InputType::Boolean => "boolean".to_string(),
+                InputType::Range(low, high) => format!("{}..{}", low, high),
+                InputType::Symbolic(values) => format!("{{{}, OTHER}}", values.join(", ")),
+            };
+            smv.push_str(&format!("  {} : {};\n", input.name, declared));
+        }
+    }
+
+    let states: Vec<String> = fb.states.keys().map(|id| state_value(id)).collect();
+    smv.push_str(&format!("VAR\n  state : {{{}}};\n", states.join(", ")));
+
+    smv.push_str("ASSIGN\n");
+    if let Some(initial) = FsmValidator::initial_state(fb) {
+        smv.push_str(&format!("  init(state) := {};\n", state_value(&initial)));
+    }
+
+    // The first branch that holds wins, like the IF/ELSIF chain it came from
+    smv.push_str("  next(state) := case\n");
+    for (transition, dnf) in fb.transitions.iter().zip(&guards) {
+        // A target computed at runtime may be any state
+        let target = if transition.computed_target {
+            format!("{{{}}}", states.join(", "))
+        } else {
+            state_value(&transition.to_state)
+        };
+        smv.push_str(&format!(
+            "    state = {}{} : {};\n",
+            state_value(&transition.from_state),
+            guard_expression(dnf, &inputs),
+            target
+        ));
+    }
+    smv.push_str("    TRUE : state;\n");
+    smv.push_str("  esac;\n");
+}
+
+/// ` & (...)` to append to the source state test, empty when unconditional
+fn guard_expression(dnf: &[Vec<Condition>], inputs: &IndexMap<String, Input>) -> String {
+    if dnf.iter().any(Vec::is_empty) {
+        return String::new();
+    }
+
+    let terms: Vec<String> = dnf
+        .iter()
+        .map(|conjunction| {
+            let conditions: Vec<String> = conjunction.iter().map(|c| smv_condition(c, inputs)).collect();
+            conditions.join(" & ")
+        })
+        .collect();
+    match terms.as_slice() {
+        [] => " & FALSE".to_string(),
+        [term] => format!(" & {}", term),
+        _ => format!(" & ({})", terms.join(" | ")),
+    }
+}
+
+fn smv_condition(condition: &Condition, inputs: &IndexMap<String, Input>) -> String {
+    let Some(input) = inputs.get(&condition.variable) else {
+        return "TRUE".to_string();
+    };
+    let variable = &input.name;
+    let operator = if condition.operator == "<>" { "!=" } else { condition.operator.as_str() };
+
+    // Another input on the right: comparable when both sides have the same kind of type
+    if let Some(other) = condition.value_variable().and_then(|name| inputs.get(name)) {
+        return match (&input.input_type, &other.input_type) {
+            (InputType::Range(..), InputType::Range(..)) => format!("{} {} {}", variable, operator, other.name),
+            (InputType::Boolean, InputType::Boolean) if matches!(operator, "=" | "!=") => {
+                format!("{} {} {}", variable, operator, other.name)
+            }
+            _ => "TRUE".to_string(),
+        };
+    }
+
+    match &input.input_type {
+        InputType::Boolean => {
+            let expected = condition.value.eq_ignore_ascii_case("TRUE") == (operator == "=");
+            if expected { variable.clone() } else { format!("!{}", variable) }
+        }
+        InputType::Range(..) => match numeric_value(&condition.value) {
+            Some(value) => integer_comparison(variable, operator, value),
+            None => "TRUE".to_string(),
+        },
+        InputType::Symbolic(_) if matches!(operator, "=" | "!=") => {
+            format!("{} {} {}", variable, operator, symbolic_value(&condition.value))
+        }
+        _ => "TRUE".to_string(),
+    }
+}
+
+/// `variable operator value` over integers: a fractional threshold moves to
+/// the integer on the side that keeps the comparison's meaning, and equality
+/// with it never holds
+fn integer_comparison(variable: &str, operator: &str, value: f64) -> String {
+    if value.fract() == 0.0 {
+        return format!("{} {} {}", variable, operator, value as i64);
+    }
+
+    match operator {
+        ">" | "<=" => format!("{} {} {}", variable, operator, value.floor() as i64),
+        "<" | ">=" => format!("{} {} {}", variable, operator, value.ceil() as i64),
+        "=" => "FALSE".to_string(),
+        // `!=` always holds
+        _ => "TRUE".to_string(),
+    }
+}
+
+/// Every variable the guards compare against a literal, typed from those
+/// literals and named uniquely, in order of first mention. A value naming a
+/// guard or declared variable compares two inputs and is no symbolic constant
+fn inputs(fb: &FunctionBlock, guards: &[Vec<Vec<Condition>>]) -> IndexMap<String, Input> {
+    let conditions: Vec<&Condition> = guards.iter().flatten().flatten().collect();
+    let is_variable = |name: &str| {
+        conditions.iter().any(|c| c.variable == name) || fb.declared_variables.iter().any(|v| v == name)
+    };
+
+    let mut compared: IndexMap<String, Vec<&str>> = IndexMap::new();
+    for condition in &conditions {
+        let values = compared.entry(condition.variable.clone()).or_default();
+        if !condition.value_variable().is_some_and(is_variable) {
+            values.push(&condition.value);
+        }
+    }
+
+    let mut taken: Vec<String> = RESERVED.iter().map(|r| r.to_string()).collect();
+    compared
+        .into_iter()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(variable, values)| {
+            let input_type = if values.iter().all(|v| v.eq_ignore_ascii_case("TRUE") || v.eq_ignore_ascii_case("FALSE")) {
+                InputType::Boolean
+            } else if let Some(numbers) = values.iter().map(|v| numeric_value(v)).collect::<Option<Vec<f64>>>() {
+                let low = numbers.iter().cloned().fold(f64::INFINITY, f64::min).floor() as i64;
+                let high = numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max).ceil() as i64;
+                InputType::Range(low - 1, high + 1)
+            } else {
+                let mut symbols: Vec<String> = values.iter().map(|v| symbolic_value(v)).collect();
+                symbols.sort();
+                symbols.dedup();
+                InputType::Symbolic(symbols)
+            };
+
+            let mut name = identifier(&variable);
+            while taken.contains(&name) {
+                name.push('_');
+            }
+            taken.push(name.clone());
+
+            (variable, Input { name, input_type })
+        })
+        .collect()
+}
+
+fn numeric_value(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().or_else(|| parse_duration_ms(value))
+}
+
+/// Integer state ids stay numeric; others become identifiers
+fn state_value(state_id: &str) -> String {
+    match state_id.parse::<i64>() {
+        Ok(number) => number.to_string(),
+        Err(_) => symbolic_value(state_id),
+    }
+}
+
+fn symbolic_value(value: &str) -> String {
+    match value.parse::<i64>() {
+        Ok(number) => number.to_string(),
+        Err(_) => identifier(value.trim_matches(|c| c == '\'' || c == '"')),
+    }
+}
+
+/// Anything but ASCII alphanumerics becomes `_`, and a leading digit gets
+/// an underscore in front
+fn identifier(name: &str) -> String {
+    let id: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", id)
+    } else {
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::machine;
+    use crate::fsm::{State, Transition};
+
+    #[test]
+    fn test_smv_declares_states_and_branch_per_transition() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 90 OR mode = AUTO".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "".to_string()));
+
+        let fsm = machine("pump.xml", vec![fb]);
+
+        let smv = render_smv(&fsm);
+        assert!(smv.contains("  PumpFB_fsm : PumpFB;\n"));
+        assert!(smv.contains("  state : {10, 20, 30};\n"));
+        assert!(smv.contains("  init(state) := 10;\n"));
+        assert!(smv.contains("  start : boolean;\n"));
+        assert!(smv.contains("  level : 89..91;\n"));
+        assert!(smv.contains("  mode : {AUTO, OTHER};\n"));
+
+        assert!(smv.contains("    state = 10 & start : 20;\n"));
+        assert!(smv.contains("    state = 20 & (level > 90 | mode = AUTO) : 30;\n"));
+        assert!(smv.contains("    state = 30 : 10;\n"));
+        assert_eq!(smv.matches("    state = ").count(), 3);
+    }
+
+    #[test]
+    fn test_computed_target_may_be_any_state() {
+        let mut fb = FunctionBlock::new("SeqFB".to_string(), "state".to_string());
+        for id in ["10", "20"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        let mut computed = Transition::new("20".to_string(), crate::fsm::COMPUTED_STATE.to_string(), "next = TRUE".to_string());
+        computed.computed_target = true;
+        computed.target_expression = Some("base + 10".to_string());
+        fb.add_transition(computed);
+
+        let mut smv = String::new();
+        write_module(&mut smv, &fb);
+        assert!(smv.contains("  state : {10, 20};\n"));
+        assert!(smv.contains("    state = 20 & next_ : {10, 20};\n"));
+        assert!(!smv.contains("computed"));
+    }
+
+    #[test]
+    fn test_fractional_thresholds_keep_their_meaning() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30", "40", "50"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "level > 2.5".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "30".to_string(), "level < 2.5".to_string()));
+        fb.add_transition(Transition::new("10".to_string(), "40".to_string(), "level = 2.5".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "50".to_string(), "level >= 0.5 AND level <= 0.5".to_string()));
+
+        let mut smv = String::new();
+        write_module(&mut smv, &fb);
+        assert!(smv.contains("    state = 10 & level > 2 : 20;\n"));
+        assert!(smv.contains("    state = 10 & level < 3 : 30;\n"));
+        assert!(smv.contains("    state = 10 & FALSE : 40;\n"));
+        assert!(smv.contains("    state = 20 & level >= 1 & level <= 0 : 50;\n"));
+    }
+
+    #[test]
+    fn test_inputs_compared_with_each_other() {
+        let mut fb = FunctionBlock::new("TankFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "level > setpoint".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level < 10 AND setpoint > 50".to_string()));
+        fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "state = TRUE".to_string()));
+
+        let mut smv = String::new();
+        write_module(&mut smv, &fb);
+        assert!(smv.contains("  level : 9..11;\n"));
+        assert!(smv.contains("  setpoint : 49..51;\n"));
+        assert!(!smv.contains("{setpoint"));
+        assert!(smv.contains("    state = 10 & level > setpoint : 20;\n"));
+
+        // A guard variable named like the state variable is renamed
+        assert!(smv.contains("  state_ : boolean;\n"));
+        assert!(smv.contains("    state = 30 & state_ : 10;\n"));
+    }
+}
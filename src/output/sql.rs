@@ -0,0 +1,178 @@
+use crate::analysis::signatures::{Condition, StateSignature};
+use crate::analysis::StateSignatureTable;
+use crate::fsm::FiniteStateMachine;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One `WHERE` clause per state over columns named after the guard variables,
+/// for finding when a historian recorded the PLC in that state
+pub fn export_sql(
+    fsm: &FiniteStateMachine,
+    signatures: &HashMap<String, StateSignatureTable>,
+    output_path: Option<&Path>,
+) -> Result<()> {
+    let sql = render_sql(fsm, signatures);
+
+    if let Some(path) = output_path {
+        std::fs::write(path, sql)?;
+    } else {
+        print!("{}", sql);
+    }
+    Ok(())
+}
+
+fn render_sql(fsm: &FiniteStateMachine, signatures: &HashMap<String, StateSignatureTable>) -> String {
+    let mut sql = String::new();
+
+    for fb in &fsm.function_blocks {
+        let Some(table) = signatures.get(&fb.name) else {
+            continue;
+        };
+
+        sql.push_str(&format!("-- Function block: {}\n", sql_comment(&fb.name)));
+        for state_id in fb.states.keys() {
+            if let Some(signature) = table.get_signature(state_id) {
+                sql.push_str(&format!("-- State {}\nWHERE {};\n", sql_comment(state_id), where_clause(signature)));
+            }
+        }
+        sql.push('\n');
+    }
+
+    sql
+}
+
+/// The signature's DNF as a SQL condition, one parenthesized term per path
+pub fn where_clause(signature: &StateSignature) -> String {
+    let terms: Vec<String> = signature.path_signatures
+        .iter()
+        .map(|path| {
+            if path.conditions.is_empty() {
+                return "(1 = 1)".to_string();
+            }
+            let conditions: Vec<String> = path.conditions.iter().map(sql_condition).collect();
+            format!("({})", conditions.join(" AND "))
+        })
+        .collect();
+
+    if terms.is_empty() {
+        "1 = 1".to_string()
+    } else {
+        terms.join(" OR ")
+    }
+}
+
+fn sql_condition(condition: &Condition) -> String {
+    format!("{} {} {}", sql_column(&condition.variable), condition.operator, sql_value(&condition.value))
+}
+
+/// Keywords a plain column name can't be, sorted for `binary_search`
+const RESERVED_WORDS: &[&str] = &[
+    "ALL", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "CHECK", "COLUMN",
+    "CONSTRAINT", "CREATE", "CROSS", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE",
+    "END", "EXCEPT", "EXISTS", "FALSE", "FETCH", "FOR", "FOREIGN", "FROM", "FULL", "GROUP",
+    "HAVING", "IN", "INNER", "INSERT", "INTERSECT", "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE",
+    "LIMIT", "NOT", "NULL", "OFFSET", "ON", "OR", "ORDER", "OUTER", "PRIMARY", "REFERENCES",
+    "RIGHT", "SELECT", "SET", "TABLE", "THEN", "TO", "TRUE", "UNION", "UNIQUE", "UPDATE", "USER",
+    "USING", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+/// Plain names stay bare; keywords (`order`), dotted paths (`axis.pos`) and
+/// subscripts (`states[i]`) are quoted so they stay single identifiers. A
+/// number on the left (`1 = 1`) stays a number.
+fn sql_column(variable: &str) -> String {
+    if is_number(variable) || !needs_quoting(variable) {
+        return variable.to_string();
+    }
+    format!("\"{}\"", variable.replace('"', "\"\""))
+}
+
+fn needs_quoting(name: &str) -> bool {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    !plain || RESERVED_WORDS.binary_search(&name.to_ascii_uppercase().as_str()).is_ok()
+}
+
+/// Numbers and booleans stay bare, everything else becomes a string literal.
+/// A quoted ST literal loses its quotes and `$` escapes first, so the SQL
+/// literal holds the same text.
+fn sql_value(value: &str) -> String {
+    if is_number(value) || value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE") {
+        return value.to_string();
+    }
+
+    let text = match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(literal) => unescape_st(literal),
+        None => value.to_string(),
+    };
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Finite numbers only, so `inf` and `NaN` stay strings
+fn is_number(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')
+        && text.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// Resolves the `$'` and `$$` escapes of an ST string literal
+fn unescape_st(literal: &str) -> String {
+    let mut text = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('$', Some(next @ ('\'' | '$'))) => {
+                text.push(next);
+                chars.next();
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+/// Keeps names containing line breaks inside their `--` comment
+fn sql_comment(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::SignatureGenerator;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    #[test]
+    fn test_where_clause_per_state() {
+        let mut fb = FunctionBlock::new("TestFB".to_string(), "state".to_string());
+        for id in ["10", "15", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+        fb.add_transition(Transition::new("15".to_string(), "20".to_string(), "button = pressed".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 5".to_string()));
+
+        let table = SignatureGenerator::generate(&fb).unwrap();
+        let sig_20 = table.get_signature("20").unwrap();
+        assert_eq!(where_clause(sig_20), "(sensor = 'low') OR (button = 'pressed')");
+
+        let sig_30 = table.get_signature("30").unwrap();
+        assert!(where_clause(sig_30).contains("level > 5"));
+    }
+
+    #[test]
+    fn test_identifiers_and_literals_escaped() {
+        assert_eq!(sql_column("level"), "level");
+        assert_eq!(sql_column("order"), "\"order\"");
+        assert_eq!(sql_column("Select"), "\"Select\"");
+        assert_eq!(sql_column("axis.pos"), "\"axis.pos\"");
+        assert_eq!(sql_column("a\"b"), "\"a\"\"b\"");
+
+        assert_eq!(sql_value("5"), "5");
+        assert_eq!(sql_value("-2.5"), "-2.5");
+        assert_eq!(sql_value("inf"), "'inf'");
+        assert_eq!(sql_value("O'Brien"), "'O''Brien'");
+        assert_eq!(sql_value("'it$'s'"), "'it''s'");
+        assert_eq!(sql_value("'5$$'"), "'5$'");
+        assert_eq!(sql_comment("A\nDROP TABLE x"), "A DROP TABLE x");
+    }
+}
@@ -0,0 +1,113 @@
+use crate::analysis::FsmStatistics;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+const HEADER: [&str; 12] = [
+    "function_block",
+    "total_states",
+    "total_transitions",
+    "avg_transitions_per_state",
+    "max_transitions_from_state",
+    "max_transitions_into_state",
+    "scc_count",
+    "cyclomatic_complexity",
+    "unreachable_states",
+    "dead_states",
+    "cycles",
+    "cycle_members",
+];
+
+/// One row per function block, sorted by name so runs diff cleanly
+pub fn export_stats_csv(stats: &HashMap<String, FsmStatistics>, output_path: Option<&Path>) -> Result<()> {
+    let csv = render_stats_csv(stats);
+
+    if let Some(path) = output_path {
+        let mut file = File::create(path)?;
+        file.write_all(csv.as_bytes())?;
+    } else {
+        print!("{}", csv);
+    }
+
+    Ok(())
+}
+
+fn render_stats_csv(stats: &HashMap<String, FsmStatistics>) -> String {
+    let mut csv = String::new();
+    csv.push_str(&HEADER.join(","));
+    csv.push('\n');
+
+    let mut names: Vec<&String> = stats.keys().collect();
+    names.sort();
+
+    for name in names {
+        let stat = &stats[name];
+        let row = [
+            escape_field(name),
+            stat.total_states.to_string(),
+            stat.total_transitions.to_string(),
+            format!("{:.2}", stat.avg_transitions_per_state),
+            stat.max_transitions_from_state.to_string(),
+            stat.max_transitions_into_state.to_string(),
+            stat.scc_count.to_string(),
+            stat.cyclomatic_complexity.to_string(),
+            stat.unreachable_states.len().to_string(),
+            stat.dead_states.len().to_string(),
+            stat.cycles.len().to_string(),
+            stat.cycle_membership.len().to_string(),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    fn create_fb(name: &str, states: &[&str]) -> FunctionBlock {
+        let mut fb = FunctionBlock::new(name.to_string(), "state".to_string());
+        for state in states {
+            fb.add_state(State::new(state.to_string()));
+        }
+        for pair in states.windows(2) {
+            fb.add_transition(Transition::new(pair[0].to_string(), pair[1].to_string(), "go = TRUE".to_string()));
+        }
+        fb
+    }
+
+    #[test]
+    fn test_one_row_per_block() {
+        let mut stats = HashMap::new();
+        stats.insert("MixerFB".to_string(), FsmStatistics::analyze(&create_fb("MixerFB", &["10", "20", "30"])));
+        stats.insert("ValveFB".to_string(), FsmStatistics::analyze(&create_fb("ValveFB", &["10", "20"])));
+
+        let csv = render_stats_csv(&stats);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let header: Vec<&str> = lines[0].split(',').collect();
+        let states_col = header.iter().position(|c| *c == "total_states").unwrap();
+
+        let mixer: Vec<&str> = lines[1].split(',').collect();
+        let valve: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(mixer.len(), header.len());
+        assert_eq!(mixer[0], "MixerFB");
+        assert_eq!(mixer[states_col], "3");
+        assert_eq!(valve[0], "ValveFB");
+        assert_eq!(valve[states_col], "2");
+    }
+}
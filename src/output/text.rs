@@ -1,7 +1,8 @@
 use crate::fsm::{FiniteStateMachine, FunctionBlock};
-use crate::analysis::{FsmStatistics, StateSignatureTable}; // ✅ NEW IMPORT
+use crate::analysis::{BlockStatus, FsmStatistics, SignatureDiff, StateSignatureTable}; // ✅ NEW IMPORT
 use colored::*;
 use tabled::{Table, Tabled, settings::Style};
+use crate::output::{block_summary, guard_text, signature_text, transition_lines, OutputOptions, TransitionLine};
 use std::collections::HashMap;
 
 #[derive(Tabled)]
@@ -25,15 +26,19 @@ struct SignatureRow {
     paths: String,
 }
 
-pub fn print_text_table(fsm: &FiniteStateMachine) {
+pub fn print_text_table(fsm: &FiniteStateMachine, options: &OutputOptions) {
     for fb in &fsm.function_blocks {
-        print_function_block(fb);
+        print_function_block(fb, None, options);
     }
 }
 
-pub fn print_with_analysis(fsm: &FiniteStateMachine, stats: &HashMap<String, FsmStatistics>) {
+pub fn print_with_analysis(
+    fsm: &FiniteStateMachine,
+    stats: &HashMap<String, FsmStatistics>,
+    options: &OutputOptions,
+) {
     for fb in &fsm.function_blocks {
-        print_function_block(fb);
+        print_function_block(fb, stats.get(&fb.name), options);
 
         if let Some(stat) = stats.get(&fb.name) {
             println!("\n{}", "Analysis Results:".bold());
@@ -43,9 +48,14 @@ pub fn print_with_analysis(fsm: &FiniteStateMachine, stats: &HashMap<String, Fsm
             println!("  Dead-end states: {}",
                      if stat.dead_states.is_empty() { "None".green() }
                      else { format!("{:?}", stat.dead_states).red() });
+            if !stat.terminal_states.is_empty() {
+                println!("  Terminal states: {:?}", stat.terminal_states);
+            }
             println!("  Cycles: {}",
                      if stat.cycles.is_empty() { "None".green() }
                      else { format!("{} found", stat.cycles.len()).yellow() });
+            println!("  Deterministic: {}",
+                     if stat.is_deterministic { "Yes".green() } else { "No".red() });
         }
     }
 }
@@ -53,13 +63,14 @@ pub fn print_with_analysis(fsm: &FiniteStateMachine, stats: &HashMap<String, Fsm
 // Print FSM with signatures
 pub fn print_with_signatures(
     fsm: &FiniteStateMachine,
-    signatures: &HashMap<String, StateSignatureTable>
+    signatures: &HashMap<String, StateSignatureTable>,
+    options: &OutputOptions,
 ) {
     for fb in &fsm.function_blocks {
-        print_function_block(fb);
+        print_function_block(fb, None, options);
 
         if let Some(sig_table) = signatures.get(&fb.name) {
-            print_signature_table(sig_table);
+            print_signature_table(sig_table, options);
         }
     }
 }
@@ -69,9 +80,10 @@ pub fn print_with_full_analysis(
     fsm: &FiniteStateMachine,
     stats: &HashMap<String, FsmStatistics>,
     signatures: &HashMap<String, StateSignatureTable>,
+    options: &OutputOptions,
 ) {
     for fb in &fsm.function_blocks {
-        print_function_block(fb);
+        print_function_block(fb, stats.get(&fb.name), options);
 
         // Print analysis
         if let Some(stat) = stats.get(&fb.name) {
@@ -82,20 +94,49 @@ pub fn print_with_full_analysis(
             println!("  Dead-end states: {}",
                      if stat.dead_states.is_empty() { "None".green() }
                      else { format!("{:?}", stat.dead_states).red() });
+            if !stat.terminal_states.is_empty() {
+                println!("  Terminal states: {:?}", stat.terminal_states);
+            }
             println!("  Cycles: {}",
                      if stat.cycles.is_empty() { "None".green() }
                      else { format!("{} found", stat.cycles.len()).yellow() });
+            println!("  Deterministic: {}",
+                     if stat.is_deterministic { "Yes".green() } else { "No".red() });
         }
 
         // Print signatures
         if let Some(sig_table) = signatures.get(&fb.name) {
-            print_signature_table(sig_table);
+            print_signature_table(sig_table, options);
+        }
+    }
+}
+
+/// Print states whose reaching conditions changed between two versions
+pub fn print_signature_diffs(diffs: &[SignatureDiff]) {
+    for diff in diffs {
+        println!("\n{}", format!("Function Block: {}", diff.function_block).bold().cyan());
+
+        if diff.is_empty() {
+            println!("{} Signatures unchanged", "✓".green());
+            continue;
+        }
+
+        for state in &diff.added {
+            println!("  {} State {} added", "+".green(), state);
+        }
+        for state in &diff.removed {
+            println!("  {} State {} removed", "-".red(), state);
+        }
+        for change in &diff.changed {
+            println!("  {} State {}:", "~".yellow(), change.state_id.cyan());
+            println!("      old: {}", change.old.red());
+            println!("      new: {}", change.new.green());
         }
     }
 }
 
 // Print signature table
-fn print_signature_table(sig_table: &StateSignatureTable) {
+fn print_signature_table(sig_table: &StateSignatureTable, options: &OutputOptions) {
     println!("\n{}", "State Signatures:".bold().cyan());
     println!("Case Variable: {}", sig_table.case_variable.yellow());
 
@@ -103,7 +144,7 @@ fn print_signature_table(sig_table: &StateSignatureTable) {
         .values()
         .map(|sig| SignatureRow {
             state: sig.state_id.clone(),
-            conditions: sig.format_conditions(),
+            conditions: signature_text(sig, options),
             paths: sig.paths_count.to_string(),
         })
         .collect();
@@ -116,22 +157,61 @@ fn print_signature_table(sig_table: &StateSignatureTable) {
     } else {
         println!("No signatures generated.");
     }
+
+    let partial = sig_table.partial_states();
+    if !partial.is_empty() {
+        println!("{} signatures for cyclic states are partial: {}", "⚠".yellow(), partial.join(", "));
+    }
+
+    let limited = sig_table.limited_states();
+    if !limited.is_empty() {
+        println!("{} path limits truncated signatures of: {}", "⚠".yellow(), limited.join(", "));
+    }
 }
 
-fn print_function_block(fb: &FunctionBlock) {
+// Mark states that take part in at least one cycle
+fn state_label(state_id: &str, stat: Option<&FsmStatistics>) -> String {
+    match stat {
+        Some(stat) if stat.is_cycle_member(state_id) => format!("{} ⟳", state_id),
+        _ => state_id.to_string(),
+    }
+}
+
+fn print_function_block(fb: &FunctionBlock, stat: Option<&FsmStatistics>, options: &OutputOptions) {
     println!("\n{}", format!("Function Block: {}", fb.name).bold().cyan());
+    let (summary, status) = block_summary(fb, stat);
+    println!("{}", match status {
+        BlockStatus::Ok => summary.green(),
+        BlockStatus::Review => summary.yellow(),
+        BlockStatus::Problem => summary.red(),
+    });
     println!("Case Variable: {}", fb.case_variable.yellow());
     println!("\nStates: {} | Transitions: {}\n",
              fb.state_count().to_string().green(),
              fb.transition_count().to_string().green()
     );
 
-    let rows: Vec<TransitionRow> = fb.transitions
-        .iter()
-        .map(|t| TransitionRow {
-            current_state: t.from_state.clone(),
-            next_state: t.to_state.clone(),
-            condition: t.condition.clone(),
+    if let Some(init) = &fb.init_transition {
+        println!("Init: {} → {}", guard_text(init, options), init.to_state.green());
+        if !init.actions.is_empty() {
+            println!("  Actions: {}", init.actions.join("; "));
+        }
+        println!();
+    }
+
+    let rows: Vec<TransitionRow> = transition_lines(&fb.transitions, options)
+        .into_iter()
+        .map(|line| match line {
+            TransitionLine::Shown(t) => TransitionRow {
+                current_state: state_label(&t.from_state, stat),
+                next_state: state_label(&t.to_state, stat),
+                condition: guard_text(t, options),
+            },
+            TransitionLine::Hidden { from_state, count } => TransitionRow {
+                current_state: state_label(from_state, stat),
+                next_state: "…".to_string(),
+                condition: format!("… and {} more", count),
+            },
         })
         .collect();
 
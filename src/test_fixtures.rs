@@ -0,0 +1,72 @@
+//! Function blocks and XML shared by the unit tests
+
+use crate::fsm::{FiniteStateMachine, FunctionBlock, Metadata, State, Transition};
+use chrono::Utc;
+
+/// `PumpFB`, moving from 10 to 20 on `start`
+pub const PUMP_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PumpFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+/// `PumpFB` followed by `ValveFB`, moving from 0 to 1 on `open`
+#[cfg(feature = "tui")]
+pub fn pump_and_valve_xml() -> String {
+    let valve = r#"<function-block-declaration>
+<derived-function-block-name>ValveFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>step</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>0</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>open</variable-name></expression>
+<assignment-statement><variable-name>step</variable-name><expression><integer-literal>1</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+    PUMP_XML.replace("</project>", valve)
+}
+
+/// 10 → 20 on `sensor = low`, 20 → 30 on `sensor = high`
+pub fn linear_block() -> FunctionBlock {
+    let mut fb = FunctionBlock::new("TestFB".to_string(), "state".to_string());
+    fb.add_state(State::new("10".to_string()));
+    fb.add_state(State::new("20".to_string()));
+    fb.add_state(State::new("30".to_string()));
+    fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "sensor = low".to_string()));
+    fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "sensor = high".to_string()));
+    fb
+}
+
+/// `linear_block` closed by 30 → 10 on `reset = true`
+pub fn cyclic_block() -> FunctionBlock {
+    let mut fb = linear_block();
+    fb.add_transition(Transition::new("30".to_string(), "10".to_string(), "reset = true".to_string()));
+    fb
+}
+
+/// Machine of `blocks` as if extracted from `source_file` just now
+pub fn machine(source_file: &str, blocks: Vec<FunctionBlock>) -> FiniteStateMachine {
+    FiniteStateMachine {
+        metadata: Metadata {
+            source_file: source_file.into(),
+            extraction_date: Utc::now(),
+            total_states: blocks.iter().map(FunctionBlock::state_count).sum(),
+            total_transitions: blocks.iter().map(FunctionBlock::transition_count).sum(),
+        },
+        function_blocks: blocks,
+    }
+}
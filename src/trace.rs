@@ -0,0 +1,114 @@
+use crate::analysis::{MatchMode, StateSignatureTable};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The state a PLC reported together with the variable values logged with it
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TraceRecord {
+    pub state_id: String,
+    #[serde(default, deserialize_with = "variables_as_strings")]
+    pub variables: HashMap<String, String>,
+}
+
+/// Read a trace: a JSON array of records, or with a `.csv` extension a
+/// `state_id` column followed by one column per variable. Empty CSV cells
+/// leave the variable out of that record.
+pub fn read_trace(path: &Path) -> Result<Vec<TraceRecord>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read trace {}", path.display()))?;
+
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+        parse_csv_trace(&content).with_context(|| format!("Invalid trace {}", path.display()))
+    } else {
+        serde_json::from_str(&content).with_context(|| format!("Invalid trace {}", path.display()))
+    }
+}
+
+pub fn parse_csv_trace(content: &str) -> Result<Vec<TraceRecord>> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    if columns.first() != Some(&"state_id") {
+        bail!("first CSV column must be state_id");
+    }
+
+    lines
+        .enumerate()
+        .map(|(row, line)| {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            if cells.len() != columns.len() {
+                bail!("row {} has {} cells, expected {}", row + 1, cells.len(), columns.len());
+            }
+            Ok(TraceRecord {
+                state_id: cells[0].to_string(),
+                variables: columns[1..]
+                    .iter()
+                    .zip(&cells[1..])
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Whether each record's variables satisfy the signature of its state;
+/// states the table doesn't know fail
+pub fn verify_trace(table: &StateSignatureTable, records: &[TraceRecord], mode: MatchMode) -> Vec<bool> {
+    records
+        .iter()
+        .map(|record| table.verify_state_with_mode(&record.state_id, &record.variables, mode))
+        .collect()
+}
+
+/// JSON numbers and booleans become the text a guard compares against
+/// (`TRUE`/`FALSE` for booleans)
+fn variables_as_strings<'de, D>(deserializer: D) -> std::result::Result<HashMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw: HashMap<String, serde_json::Value> = HashMap::deserialize(deserializer)?;
+    raw.into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Bool(b) => if b { "TRUE" } else { "FALSE" }.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                other => return Err(serde::de::Error::custom(format!("unsupported value for {}: {}", name, other))),
+            };
+            Ok((name, text))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::SignatureGenerator;
+    use crate::fsm::{FunctionBlock, State, Transition};
+
+    #[test]
+    fn test_trace_verdict_per_record() {
+        let mut fb = FunctionBlock::new("PumpFB".to_string(), "state".to_string());
+        for id in ["10", "20", "30"] {
+            fb.add_state(State::new(id.to_string()));
+        }
+        fb.add_transition(Transition::new("10".to_string(), "20".to_string(), "start = TRUE".to_string()));
+        fb.add_transition(Transition::new("20".to_string(), "30".to_string(), "level > 5".to_string()));
+        let table = SignatureGenerator::generate(&fb).unwrap();
+
+        let json = r#"[
+            {"state_id": "30", "variables": {"start": true, "level": 7}},
+            {"state_id": "30", "variables": {"start": true, "level": 2}}
+        ]"#;
+        let records: Vec<TraceRecord> = serde_json::from_str(json).unwrap();
+        assert_eq!(verify_trace(&table, &records, MatchMode::Strict), [true, false]);
+
+        let csv = "state_id,start,level\n30,TRUE,7\n30,TRUE,2\n";
+        assert_eq!(parse_csv_trace(csv).unwrap(), records);
+    }
+}
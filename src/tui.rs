@@ -0,0 +1,295 @@
+use crate::analysis::{FsmAnalyzer, FsmStatistics, StateSignatureTable};
+use crate::fsm::{FiniteStateMachine, FunctionBlock};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+
+/// List that arrow keys and `/` act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Blocks,
+    States,
+}
+
+/// Browsing state of the TUI, kept apart from drawing so it can be tested
+/// without a terminal
+pub struct App {
+    fsm: FiniteStateMachine,
+    stats: HashMap<String, FsmStatistics>,
+    signatures: HashMap<String, StateSignatureTable>,
+    pub focus: Pane,
+    /// Substring the focused list is narrowed to
+    pub filter: String,
+    /// Keys go to `filter` rather than navigation
+    pub filtering: bool,
+    block_idx: usize,
+    state_idx: usize,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(fsm: FiniteStateMachine) -> Result<Self> {
+        let analyzer = FsmAnalyzer::new();
+        let stats = analyzer.analyze_all(&fsm);
+        let signatures = analyzer.generate_signatures(&fsm)?;
+
+        Ok(Self {
+            fsm,
+            stats,
+            signatures,
+            focus: Pane::Blocks,
+            filter: String::new(),
+            filtering: false,
+            block_idx: 0,
+            state_idx: 0,
+            quit: false,
+        })
+    }
+
+    /// Function blocks shown in the left pane
+    pub fn blocks(&self) -> Vec<&FunctionBlock> {
+        self.fsm.function_blocks
+            .iter()
+            .filter(|fb| self.focus != Pane::Blocks || fb.name.contains(&self.filter))
+            .collect()
+    }
+
+    pub fn selected_block(&self) -> Option<&FunctionBlock> {
+        self.blocks().get(self.block_idx).copied()
+    }
+
+    /// State ids of the selected block shown in the middle pane
+    pub fn states(&self) -> Vec<&str> {
+        let Some(fb) = self.selected_block() else {
+            return Vec::new();
+        };
+        fb.states
+            .keys()
+            .filter(|id| self.focus != Pane::States || id.contains(&self.filter))
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn selected_state(&self) -> Option<&str> {
+        self.states().get(self.state_idx).copied()
+    }
+
+    /// Issues the analysis found for a state of the selected block
+    pub fn markers(&self, state_id: &str) -> Vec<&'static str> {
+        let Some(stat) = self.selected_block().and_then(|fb| self.stats.get(&fb.name)) else {
+            return Vec::new();
+        };
+
+        let mut markers = Vec::new();
+        if stat.unreachable_states.iter().any(|s| s == state_id) {
+            markers.push("unreachable");
+        }
+        if stat.dead_states.iter().any(|s| s == state_id) {
+            markers.push("dead");
+        }
+        if stat.cycle_membership.contains_key(state_id) {
+            markers.push("cycle");
+        }
+        markers
+    }
+
+    /// Signature and outgoing transitions of the selected state
+    pub fn details(&self) -> Vec<String> {
+        let (Some(fb), Some(state_id)) = (self.selected_block(), self.selected_state()) else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![format!("State {}", state_id), String::new(), "Signature:".to_string()];
+        match self.signatures.get(&fb.name).and_then(|t| t.get_signature(state_id)) {
+            Some(signature) if !signature.path_signatures.is_empty() => {
+                for path in &signature.path_signatures {
+                    lines.push(format!("  {}", path.format_conditions()));
+                }
+            }
+            Some(signature) => lines.push(format!("  {}", signature.format_conditions())),
+            None => lines.push("  (none)".to_string()),
+        }
+
+        lines.push(String::new());
+        lines.push("Outgoing:".to_string());
+        for transition in fb.transitions.iter().filter(|t| t.from_state == state_id) {
+            lines.push(format!("  → {} when {}", transition.to_state, transition.condition_label("TRUE")));
+        }
+        lines
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if self.filtering {
+            match key.code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.filtering = false;
+                }
+                KeyCode::Enter => self.filtering = false,
+                _ => {}
+            }
+            self.reset_selection();
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => {
+                self.filtering = true;
+                self.filter.clear();
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Left if self.focus == Pane::States => self.switch_focus(Pane::Blocks),
+            KeyCode::Right | KeyCode::Enter if self.focus == Pane::Blocks => self.switch_focus(Pane::States),
+            _ => {}
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (len, idx) = match self.focus {
+            Pane::Blocks => (self.blocks().len(), &mut self.block_idx),
+            Pane::States => (self.states().len(), &mut self.state_idx),
+        };
+        if len == 0 {
+            return;
+        }
+        *idx = idx.saturating_add_signed(delta).min(len - 1);
+
+        if self.focus == Pane::Blocks {
+            self.state_idx = 0;
+        }
+    }
+
+    /// Keeps the selected block when leaving a filtered list
+    fn switch_focus(&mut self, focus: Pane) {
+        let block = self.selected_block().map(|fb| fb.name.clone());
+        self.focus = focus;
+        self.filter.clear();
+        self.filtering = false;
+        self.block_idx = block
+            .and_then(|name| self.fsm.function_blocks.iter().position(|fb| fb.name == name))
+            .unwrap_or(0);
+        self.state_idx = 0;
+    }
+
+    fn reset_selection(&mut self) {
+        match self.focus {
+            Pane::Blocks => {
+                self.block_idx = 0;
+                self.state_idx = 0;
+            }
+            Pane::States => self.state_idx = 0,
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [left, middle, right] = Layout::horizontal([
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Percentage(45),
+        ])
+        .areas(frame.area());
+
+        let blocks: Vec<ListItem> = self.blocks()
+            .iter()
+            .map(|fb| ListItem::new(format!("{} ({} states)", fb.name, fb.state_count())))
+            .collect();
+        let states: Vec<ListItem> = self.states()
+            .iter()
+            .map(|id| {
+                let markers = self.markers(id);
+                if markers.is_empty() {
+                    ListItem::new(id.to_string())
+                } else {
+                    ListItem::new(format!("{} [{}]", id, markers.join(", ")))
+                        .style(Style::default().fg(Color::Yellow))
+                }
+            })
+            .collect();
+
+        let mut block_state = ListState::default().with_selected(Some(self.block_idx));
+        let mut state_state = ListState::default().with_selected(Some(self.state_idx));
+        frame.render_stateful_widget(self.list(blocks, "Function blocks", Pane::Blocks), left, &mut block_state);
+        frame.render_stateful_widget(self.list(states, "States", Pane::States), middle, &mut state_state);
+
+        let details = Paragraph::new(self.details().join("\n"))
+            .block(Block::default().borders(Borders::ALL).title("Details"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, right);
+    }
+
+    fn list<'a>(&self, items: Vec<ListItem<'a>>, title: &str, pane: Pane) -> List<'a> {
+        let title = if self.focus == pane && (self.filtering || !self.filter.is_empty()) {
+            format!("{} /{}", title, self.filter)
+        } else {
+            title.to_string()
+        };
+        let border = if self.focus == pane { Color::Cyan } else { Color::Reset };
+
+        List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(border)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    }
+}
+
+/// Take over the terminal until the user quits
+pub fn run(fsm: FiniteStateMachine) -> Result<()> {
+    let mut app = App::new(fsm)?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press {
+            app.handle_key(key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::pump_and_valve_xml;
+    use crate::fsm::FsmExtractor;
+    use ratatui::crossterm::event::KeyModifiers;
+    use std::io::Write;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_app_lists_blocks_and_states() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(pump_and_valve_xml().as_bytes()).unwrap();
+        let fsm = FsmExtractor::new(file.path()).unwrap().extract().unwrap();
+
+        let mut app = App::new(fsm).unwrap();
+        let names: Vec<_> = app.blocks().iter().map(|fb| fb.name.as_str()).collect();
+        assert_eq!(names, ["PumpFB", "ValveFB"]);
+        assert_eq!(app.states(), ["10", "20"]);
+        assert_eq!(app.markers("20"), ["dead"]);
+
+        for code in [KeyCode::Char('/'), KeyCode::Char('V'), KeyCode::Enter, KeyCode::Right, KeyCode::Down] {
+            app.handle_key(key(code));
+        }
+        assert_eq!(app.selected_block().unwrap().name, "ValveFB");
+        assert_eq!(app.selected_state(), Some("1"));
+        assert!(app.details().iter().any(|line| line.contains("open")));
+    }
+}
@@ -1,72 +1,376 @@
 use crate::error::FsmError;
 use anyhow::Result;
 use roxmltree::{Document, Node};
+use serde::Serialize;
 use std::path::Path;
 use std::fs;
+use std::str::FromStr;
+
+/// Literal element kinds (`<{kind}-literal>`) understood by the parser
+pub const LITERAL_KINDS: [&str; 5] = ["integer", "boolean", "real", "time", "string"];
+
+/// Case variable of a block extracted from an SFC, standing for the active step
+pub const SFC_STEP_VARIABLE: &str = "__step__";
+
+/// Textual `from` -> `to` replacement applied to the raw XML before parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    pub from: String,
+    pub to: String,
+}
+
+impl RewriteRule {
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+/// Parses `FROM=TO` as given to `--xml-rewrite`
+impl FromStr for RewriteRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok(Self::new(from, to)),
+            _ => Err(format!("expected FROM=TO, got '{}'", s)),
+        }
+    }
+}
+
+/// Ordered list of rewrite rules run over the XML text before it is parsed
+#[derive(Debug, Clone)]
+pub struct XmlPreprocessor {
+    rules: Vec<RewriteRule>,
+}
+
+impl Default for XmlPreprocessor {
+    /// Unwraps `<expression>` around every literal kind, like the original C# tool did
+    /// for integers and booleans
+    fn default() -> Self {
+        let rules = LITERAL_KINDS
+            .iter()
+            .flat_map(|kind| [
+                RewriteRule::new(
+                    format!("<expression><{}-literal>", kind),
+                    format!("<value><{}-literal>", kind),
+                ),
+                RewriteRule::new(
+                    format!("</{}-literal></expression>", kind),
+                    format!("</{}-literal></value>", kind),
+                ),
+            ])
+            .collect();
+
+        Self { rules }
+    }
+}
+
+impl XmlPreprocessor {
+    /// Preprocessor without any rules
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn with_rules(mut self, rules: impl IntoIterator<Item = RewriteRule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    pub fn rules(&self) -> &[RewriteRule] {
+        &self.rules
+    }
+
+    pub fn apply(&self, content: &str) -> String {
+        self.rules
+            .iter()
+            .fold(content.to_string(), |acc, rule| acc.replace(&rule.from, &rule.to))
+    }
+}
+
+self_cell::self_cell!(
+    /// Preprocessed XML together with the document borrowing it
+    struct ParsedXml {
+        owner: String,
+        #[covariant]
+        dependent: Document,
+    }
+);
 
 pub struct XmlParser {
-    content: String,
-    document: Document<'static>,
+    xml: ParsedXml,
 }
 
 impl XmlParser {
     pub fn new(xml_path: &Path) -> Result<Self> {
+        Self::with_preprocessor(xml_path, &XmlPreprocessor::default())
+    }
+
+    pub fn with_preprocessor(xml_path: &Path, preprocessor: &XmlPreprocessor) -> Result<Self> {
         let content = fs::read_to_string(xml_path)?;
-        // Preprocess content similar to C# implementation
-        let content = content
-            .replace("<expression><integer-literal>", "<value><integer-literal>")
-            .replace("<expression><boolean-literal>", "<value><boolean-literal>");
-
-        // This is a workaround for the lifetime issue with roxmltree
-        // In production, you'd want to handle this more carefully
-        let content_leaked = Box::leak(content.clone().into_boxed_str());
-        let document = Document::parse(content_leaked)
+        Self::from_content(&content, preprocessor)
+    }
+
+    pub fn from_content(content: &str, preprocessor: &XmlPreprocessor) -> Result<Self> {
+        let xml = ParsedXml::try_new(preprocessor.apply(content), |content| Document::parse(content))
             .map_err(|e| FsmError::XmlParse(e.to_string()))?;
 
-        Ok(Self {
-            content,
-            document,
-        })
+        Ok(Self { xml })
+    }
+
+    fn document(&self) -> &Document<'_> {
+        self.xml.borrow_dependent()
     }
 
     pub fn find_function_blocks(&self) -> Vec<String> {
         let mut blocks = Vec::new();
 
-        for node in self.document.descendants() {
+        for node in self.document().descendants() {
             let tag_name = node.tag_name().name();
             if tag_name == "function-block-declaration" {
                 if let Some(name) = self.extract_function_block_name(&node) {
                     blocks.push(name);
                 }
-            } else if tag_name == "program-declaration" {
-                if let Some(name) = self.extract_program_name(&node) {
-                    blocks.push(name);
-                }
+            } else if tag_name == "program-declaration"
+                && let Some(name) = self.extract_program_name(&node) {
+                blocks.push(name);
             }
         }
 
         blocks
     }
 
+    /// Name, case variable and state count of every block, read without
+    /// building transitions
+    pub fn summarize_function_blocks(&self) -> Vec<BlockSummary> {
+        self.find_function_blocks()
+            .into_iter()
+            .map(|name| {
+                let fb_node = self.find_function_block_node(&name);
+                let case_stmt = fb_node.as_ref().and_then(|n| self.find_case_statement(n));
+                let (case_variable, state_count) = match case_stmt {
+                    Some(case_stmt) => {
+                        let case_variable = self.extract_case_variable(&case_stmt).ok();
+                        // Branch labels plus the constant states the branches assign
+                        let mut states: Vec<String> = case_stmt.descendants()
+                            .filter(|n| n.has_tag_name("case-element") && Self::enclosing_case(n) == Some(case_stmt))
+                            .filter_map(|n| self.extract_state_id(&n).ok())
+                            .collect();
+                        states.extend(
+                            case_stmt.descendants()
+                                .filter(|n| n.has_tag_name("assignment-statement") && Self::enclosing_case(n) == Some(case_stmt))
+                                .filter_map(|n| self.parse_assignment(&n).ok())
+                                .filter(|a| !a.computed && case_variable.as_ref() == Some(&a.variable))
+                                .map(|a| a.value),
+                        );
+                        states.sort();
+                        states.dedup();
+                        (case_variable, states.len())
+                    }
+                    None => match fb_node.and_then(|n| n.descendants().find(|n| n.has_tag_name("sequential-function-chart"))) {
+                        Some(sfc) => {
+                            let steps = sfc.descendants().filter(|n| n.has_tag_name("step") || n.has_tag_name("initial-step"));
+                            (Some(SFC_STEP_VARIABLE.to_string()), steps.count())
+                        }
+                        None => (None, 0),
+                    },
+                };
+
+                BlockSummary { name, case_variable, state_count }
+            })
+            .collect()
+    }
+
+    /// Caller/callee pairs for every `function-block-invocation` whose target
+    /// resolves to another block of the document, in document order
+    pub fn find_block_calls(&self) -> Vec<BlockCall> {
+        let blocks = self.find_function_blocks();
+        let mut calls = Vec::new();
+
+        for caller in &blocks {
+            let Some(fb_node) = self.find_function_block_node(caller) else {
+                continue;
+            };
+
+            for invocation in fb_node.descendants().filter(|n| n.has_tag_name("function-block-invocation")) {
+                let Some(callee) = Self::invoked_block(&fb_node, &invocation) else {
+                    continue;
+                };
+
+                let call = BlockCall { caller: caller.clone(), callee };
+                if blocks.contains(&call.callee) && !calls.contains(&call) {
+                    calls.push(call);
+                }
+            }
+        }
+
+        calls
+    }
+
+    /// Type named in the invocation itself, else the declared type of the invoked
+    /// instance, else the invoked name as-is
+    fn invoked_block(fb_node: &Node, invocation: &Node) -> Option<String> {
+        if let Some(type_name) = invocation.descendants()
+            .find(|n| n.has_tag_name("derived-function-block-name"))
+            .and_then(|n| n.text()) {
+            return Some(type_name.to_string());
+        }
+
+        let instance = invocation.descendants()
+            .find(|n| n.is_element() && n.text().is_some_and(|t| !t.trim().is_empty()))?
+            .text()?
+            .trim();
+
+        let declared_type = fb_node.descendants()
+            .filter(|decl| !decl.ancestors().any(|n| n == *invocation))
+            .find(|decl| {
+                decl.children().any(|c| c.is_element() && c.text() == Some(instance))
+                    && decl.children().any(|c| c.has_tag_name("derived-function-block-name"))
+            })
+            .and_then(|decl| decl.children().find(|c| c.has_tag_name("derived-function-block-name")))
+            .and_then(|n| n.text());
+
+        Some(declared_type.unwrap_or(instance).to_string())
+    }
+
     pub fn extract_function_block(&self, name: &str) -> Result<FunctionBlockData> {
         let fb_node = self.find_function_block_node(name)
             .ok_or_else(|| FsmError::FunctionBlockNotFound(name.to_string()))?;
 
-        let case_stmt = self.find_case_statement(&fb_node)
-            .ok_or_else(|| FsmError::NoCaseStatement(name.to_string()))?;
+        let mut data = match self.find_case_statement(&fb_node) {
+            Some(case_stmt) => self.extract_case_block(name, &fb_node, &case_stmt)?,
+            None => match fb_node.descendants().find(|n| n.has_tag_name("sequential-function-chart")) {
+                Some(sfc) => self.extract_sfc(name, &sfc),
+                None => return Err(FsmError::NoCaseStatement(name.to_string()).into()),
+            },
+        };
+        data.declarations = Self::extract_declarations(&fb_node);
 
-        let case_variable = self.extract_case_variable(&case_stmt)?;
-        let case_elements = self.extract_case_elements(&case_stmt)?;
+        Ok(data)
+    }
+
+    /// Names declared in the block's `VAR*` sections (`input-declarations`,
+    /// `var-declarations`, `temp-var-decls`, ...), instances included
+    fn extract_declarations(fb_node: &Node) -> Declarations {
+        let mut declarations = Declarations::default();
+        let sections = fb_node.descendants().filter(|n| {
+            let tag = n.tag_name().name();
+            tag.ends_with("-declarations") || tag.ends_with("-decls")
+        });
+
+        for section in sections {
+            let is_input = section.has_tag_name("input-declarations") || section.has_tag_name("input-output-declarations");
+            let names = section.descendants()
+                .filter(|n| n.has_tag_name("variable-name") || n.has_tag_name("fb-name"))
+                .filter_map(|n| n.text())
+                .map(str::trim);
+
+            for name in names {
+                if is_input && !declarations.inputs.iter().any(|n| n == name) {
+                    declarations.inputs.push(name.to_string());
+                }
+                if !declarations.variables.iter().any(|n| n == name) {
+                    declarations.variables.push(name.to_string());
+                }
+            }
+        }
+
+        declarations
+    }
+
+    /// Data of the machine `case_stmt` switches, with aliases and IFs outside the
+    /// CASE looked for in `scope` (the block, or the outer state of a nested CASE)
+    fn extract_case_block(&self, name: &str, scope: &Node, case_stmt: &Node) -> Result<FunctionBlockData> {
+        let case_variable = self.extract_case_variable(case_stmt)?;
+        let case_elements = self.extract_case_elements(name, case_stmt)?;
+        let (state_aliases, state_source) = self.extract_state_aliases(scope, case_stmt, &case_variable);
+
+        let mut state_variables = vec![case_variable.clone()];
+        state_variables.extend(state_aliases.iter().cloned());
+        let outer_statements = self.extract_outer_statements(scope, case_stmt, &state_variables);
 
         Ok(FunctionBlockData {
             name: name.to_string(),
             case_variable,
+            state_aliases,
+            state_source,
             case_elements,
+            outer_statements,
+            initial_state: None,
+            declarations: Declarations::default(),
         })
     }
 
-    fn find_function_block_node(&self, name: &str) -> Option<Node> {
-        for node in self.document.descendants() {
+    /// Model an SFC as a CASE over its active step: each step becomes a case
+    /// element and each `TRANSITION FROM .. TO ..` an IF assigning the target step
+    fn extract_sfc(&self, name: &str, sfc: &Node) -> FunctionBlockData {
+        let step_names = |node: &Node| -> Vec<String> {
+            node.children()
+                .filter(|n| n.has_tag_name("step-name"))
+                .filter_map(|n| n.text())
+                .map(str::to_string)
+                .collect()
+        };
+
+        let mut case_elements: Vec<CaseElement> = Vec::new();
+        let mut initial_state = None;
+        for step in sfc.descendants().filter(|n| n.has_tag_name("step") || n.has_tag_name("initial-step")) {
+            let Some(state_id) = step_names(&step).into_iter().next() else {
+                continue;
+            };
+            if step.has_tag_name("initial-step") && initial_state.is_none() {
+                initial_state = Some(state_id.clone());
+            }
+            case_elements.push(CaseElement {
+                state_id,
+                if_statements: Vec::new(),
+                assignments: Vec::new(),
+                sub_machines: Vec::new(),
+            });
+        }
+
+        for transition in sfc.descendants().filter(|n| n.has_tag_name("transition")) {
+            // The first `steps` list is FROM, the second TO
+            let mut step_lists = transition.children().filter(|n| n.has_tag_name("steps"));
+            let sources = step_lists.next().map(|l| step_names(&l)).unwrap_or_default();
+            let targets = step_lists.next().map(|l| step_names(&l)).unwrap_or_default();
+
+            let condition = transition.children()
+                .find(|n| n.has_tag_name("transition-condition"))
+                .and_then(|c| self.extract_expression(&c).ok())
+                .unwrap_or_default();
+
+            for source in &sources {
+                let Some(element) = case_elements.iter_mut().find(|e| e.state_id == *source) else {
+                    continue;
+                };
+                for target in &targets {
+                    let chain = element.if_statements.len();
+                    element.if_statements.push(IfStatement {
+                        condition: condition.clone(),
+                        chain,
+                        assignments: vec![Assignment { variable: SFC_STEP_VARIABLE.to_string(), value: target.clone(), computed: false }],
+                    });
+                }
+            }
+        }
+
+        FunctionBlockData {
+            name: name.to_string(),
+            case_variable: SFC_STEP_VARIABLE.to_string(),
+            state_aliases: Vec::new(),
+            state_source: None,
+            case_elements,
+            outer_statements: Vec::new(),
+            initial_state,
+            declarations: Declarations::default(),
+        }
+    }
+
+    fn find_function_block_node(&self, name: &str) -> Option<Node<'_, '_>> {
+        for node in self.document().descendants() {
             let tag_name = node.tag_name().name();
             let current_name = if tag_name == "function-block-declaration" {
                 self.extract_function_block_name(&node)
@@ -76,10 +380,8 @@ impl XmlParser {
                 None
             };
 
-            if let Some(block_name) = current_name {
-                if block_name == name {
-                    return Some(node);
-                }
+            if current_name.as_deref() == Some(name) {
+                return Some(node);
             }
         }
         None
@@ -104,46 +406,174 @@ impl XmlParser {
             .find(|n| n.tag_name().name() == "case-statement")
     }
 
+    /// Variables copied both to and from the case variable by unconditional
+    /// statements outside the CASE (`tmp := state; CASE tmp OF ... END_CASE;
+    /// state := tmp`), along with the one copied into it before the CASE, which
+    /// keeps the state between scans. A one-way copy such as `prevState := state`
+    /// only records history and is no alias
+    fn extract_state_aliases(&self, fb_node: &Node, case_stmt: &Node, case_variable: &str) -> (Vec<String>, Option<String>) {
+        let mut copied_from = Vec::new();
+        let mut copied_to = Vec::new();
+        let mut loaded_before_case = None;
+
+        for node in fb_node.descendants() {
+            if node.tag_name().name() != "assignment-statement"
+                || Self::enclosing_if(&node).is_some()
+                || node.ancestors().any(|n| n == *case_stmt) {
+                continue;
+            }
+
+            let Some((target, source)) = Self::variable_copy(&node) else {
+                continue;
+            };
+            if target == case_variable && source != case_variable {
+                if node.range().start < case_stmt.range().start && loaded_before_case.is_none() {
+                    loaded_before_case = Some(source.clone());
+                }
+                copied_from.push(source);
+            } else if source == case_variable && target != case_variable {
+                copied_to.push(target);
+            }
+        }
+
+        let mut aliases: Vec<String> = Vec::new();
+        for other in copied_from {
+            if copied_to.contains(&other) && !aliases.contains(&other) {
+                aliases.push(other);
+            }
+        }
+        let source = loaded_before_case.filter(|source| aliases.contains(source));
+
+        (aliases, source)
+    }
+
+    /// `(target, source)` of an assignment whose right-hand side is a bare variable
+    fn variable_copy(assign_node: &Node) -> Option<(String, String)> {
+        let mut children = assign_node.children().filter(|n| n.is_element());
+        let target = children.next().and_then(|n| Self::variable_text(&n))?;
+        let expression = children.next().filter(|n| n.has_tag_name("expression"))?;
+
+        let mut operands = expression.children().filter(|n| n.is_element());
+        match (operands.next(), operands.next()) {
+            (Some(source), None) => Some((target, Self::variable_text(&source)?)),
+            _ => None,
+        }
+    }
+
+    fn is_variable(node: &Node) -> bool {
+        node.has_tag_name("variable-name") || node.has_tag_name("array-variable")
+    }
+
+    /// Name of a variable node; array accesses are kept whole (`states[axis]`)
+    /// so they compare as one opaque variable
+    fn variable_text(node: &Node) -> Option<String> {
+        if node.has_tag_name("variable-name") {
+            return node.text().map(str::to_string);
+        }
+        if !node.has_tag_name("array-variable") {
+            return None;
+        }
+
+        let mut children = node.children().filter(|n| n.is_element());
+        let base = Self::variable_text(&children.next()?)?;
+        let subscripts: Vec<String> = children
+            .flat_map(|list| list.children().filter(|n| n.has_tag_name("expression")))
+            .map(|subscript| Self::parse_expression_node(&subscript))
+            .collect();
+
+        Some(format!("{}[{}]", base, subscripts.join(", ")))
+    }
+
+    /// `node` belongs to an array access nested inside `within`
+    fn in_array_variable(node: &Node, within: &Node) -> bool {
+        node.ancestors()
+            .skip(1)
+            .take_while(|n| n != within)
+            .any(|n| n.has_tag_name("array-variable"))
+    }
+
+    /// IFs outside the CASE that assign a state variable, in document order
+    /// (e.g. `IF firstScan THEN state := 100` or `IF estop THEN state := 0`)
+    fn extract_outer_statements(&self, fb_node: &Node, case_stmt: &Node, state_variables: &[String]) -> Vec<IfStatement> {
+        let mut statements = Vec::new();
+        let mut chains = 0;
+
+        for node in fb_node.descendants() {
+            if node.tag_name().name() == "if-statement"
+                && Self::enclosing_if(&node).is_none()
+                && !node.ancestors().any(|n| n == *case_stmt) {
+                self.parse_if_statement(&node, "", &mut statements, &mut chains);
+            }
+        }
+
+        statements.retain(|stmt| stmt.assignments.iter().any(|a| state_variables.contains(&a.variable)));
+        statements
+    }
+
     fn extract_case_variable(&self, case_stmt: &Node) -> Result<String> {
         case_stmt.descendants()
-            .find(|n| n.tag_name().name() == "variable-name")
-            .and_then(|n| n.text())
-            .map(|s| s.to_string())
+            .find(Self::is_variable)
+            .and_then(|n| Self::variable_text(&n))
             .ok_or_else(|| FsmError::XmlParse("Case variable not found".to_string()).into())
     }
 
-    fn extract_case_elements(&self, case_stmt: &Node) -> Result<Vec<CaseElement>> {
+    fn extract_case_elements(&self, block_name: &str, case_stmt: &Node) -> Result<Vec<CaseElement>> {
         let mut elements = Vec::new();
 
         for node in case_stmt.descendants() {
-            if node.tag_name().name() == "case-element" {
-                if let Ok(element) = self.parse_case_element(&node) {
-                    elements.push(element);
-                }
+            if node.tag_name().name() == "case-element"
+                && Self::enclosing_case(&node) == Some(*case_stmt)
+                && let Ok(element) = self.parse_case_element(block_name, &node) {
+                elements.push(element);
             }
         }
 
         Ok(elements)
     }
 
-    fn parse_case_element(&self, element_node: &Node) -> Result<CaseElement> {
+    fn parse_case_element(&self, block_name: &str, element_node: &Node) -> Result<CaseElement> {
         let state_id = self.extract_state_id(element_node)?;
         let if_statements = self.extract_if_statements(element_node)?;
+        let own_case = Self::enclosing_case(element_node);
+
+        // Statements outside any IF of the element run on every scan in the state
+        let assignments = element_node.descendants()
+            .filter(|n| n.tag_name().name() == "assignment-statement")
+            .filter(|n| Self::enclosing_case(n) == own_case)
+            .filter(|n| Self::enclosing_if(n).is_none_or(|outer| !outer.ancestors().any(|a| a == *element_node)))
+            .filter_map(|n| self.parse_assignment(&n).ok())
+            .collect();
+
+        // A CASE inside the state is a sub-machine running while in it
+        let sub_machines = element_node.descendants()
+            .filter(|n| n.tag_name().name() == "case-statement" && Self::enclosing_case(n) == own_case)
+            .filter_map(|inner| {
+                self.extract_case_block(&format!("{}.{}", block_name, state_id), element_node, &inner).ok()
+            })
+            .collect();
 
         Ok(CaseElement {
             state_id,
             if_statements,
+            assignments,
+            sub_machines,
         })
     }
 
+    /// Nearest CASE statement `node` sits in
+    fn enclosing_case<'a, 'input>(node: &Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+        node.ancestors()
+            .skip(1)
+            .find(|n| n.tag_name().name() == "case-statement")
+    }
+
     fn extract_state_id(&self, element_node: &Node) -> Result<String> {
         for node in element_node.descendants() {
             if node.tag_name().name() == "case-list-element" {
                 for child in node.descendants() {
-                    if child.tag_name().name() == "integer-literal" {
-                        if let Some(text) = child.text() {
-                            return Ok(text.to_string());
-                        }
+                    if child.tag_name().name() == "integer-literal"
+                        && let Some(text) = child.text() {
+                        return Ok(text.to_string());
                     }
                 }
             }
@@ -153,42 +583,142 @@ impl XmlParser {
 
     fn extract_if_statements(&self, element_node: &Node) -> Result<Vec<IfStatement>> {
         let mut statements = Vec::new();
+        let mut chains = 0;
 
+        // Nested IFs are reached through their outermost IF; those of a nested
+        // CASE belong to its sub-machine
+        let own_case = Self::enclosing_case(element_node);
         for node in element_node.descendants() {
-            if node.tag_name().name() == "if-statement" {
-                if let Ok(stmt) = self.parse_if_statement(&node) {
-                    statements.push(stmt);
-                }
+            if node.tag_name().name() == "if-statement"
+                && Self::enclosing_case(&node) == own_case
+                && Self::enclosing_if(&node).is_none_or(|outer| !outer.ancestors().any(|n| n == *element_node)) {
+                self.parse_if_statement(&node, "", &mut statements, &mut chains);
             }
         }
 
         Ok(statements)
     }
 
-    fn parse_if_statement(&self, if_node: &Node) -> Result<IfStatement> {
-        let condition = self.extract_expression(if_node)?;
-        let assignments = self.extract_assignments(if_node)?;
+    /// Parse `if_node` under `outer_condition`, then recurse into the IFs nested in
+    /// its body so their assignments are guarded by both conditions
+    ///
+    /// ELSIF and ELSE branches are guarded by the negation of every condition
+    /// before them, so `IF a THEN .. ELSE ..` yields `a` and `NOT (a)`; they all
+    /// share the next number from `chains`
+    fn parse_if_statement(&self, if_node: &Node, outer_condition: &str, statements: &mut Vec<IfStatement>, chains: &mut usize) {
+        let Ok(own_condition) = self.extract_expression(if_node) else {
+            return;
+        };
+        let chain = *chains;
+        *chains += 1;
+
+        let mut branches = vec![(None, own_condition.clone())];
+        let mut preceding = vec![own_condition];
+        for clause in if_node.children().filter(|n| n.has_tag_name("elsif-clause") || n.has_tag_name("else-clause")) {
+            let negated: Vec<String> = preceding.iter().map(|c| format!("NOT ({})", c)).collect();
+            let mut condition = negated.join(" AND ");
+
+            if clause.has_tag_name("elsif-clause") {
+                let Ok(own) = self.extract_expression(&clause) else {
+                    continue;
+                };
+                condition = Self::conjoin(&condition, &own);
+                preceding.push(own);
+            }
+            branches.push((Some(clause), condition));
+        }
 
-        Ok(IfStatement {
-            condition,
-            assignments,
-        })
+        for (clause, branch_condition) in branches {
+            let condition = Self::conjoin(outer_condition, &branch_condition);
+
+            if let Ok(assignments) = self.extract_assignments(if_node, clause) {
+                statements.push(IfStatement {
+                    condition: condition.clone(),
+                    chain,
+                    assignments,
+                });
+            }
+
+            for node in if_node.descendants().skip(1) {
+                if node.tag_name().name() == "if-statement"
+                    && Self::enclosing_if(&node) == Some(*if_node)
+                    && Self::enclosing_case(&node) == Self::enclosing_case(if_node)
+                    && Self::branch_clause(&node, if_node) == clause {
+                    self.parse_if_statement(&node, &condition, statements, chains);
+                }
+            }
+        }
+    }
+
+    /// ELSIF/ELSE clause of `if_node` containing `node`, `None` for its THEN branch
+    fn branch_clause<'a, 'input>(node: &Node<'a, 'input>, if_node: &Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+        node.ancestors()
+            .skip(1)
+            .take_while(|n| n != if_node)
+            .find(|n| {
+                (n.has_tag_name("elsif-clause") || n.has_tag_name("else-clause")) && n.parent() == Some(*if_node)
+            })
+    }
+
+    fn enclosing_if<'a, 'input>(node: &Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+        node.ancestors()
+            .skip(1)
+            .find(|n| n.tag_name().name() == "if-statement")
+    }
+
+    fn conjoin(outer: &str, inner: &str) -> String {
+        let wrap = |c: &str| if c.contains(" OR ") { format!("({})", c) } else { c.to_string() };
+
+        match (outer.is_empty(), inner.is_empty()) {
+            (true, _) => inner.to_string(),
+            (false, true) => outer.to_string(),
+            (false, false) => format!("{} AND {}", wrap(outer), wrap(inner)),
+        }
     }
 
     fn extract_expression(&self, node: &Node) -> Result<String> {
+        // Expressions that merely wrap a literal are values (e.g. an assignment's
+        // right-hand side), not the guard we're after
         if let Some(expr_node) = node.descendants()
-            .find(|n| n.tag_name().name() == "expression") {
-            Ok(self.parse_expression_node(&expr_node))
+            .find(|n| n.tag_name().name() == "expression" && !Self::is_literal_wrapper(n)) {
+            Ok(Self::parse_expression_node(&expr_node))
         } else {
             Ok(String::new())
         }
     }
 
-    fn parse_expression_node(&self, expr_node: &Node) -> String {
+    fn is_literal(node: &Node) -> bool {
+        node.tag_name()
+            .name()
+            .strip_suffix("-literal")
+            .is_some_and(|kind| LITERAL_KINDS.contains(&kind))
+    }
+
+    /// Text of a literal, including text nested in children such as the
+    /// `<duration>` of a `<time-literal>`
+    fn literal_text(node: &Node) -> Option<String> {
+        let text: String = node.descendants()
+            .filter(|n| n.is_text())
+            .filter_map(|n| n.text())
+            .collect();
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn is_literal_wrapper(node: &Node) -> bool {
+        let mut children = node.children().filter(|n| n.is_element());
+        matches!((children.next(), children.next()), (Some(child), None) if Self::is_literal(&child))
+    }
+
+    fn parse_expression_node(expr_node: &Node) -> String {
         let mut result = String::new();
         let mut in_not = false;
 
         for node in expr_node.descendants() {
+            if Self::in_array_variable(&node, expr_node) {
+                continue;
+            }
+
             match node.tag_name().name() {
                 "logical-not" => in_not = true,
                 "logical-and" => result.push_str(" AND "),
@@ -201,18 +731,18 @@ impl XmlParser {
                 "greater-or-equal" => result.push_str(" >= "),
                 "adding" => result.push_str(" + "),
                 "subtracting" => result.push_str(" - "),
-                "variable-name" => {
-                    if let Some(text) = node.text() {
+                "variable-name" | "array-variable" => {
+                    if let Some(text) = Self::variable_text(&node) {
                         if in_not {
                             result.push_str("NOT ");
                             in_not = false;
                         }
-                        result.push_str(text);
+                        result.push_str(&text);
                     }
                 },
-                "integer-literal" | "boolean-literal" => {
-                    if let Some(text) = node.text() {
-                        result.push_str(text);
+                _ if Self::is_literal(&node) => {
+                    if let Some(text) = Self::literal_text(&node) {
+                        result.push_str(&text);
                     }
                 },
                 _ => {}
@@ -222,14 +752,16 @@ impl XmlParser {
         result.trim().to_string()
     }
 
-    fn extract_assignments(&self, if_node: &Node) -> Result<Vec<Assignment>> {
+    fn extract_assignments(&self, if_node: &Node, clause: Option<Node>) -> Result<Vec<Assignment>> {
         let mut assignments = Vec::new();
 
         for node in if_node.descendants() {
-            if node.tag_name().name() == "assignment-statement" {
-                if let Ok(assignment) = self.parse_assignment(&node) {
-                    assignments.push(assignment);
-                }
+            if node.tag_name().name() == "assignment-statement"
+                && Self::enclosing_if(&node) == Some(*if_node)
+                && Self::enclosing_case(&node) == Self::enclosing_case(if_node)
+                && Self::branch_clause(&node, if_node) == clause
+                && let Ok(assignment) = self.parse_assignment(&node) {
+                assignments.push(assignment);
             }
         }
 
@@ -237,44 +769,204 @@ impl XmlParser {
     }
 
     fn parse_assignment(&self, assign_node: &Node) -> Result<Assignment> {
-        let variable = assign_node.descendants()
-            .find(|n| n.tag_name().name() == "variable-name")
-            .and_then(|n| n.text())
-            .unwrap_or("")
-            .to_string();
+        let target = assign_node.descendants().find(Self::is_variable);
+        let variable = target
+            .and_then(|n| Self::variable_text(&n))
+            .unwrap_or_default();
+
+        // An expression over anything but literals is only known at runtime;
+        // constant arithmetic such as `10 + 5` is folded
+        let expression = assign_node.children()
+            .filter(|n| n.is_element())
+            .nth(1)
+            .filter(|n| n.has_tag_name("expression") && !Self::is_literal_wrapper(n))
+            .map(|n| Self::parse_expression_node(&n));
+        if let Some(expression) = expression {
+            return Ok(match Self::fold_constant(&expression) {
+                Some(value) => Assignment { variable, value: value.to_string(), computed: false },
+                None => Assignment { variable, value: expression, computed: true },
+            });
+        }
 
         let value = assign_node.descendants()
-            .find(|n| n.tag_name().name() == "integer-literal" ||
-                n.tag_name().name() == "boolean-literal")
-            .and_then(|n| n.text())
-            .unwrap_or("")
-            .to_string();
+            .find(|n| Self::is_literal(n) && !target.is_some_and(|t| n.ancestors().any(|a| a == t)))
+            .and_then(|n| Self::literal_text(&n))
+            .unwrap_or_default();
 
-        Ok(Assignment { variable, value })
+        Ok(Assignment { variable, value, computed: false })
     }
+
+    /// Value of a sum like `10 + 5 - 1` over integer literals
+    fn fold_constant(expression: &str) -> Option<i64> {
+        let mut tokens = expression.split_whitespace();
+        let mut total: i64 = tokens.next()?.parse().ok()?;
+        while let Some(op) = tokens.next() {
+            let operand: i64 = tokens.next()?.parse().ok()?;
+            total = match op {
+                "+" => total.checked_add(operand)?,
+                "-" => total.checked_sub(operand)?,
+                _ => return None,
+            };
+        }
+        Some(total)
+    }
+}
+
+/// What `list` shows of a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockSummary {
+    pub name: String,
+    /// `None` for a block without a CASE statement or SFC
+    pub case_variable: Option<String>,
+    /// States with a CASE branch or assigned by one (or SFC steps)
+    pub state_count: usize,
+}
+
+/// Body of block `caller` invokes an instance of block `callee`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCall {
+    pub caller: String,
+    pub callee: String,
 }
 
 #[derive(Debug)]
 pub struct FunctionBlockData {
     pub name: String,
     pub case_variable: String,
+    /// Other variables holding the same state as `case_variable`
+    pub state_aliases: Vec<String>,
+    /// Alias `case_variable` is loaded from before the CASE, when the CASE
+    /// switches on a temp copy
+    pub state_source: Option<String>,
     pub case_elements: Vec<CaseElement>,
+    pub outer_statements: Vec<IfStatement>,
+    /// Entry state declared by the source itself (an SFC's initial step)
+    pub initial_state: Option<String>,
+    /// Variables of the block's interface and locals; empty for nested machines
+    pub declarations: Declarations,
+}
+
+/// Variables declared by a function block
+#[derive(Debug, Default)]
+pub struct Declarations {
+    /// `VAR_INPUT` and `VAR_IN_OUT` variables
+    pub inputs: Vec<String>,
+    /// Every declared variable, inputs included
+    pub variables: Vec<String>,
 }
 
 #[derive(Debug)]
 pub struct CaseElement {
     pub state_id: String,
     pub if_statements: Vec<IfStatement>,
+    /// Unconditional assignments directly in the element
+    pub assignments: Vec<Assignment>,
+    /// Machines of CASE statements nested in the element, named `block.state`
+    pub sub_machines: Vec<FunctionBlockData>,
 }
 
 #[derive(Debug)]
 pub struct IfStatement {
     pub condition: String,
+    /// Numbers the IF statement this is a THEN/ELSIF/ELSE branch of, so the
+    /// branches of one IF/ELSIF chain share it
+    pub chain: usize,
     pub assignments: Vec<Assignment>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Assignment {
     pub variable: String,
+    /// Literal value, or the expression text when `computed`
     pub value: String,
-}
\ No newline at end of file
+    /// The value is an expression only known at runtime (e.g. `base + offset`)
+    pub computed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REAL_LITERAL_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>ConveyorFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>speed</variable-name></expression><greater-than/><expression><real-literal>1.5</real-literal></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    #[test]
+    fn test_real_literal_inside_expression() {
+        for preprocessor in [XmlPreprocessor::default(), XmlPreprocessor::empty()] {
+            let parser = XmlParser::from_content(REAL_LITERAL_XML, &preprocessor).unwrap();
+            let fb = parser.extract_function_block("ConveyorFB").unwrap();
+
+            let if_stmt = &fb.case_elements[0].if_statements[0];
+            assert_eq!(if_stmt.condition, "speed > 1.5");
+            assert_eq!(if_stmt.assignments[0].variable, "state");
+            assert_eq!(if_stmt.assignments[0].value, "20");
+        }
+    }
+
+    const STRING_LITERAL_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>ModeFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>mode</variable-name></expression><equal/><expression><string-literal>AUTO</string-literal></expression></expression>
+<assignment-statement><variable-name>msg</variable-name><expression><string-literal>'DONE'</string-literal></expression></assignment-statement>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+    #[test]
+    fn test_string_literal_in_guard_and_assignment() {
+        for preprocessor in [XmlPreprocessor::default(), XmlPreprocessor::empty()] {
+            let parser = XmlParser::from_content(STRING_LITERAL_XML, &preprocessor).unwrap();
+            let fb = parser.extract_function_block("ModeFB").unwrap();
+
+            let if_stmt = &fb.case_elements[0].if_statements[0];
+            assert_eq!(if_stmt.condition, "mode = AUTO");
+            assert_eq!(if_stmt.assignments[0], Assignment { variable: "msg".to_string(), value: "'DONE'".to_string(), computed: false });
+        }
+    }
+
+    #[test]
+    fn test_parser_owns_its_content() {
+        fn owned<T: 'static>(value: T) -> T {
+            value
+        }
+
+        let content = REAL_LITERAL_XML.to_string();
+        let parser = owned(XmlParser::from_content(&content, &XmlPreprocessor::empty()).unwrap());
+        drop(content);
+
+        // The document borrows the parser's own buffer, freed along with it
+        assert_eq!(parser.document().input_text().as_ptr(), parser.xml.borrow_owner().as_ptr());
+        assert_eq!(parser.find_function_blocks(), ["ConveyorFB"]);
+    }
+
+    #[test]
+    fn test_rewrite_rule_from_str() {
+        let rule: RewriteRule = "<a>=<b>".parse().unwrap();
+        assert_eq!(rule, RewriteRule::new("<a>", "<b>"));
+        assert!("no-separator".parse::<RewriteRule>().is_err());
+
+        let preprocessor = XmlPreprocessor::empty().with_rules([rule]);
+        assert_eq!(preprocessor.apply("<a></a>"), "<b></a>");
+    }
+}
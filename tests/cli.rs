@@ -0,0 +1,274 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use predicates::str::contains;
+use std::io::Write;
+
+const PUMP_XML: &str = r#"<project>
+<function-block-declaration>
+<derived-function-block-name>PumpFB</derived-function-block-name>
+<case-statement>
+<expression><variable-name>state</variable-name></expression>
+<case-element>
+<case-list-element><integer-literal>10</integer-literal></case-list-element>
+<if-statement>
+<expression><variable-name>start</variable-name></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>20</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+<case-element>
+<case-list-element><integer-literal>20</integer-literal></case-list-element>
+<if-statement>
+<expression><expression><variable-name>level</variable-name></expression><greater-than/><expression><integer-literal>5</integer-literal></expression></expression>
+<assignment-statement><variable-name>state</variable-name><expression><integer-literal>30</integer-literal></expression></assignment-statement>
+</if-statement>
+</case-element>
+</case-statement>
+</function-block-declaration>
+</project>"#;
+
+#[test]
+fn test_analyze_show_signatures() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(PUMP_XML.as_bytes()).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .arg("analyze")
+        .arg(file.path())
+        .arg("--show-signatures")
+        .assert()
+        .success()
+        .stdout(contains("State Signatures:"))
+        .stdout(contains("State 20: start = TRUE"))
+        .stdout(contains("State 30: level > 5 AND start = TRUE"));
+}
+
+#[test]
+fn test_analyze_output_needs_stats_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("pump.xml"), PUMP_XML).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["analyze", "pump.xml", "-o", "report.txt"])
+        .assert()
+        .failure()
+        .stderr(contains("--output needs --format stats-csv"));
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["analyze", "pump.xml", "--format", "stats-csv", "-o", "stats.csv"])
+        .assert()
+        .success();
+    assert!(std::fs::read_to_string(dir.path().join("stats.csv")).unwrap().contains("PumpFB"));
+}
+
+#[test]
+fn test_config_format_default_and_override() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("pump.xml"), PUMP_XML).unwrap();
+    std::fs::write(dir.path().join("fsm-extractor.toml"), "format = \"json\"\n").unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .current_dir(dir.path())
+        .args(["extract", "pump.xml"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("{"));
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .current_dir(dir.path())
+        .args(["extract", "pump.xml", "--format", "markdown"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("#"));
+}
+
+#[test]
+fn test_list_blocks() {
+    let valve_block = PUMP_XML["<project>\n".len()..]
+        .replace("PumpFB", "ValveFB")
+        .replace("<variable-name>state</variable-name>", "<variable-name>step</variable-name>");
+    let two_blocks = PUMP_XML.replace("</project>", &valve_block);
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(two_blocks.as_bytes()).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .arg("list")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(contains("PumpFB  case: state  states: 3"))
+        .stdout(contains("ValveFB  case: step  states: 3"));
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .args(["list", "--format", "json"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(contains("\"name\": \"ValveFB\""))
+        .stdout(contains("\"case_variable\": \"step\""));
+}
+
+#[test]
+fn test_path_length_limit_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("pump.xml"), PUMP_XML).unwrap();
+    std::fs::write(dir.path().join("fsm-extractor.toml"), "max-path-length = 1\n").unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .current_dir(dir.path())
+        .args(["analyze", "pump.xml", "--show-signatures"])
+        .assert()
+        .success()
+        .stdout(contains("State 20: start = TRUE"))
+        .stdout(contains("State 30:").not());
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .current_dir(dir.path())
+        .args(["analyze", "pump.xml", "--show-signatures", "--max-path-length", "5"])
+        .assert()
+        .success()
+        .stdout(contains("State 30: level > 5 AND start = TRUE"));
+}
+
+#[test]
+fn test_duplicates_reported_from_json() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("pump.xml"), PUMP_XML).unwrap();
+
+    let output = cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["extract", "pump.xml", "--format", "json"])
+        .output()
+        .unwrap();
+    let mut fsm: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let transitions = fsm["function_blocks"][0]["transitions"].as_array_mut().unwrap();
+    transitions.push(transitions[0].clone());
+    std::fs::write(dir.path().join("pump.json"), fsm.to_string()).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .env("NO_COLOR", "1")
+        .current_dir(dir.path())
+        .args(["analyze", "pump.json", "--from-json", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Duplicate transitions found:"))
+        .stdout(contains("10 → 20 when start repeats an earlier transition"));
+}
+
+#[test]
+fn test_sql_output_honours_prune_guards() {
+    let xml = PUMP_XML.replace(
+        "<expression><variable-name>start</variable-name></expression>",
+        "<expression><expression><integer-literal>1</integer-literal></expression><equal/><expression><integer-literal>1</integer-literal></expression><logical-and/><expression><variable-name>start</variable-name></expression></expression>",
+    );
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(xml.as_bytes()).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .args(["extract", "--format", "sql"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(contains("WHERE (1 = 1 AND start = TRUE);"));
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .args(["extract", "--format", "sql", "--prune-guards"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(contains("WHERE (start = TRUE);"));
+}
+
+#[test]
+fn test_split_by_block_keeps_colliding_names_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = &PUMP_XML[PUMP_XML.find("<function-block-declaration>").unwrap()..PUMP_XML.find("</project>").unwrap()];
+    let xml = format!(
+        "<project>{}{}</project>",
+        block.replace("PumpFB", "Pump:FB"),
+        block.replace("PumpFB", "Pump_FB"),
+    );
+    std::fs::write(dir.path().join("plant.xml"), xml).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["extract", "plant.xml", "--format", "json", "--split-by-block", "-o", "out"])
+        .assert()
+        .success();
+
+    let mut files: Vec<_> = std::fs::read_dir(dir.path().join("out"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["Pump_FB.json", "Pump_FB_2.json"]);
+}
+
+#[test]
+fn test_batch_same_stem_inputs_get_separate_outputs() {
+    let dir = tempfile::tempdir().unwrap();
+    for sub in ["a", "b"] {
+        std::fs::create_dir(dir.path().join(sub)).unwrap();
+        let xml = PUMP_XML.replace("PumpFB", &format!("Pump{}", sub.to_uppercase()));
+        std::fs::write(dir.path().join(sub).join("pump.xml"), xml).unwrap();
+    }
+    std::fs::create_dir(dir.path().join("out")).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["batch", "a/pump.xml", "b/pump.xml", "--format", "json", "-o", "out", "--jobs", "2", "--manifest-out", "m.json"])
+        .assert()
+        .success();
+
+    let mut blocks: Vec<String> = ["pump.json", "pump_2.json"]
+        .iter()
+        .map(|name| {
+            let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.path().join("out").join(name)).unwrap()).unwrap();
+            json["function_blocks"][0]["name"].as_str().unwrap().to_string()
+        })
+        .collect();
+    blocks.sort();
+    assert_eq!(blocks, ["PumpA", "PumpB"]);
+
+    let manifest = std::fs::read_to_string(dir.path().join("m.json")).unwrap();
+    assert!(manifest.contains("pump.json") && manifest.contains("pump_2.json"));
+}
+
+#[test]
+fn test_split_by_block_manifest_lists_every_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = &PUMP_XML[PUMP_XML.find("<function-block-declaration>").unwrap()..PUMP_XML.find("</project>").unwrap()];
+    let xml = format!("<project>{}{}</project>", block, block.replace("PumpFB", "ValveFB"));
+    std::fs::write(dir.path().join("plant.xml"), xml).unwrap();
+
+    cargo_bin_cmd!("plc-fsm-analyzer")
+        .current_dir(dir.path())
+        .args(["extract", "plant.xml", "--format", "json", "--split-by-block", "-o", "out", "--manifest-out", "m.json"])
+        .assert()
+        .success();
+
+    let manifest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.path().join("m.json")).unwrap()).unwrap();
+    let mut listed: Vec<String> = manifest
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["file"].as_str().unwrap().to_string())
+        .collect();
+    listed.sort();
+
+    let mut written: Vec<String> = std::fs::read_dir(dir.path().join("out"))
+        .unwrap()
+        .map(|entry| format!("out/{}", entry.unwrap().file_name().into_string().unwrap()))
+        .collect();
+    written.sort();
+
+    assert_eq!(written, ["out/PumpFB.json", "out/ValveFB.json"]);
+    assert_eq!(listed, written);
+}